@@ -2,6 +2,9 @@ import "./mahjong_hai";
 import "./mahjong_tehai";
 import "./mahjong_furo";
 
+// 入力状態 (手牌と Env の JSON) を保存する localStorage のキー
+const STATE_KEY = "mjview-state";
+
 class EnvInput {
   constructor(wasmMod, form, tehaiElement, outputElement) {
     this._wasmMod = wasmMod;
@@ -27,19 +30,19 @@ class EnvInput {
     this._tehai = null;
     let { Env } = wasmMod;
     this._env = new Env();
+    this._restoring = false;
 
-    for (let [name, value] of new URLSearchParams(location.search)) {
-      let element = this._form[name];
-      if (element.type == "checkbox") {
-        element.checked = true;
+    let state = this._loadInitialState();
+    try {
+      if (state === null) {
+        this._applyFormToEnv();
       } else {
-        element.value = value;
+        this._restoreState(state);
       }
-    }
-
-    for (let element of form.elements) {
-      this._onChange(element);
-      this._onInput(element);
+    } catch (_err) {
+      // 保存された状態が読み込めない場合はフォームの初期値を使う
+      localStorage.removeItem(STATE_KEY);
+      this._applyFormToEnv();
     }
 
     form.addEventListener("submit", (e) => {
@@ -48,11 +51,153 @@ class EnvInput {
     });
     form.addEventListener("change", (e) => this._onChange(e.target));
     form.addEventListener("input", (e) => this._onInput(e.target));
+
+    let stateElement = form["state"];
+    form["export-state"].addEventListener("click", () => {
+      stateElement.value = JSON.stringify(this._toState(), null, 2);
+    });
+    form["import-state"].addEventListener("click", () => {
+      let messageElement = this._messageElementMap.get("state");
+      try {
+        this._restoreState(JSON.parse(stateElement.value));
+        this._setOKMessage(messageElement);
+      } catch (err) {
+        this._setErrorMessage(messageElement, err.toString());
+      }
+    });
+  }
+
+  // フォームの入力をすべて Env に反映する
+  _applyFormToEnv() {
+    for (let element of this._form.elements) {
+      this._onChange(element);
+      this._onInput(element);
+    }
+  }
+
+  // 起動時の入力状態
+  //
+  // 以前の形式のリンク (`?tehai=...` などの URL パラメータ) はフォームに読み込み、URL から取り除く。
+  _loadInitialState() {
+    let params = new URLSearchParams(location.search);
+    if ([...params.keys()].length > 0) {
+      for (let [name, value] of params) {
+        let element = this._form[name];
+        if (element === undefined) {
+          continue;
+        }
+        if (element.type == "checkbox") {
+          element.checked = true;
+        } else {
+          element.value = value;
+        }
+      }
+      history.replaceState(null, null, location.pathname);
+      return null;
+    }
+
+    let json = localStorage.getItem(STATE_KEY);
+    if (json === null) {
+      return null;
+    }
+    try {
+      return JSON.parse(json);
+    } catch (_err) {
+      return null;
+    }
+  }
+
+  // 入力状態 (`{ tehai, env }`) を `toJSON` で作る
+  _toState() {
+    let tehai =
+      this._tehai !== null ? this._tehai.toJSON() : this._form["tehai"].value;
+    return { tehai, env: this._env.toJSON() };
+  }
+
+  _saveState() {
+    if (this._restoring) {
+      return;
+    }
+    localStorage.setItem(STATE_KEY, JSON.stringify(this._toState()));
+  }
+
+  // 入力状態を `fromJSON` で読み込み、フォームに反映する
+  _restoreState(state) {
+    let { Env, Tehai } = this._wasmMod;
+    let env = Env.fromJSON(state.env);
+    let json = env.toJSON();
+    let tehai = state.tehai ?? "";
+    try {
+      tehai = Tehai.fromJSON(tehai).toString();
+    } catch (_err) {
+      // 不正な手牌はそのまま入力欄に戻してエラーを表示する
+    }
+
+    let form = this._form;
+    form["tehai"].value = tehai;
+    form["bakaze"].value = json.bakaze;
+    form["jikaze"].value = json.jikaze;
+    form["richi"].value = json.richi ?? "";
+    for (let name of ["ippatsu", "rinshan", "haitei", "tenho", "renho"]) {
+      form[name].checked = json[name];
+    }
+    for (let name of ["dora", "uradora", "kawa", "minogashi"]) {
+      form[name].value = json[name].join("");
+    }
+    form["hoju"].value = json.hoju ?? "";
+    form["honba"].value = json.honba;
+    form["kyotaku"].value = json.kyotaku;
+    form["aotenjo"].checked = json.aotenjo;
+
+    let rules = json.rules;
+    form["rules"].value = this._presetName(rules);
+    form["kiriage"].checked = rules.kiriage_mangan;
+    form["local-yaku"].checked = rules.local_yaku;
+    form["renho-rule"].value = rules.renho;
+    let akadora = rules.akadora.join("/");
+    form["akadora"].value = akadora === "0/0/0" ? "0" : akadora;
+    form["sanma"].value = rules.sanma ?? "";
+
+    // フォームで表せないルールや他家の牌も含めて、読み込んだ Env をそのまま使う
+    this._restoring = true;
+    try {
+      this._applyFormToEnv();
+    } finally {
+      this._restoring = false;
+    }
+    this._env = env;
+    this._onInput(form["tehai"]);
+  }
+
+  // フォームで個別に選べない項目が一致するルールのプリセット名
+  _presetName(rules) {
+    let { Rules } = this._wasmMod;
+    let overridable = [
+      "kiriage_mangan",
+      "local_yaku",
+      "renho",
+      "akadora",
+      "sanma",
+    ];
+    let strip = (json) => {
+      let res = { ...json };
+      for (let key of overridable) {
+        delete res[key];
+      }
+      return JSON.stringify(res);
+    };
+    for (let option of this._form["rules"].options) {
+      if (strip(Rules.fromPreset(option.value).toJSON()) === strip(rules)) {
+        return option.value;
+      }
+    }
+    return "default";
   }
 
   _update() {
     this._outputElement.textContent = "";
     this._updateWarning();
+    this._saveState();
 
     let tehai = this._tehai;
     if (tehai === null || this._form.querySelector(".error") !== null) {
@@ -60,7 +205,7 @@ class EnvInput {
     }
 
     let comb = tehai
-      .toAgariCombinations()
+      .toAgariCombinationsWithEnv(this._env)
       .map((agari) => [agari, agari.judgeYaku(this._env)])
       .sort(([agariA, yakuA], [agariB, yakuB]) => {
         let c = yakuA.compare(yakuB);
@@ -74,6 +219,13 @@ class EnvInput {
     for (let [agari, yaku] of comb) {
       let header = document.createElement("dt");
       header.textContent = `${agari} (${yaku.name}${yaku.point}点 ${yaku.rank} ${yaku.fu}符)`;
+      let payments = yaku.payments.map((payment) => payment.toString());
+      if (payments.length > 0) {
+        header.textContent += ` [${payments.join(", ")}]`;
+      }
+      if (yaku.total !== yaku.point) {
+        header.textContent += ` 収入${yaku.total}点`;
+      }
       list.appendChild(header);
       let body = document.createElement("dd");
       let ul = document.createElement("ul");
@@ -83,13 +235,14 @@ class EnvInput {
         ul.appendChild(li);
       }
       body.appendChild(ul);
+      let fuDetail = document.createElement("p");
+      fuDetail.textContent = `符の内訳: ${yaku.fuDetail
+        .map(([name, fu]) => `${name} ${fu}符`)
+        .join(", ")}`;
+      body.appendChild(fuDetail);
       list.appendChild(body);
     }
     this._outputElement.appendChild(list);
-
-    let formData = new FormData(this._form);
-    let params = new URLSearchParams(formData);
-    history.replaceState(null, null, `?${params}`);
   }
 
   _updateWarning() {
@@ -108,7 +261,9 @@ class EnvInput {
       let set = new Set();
       for (let item of items) {
         let messageElement = this._messageElementMap.get(item);
-        set.add(messageElement);
+        if (messageElement !== undefined) {
+          set.add(messageElement);
+        }
       }
       for (let element of set.values()) {
         this._addWarningMessage(element, message);
@@ -128,6 +283,7 @@ class EnvInput {
       case "rinshan":
       case "haitei":
       case "tenho":
+      case "renho":
       case "aotenjo":
         this._env[target.name] = target.checked;
         break;
@@ -135,12 +291,48 @@ class EnvInput {
       case "jikaze":
         this._env[target.name] = Hai.fromStr(target.value);
         break;
+      case "hoju":
+        this._env.hoju = target.value;
+        break;
+      case "honba":
+      case "kyotaku":
+        this._env[target.name] = Math.max(0, parseInt(target.value, 10) || 0);
+        break;
+      case "rules":
+      case "kiriage":
+      case "local-yaku":
+      case "renho-rule":
+      case "akadora":
+      case "sanma":
+        this._updateRules();
+        break;
       default:
         break;
     }
     this._update();
   }
 
+  _updateRules() {
+    let { Rules } = this._wasmMod;
+    let rules = Rules.fromPreset(this._form["rules"].value);
+    if (this._form["kiriage"].checked) {
+      rules.kiriageMangan = true;
+    }
+    if (this._form["local-yaku"].checked) {
+      rules.localYaku = true;
+    }
+    if (this._form["renho-rule"].value !== "") {
+      rules.renho = this._form["renho-rule"].value;
+    }
+    if (this._form["akadora"].value !== "") {
+      rules.akadora = this._form["akadora"].value;
+    }
+    rules.sanma = this._form["sanma"].value;
+    this._env.rules = rules;
+    // 三人麻雀かどうかで手牌の妥当性が変わるため再度パースする
+    this._onInput(this._form["tehai"]);
+  }
+
   _addMessage(element, addClass, message, clear) {
     if (!element.classList.contains(addClass)) {
       element.textContent = "";
@@ -191,7 +383,7 @@ class EnvInput {
         this._tehaiElement.tehai = tehai;
         let { Tehai } = this._wasmMod;
         try {
-          this._tehai = Tehai.fromStr(tehai);
+          this._tehai = Tehai.fromStrWithRules(tehai, this._env.rules);
           target.setCustomValidity("");
           this._setOKMessage(messageElement);
         } catch (err) {
@@ -202,13 +394,24 @@ class EnvInput {
         break;
       }
       case "dora":
-      case "uradora": {
+      case "uradora":
+      case "kawa":
+      case "minogashi": {
         let messageElement = this._messageElementMap.get(target.name);
         try {
-          if (target.name === "dora") {
-            this._env.setDora(target.value);
-          } else {
-            this._env.setUradora(target.value);
+          switch (target.name) {
+            case "dora":
+              this._env.setDora(target.value);
+              break;
+            case "uradora":
+              this._env.setUradora(target.value);
+              break;
+            case "kawa":
+              this._env.setKawa(target.value);
+              break;
+            case "minogashi":
+              this._env.setMinogashi(target.value);
+              break;
           }
           target.setCustomValidity("");
           this._setOKMessage(messageElement);
@@ -67,6 +67,15 @@ class MahjongTehai extends HTMLElement {
       elem.appendChild(furoElem);
     }
 
+    if (res.nukidora.length > 0) {
+      let nukidoraElem = document.createElement("div");
+      nukidoraElem.classList.add("nukidora");
+      for (let nukidora of res.nukidora) {
+        nukidoraElem.appendChild(haiImage(nukidora.toImage()));
+      }
+      elem.appendChild(nukidoraElem);
+    }
+
     let agariHaiElem = document.createElement("div");
     agariHaiElem.classList.add("agari-hai");
     switch (res.agariHai.agari) {
@@ -123,6 +132,7 @@ const TEHAI_STYLE = `
   line-height: 1;
 }
 mj-furo,
+.nukidora,
 .jun-tehai,
 .agari-hai {
   display: table-cell;
@@ -132,7 +142,8 @@ mj-furo,
   float: left;
   margin-right: 5px;
 }
-mj-furo {
+mj-furo,
+.nukidora {
   float: right;
   margin-left: 5px;
 }
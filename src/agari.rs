@@ -8,10 +8,15 @@ use crate::{
     tehai::Tehai,
     yaku::Yaku,
 };
-use std::{cmp::Ordering, fmt};
+#[cfg(feature = "serde")]
+use serde::{ser::SerializeStruct, Serialize, Serializer};
+#[cfg(feature = "wasm")]
+use std::cmp::Ordering;
+use std::fmt;
+#[cfg(feature = "wasm")]
 use wasm_bindgen::prelude::*;
 
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Agari {
     tehai: Tehai,
@@ -68,8 +73,26 @@ impl fmt::Display for Agari {
     }
 }
 
+/// `mentsu` は純手牌の面子、`machi_mentsu` はそのうちあがり牌を含む面子の位置
+#[cfg(feature = "serde")]
+impl Serialize for Agari {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mentsu = self
+            .tehai_mentsu
+            .iter()
+            .map(|mentsu| mentsu.to_string())
+            .collect::<Vec<_>>();
+        let mut state = serializer.serialize_struct("Agari", 4)?;
+        state.serialize_field("tehai", &self.tehai)?;
+        state.serialize_field("mentsu", &mentsu)?;
+        state.serialize_field("machi_mentsu", &self.machi_mentsu_index)?;
+        state.serialize_field("machi", &self.machi)?;
+        state.end()
+    }
+}
+
 impl Agari {
-    pub(crate) fn new(
+    pub fn new(
         tehai: Tehai,
         tehai_mentsu: Vec<Mentsu>,
         machi: Machi,
@@ -201,140 +224,167 @@ impl Agari {
         }
     }
 
-    pub(crate) fn tehai(&self) -> &Tehai {
+    pub fn tehai(&self) -> &Tehai {
         &self.tehai
     }
 
-    pub(crate) fn tehai_mentsu(&self) -> &[Mentsu] {
+    pub fn tehai_mentsu(&self) -> &[Mentsu] {
         &self.tehai_mentsu
     }
 
-    pub(crate) fn machi(&self) -> Machi {
+    pub fn machi(&self) -> Machi {
         self.machi
     }
 
-    pub(crate) fn machi_mentsu(&self) -> Mentsu {
+    pub fn machi_mentsu(&self) -> Mentsu {
         self.tehai_mentsu[self.machi_mentsu_index]
     }
 
-    pub(crate) fn janto(&self) -> Option<Mentsu> {
+    pub fn janto(&self) -> Option<Mentsu> {
         self.janto_mentsu_index
             .and_then(|idx| self.tehai_mentsu.get(idx).copied())
     }
 
-    pub(crate) fn all_mentsu(&self) -> impl Iterator<Item = Mentsu> + '_ {
+    pub fn all_mentsu(&self) -> impl Iterator<Item = Mentsu> + '_ {
         self.tehai_mentsu()
             .iter()
             .copied()
             .chain(self.tehai().furo().iter().map(|furo| Mentsu::from(*furo)))
     }
 
-    pub(crate) fn num_hai(&self) -> usize {
+    pub fn num_hai(&self) -> usize {
         self.num_hai
     }
 
-    pub(crate) fn num_manzu(&self) -> usize {
+    pub fn num_manzu(&self) -> usize {
         self.num_manzu
     }
 
-    pub(crate) fn num_souzu(&self) -> usize {
+    pub fn num_souzu(&self) -> usize {
         self.num_souzu
     }
 
-    pub(crate) fn num_pinzu(&self) -> usize {
+    pub fn num_pinzu(&self) -> usize {
         self.num_pinzu
     }
 
-    pub(crate) fn num_jihai(&self) -> usize {
+    pub fn num_jihai(&self) -> usize {
         self.num_jihai
     }
 
-    pub(crate) fn num_yaochuhai(&self) -> usize {
+    pub fn num_yaochuhai(&self) -> usize {
         self.num_yaochuhai
     }
 
-    pub(crate) fn num_anshun(&self) -> usize {
+    pub fn num_anshun(&self) -> usize {
         self.num_anshun
     }
 
-    pub(crate) fn num_minshun(&self) -> usize {
+    pub fn num_minshun(&self) -> usize {
         self.num_minshun
     }
 
-    pub(crate) fn num_anko(&self) -> usize {
+    pub fn num_anko(&self) -> usize {
         self.num_anko
     }
 
-    pub(crate) fn num_minko(&self) -> usize {
+    pub fn num_minko(&self) -> usize {
         self.num_minko
     }
 
-    pub(crate) fn num_ankan(&self) -> usize {
+    pub fn num_ankan(&self) -> usize {
         self.num_ankan
     }
 
-    pub(crate) fn num_minkan(&self) -> usize {
+    pub fn num_minkan(&self) -> usize {
         self.num_minkan
     }
 
-    pub(crate) fn num_toitsu(&self) -> usize {
+    pub fn num_toitsu(&self) -> usize {
         self.num_toitsu
     }
 
-    pub(crate) fn num_single(&self) -> usize {
+    pub fn num_single(&self) -> usize {
         self.num_single
     }
 
-    pub(crate) fn compute_fu(&self, env: &Env) -> u32 {
+    /// 符と、その内訳を計算する
+    pub fn compute_fu_detail(&self, env: &Env) -> (u32, Vec<(String, u32)>) {
         let is_menzen = self.tehai.is_menzen();
         if self.tehai_mentsu.len() == 7 {
             // 七対子
-            return 25;
+            return (25, vec![("七対子".into(), 25)]);
         }
 
+        const FUTEI: u32 = 20; // 副底
+        let mut detail = vec![("副底".to_string(), FUTEI)];
+
         let is_ron = self.tehai.agari_hai().type_() == AgariType::Ron;
-        let tehai_mentsu = self
-            .tehai_mentsu
-            .iter()
-            .enumerate()
-            .map(|(idx, mentsu)| {
-                let is_menzen = !is_ron || self.machi_mentsu_index != idx;
-                mentsu.compute_fu(is_menzen, env)
-            })
-            .sum::<u32>();
-        let furo = self
-            .tehai
-            .furo()
-            .iter()
-            .map(|furo| furo.compute_fu(env))
-            .sum::<u32>();
+        let mut mentsu_fu = vec![];
+        for (idx, mentsu) in self.tehai_mentsu.iter().enumerate() {
+            let is_menzen = !is_ron || self.machi_mentsu_index != idx;
+            let fu = mentsu.compute_fu(is_menzen, env);
+            mentsu_fu.push((format!("{} {}", fu_name(mentsu, is_menzen), mentsu), fu));
+        }
+        for furo in self.tehai.furo() {
+            let mentsu = Mentsu::from(*furo);
+            let fu = furo.compute_fu(env);
+            mentsu_fu.push((
+                format!("{} {}", fu_name(&mentsu, furo.is_menzen()), furo),
+                fu,
+            ));
+        }
         let machi = self.machi.compute_fu();
+        mentsu_fu.push((format!("{}待ち", self.machi), machi));
 
-        if tehai_mentsu + furo + machi == 0 {
+        if mentsu_fu.iter().all(|(_, fu)| *fu == 0) {
             // 平和形
             if is_menzen {
                 if !is_ron {
                     // ツモ平和
-                    return 20;
+                    return (FUTEI, detail);
                 }
             } else {
                 // 食い平和
-                return 30;
+                detail.push(("食い平和形".into(), 10));
+                return (30, detail);
             }
         }
 
-        const FUTEI: u32 = 20; // 副底
-        let menzen_kafu = if is_ron && is_menzen { 10 } else { 0 };
-        let tsumofu = if is_ron { 0 } else { 2 };
-        let total = FUTEI + tehai_mentsu + furo + machi + menzen_kafu + tsumofu;
-        (total + 9) / 10 * 10
+        if is_ron && is_menzen {
+            detail.push(("門前加符".into(), 10));
+        }
+        if !is_ron {
+            detail.push(("ツモ符".into(), 2));
+        }
+        detail.extend(mentsu_fu.into_iter().filter(|(_, fu)| *fu > 0));
+
+        let total = detail.iter().map(|(_, fu)| fu).sum::<u32>();
+        let rounded = (total + 9) / 10 * 10;
+        if rounded > total {
+            detail.push(("切り上げ".into(), rounded - total));
+        }
+        (rounded, detail)
     }
 
-    pub(crate) fn judge_yaku(&self, env: &Env) -> Yaku {
+    pub fn judge_yaku(&self, env: &Env) -> Yaku {
         Yaku::new(self, env)
     }
 }
 
+/// 符の内訳に表示する面子の種類
+fn fu_name(mentsu: &Mentsu, is_menzen: bool) -> &'static str {
+    match (mentsu.kind(), is_menzen) {
+        (MentsuKind::Kotsu(..), true) => "暗刻",
+        (MentsuKind::Kotsu(..), false) => "明刻",
+        (MentsuKind::Kantsu(..), true) => "暗槓",
+        (MentsuKind::Kantsu(..), false) => "明槓",
+        (MentsuKind::Toitsu(..), _) => "雀頭",
+        (MentsuKind::Shuntsu(..), _) | (MentsuKind::Single(..), _) => "",
+    }
+}
+
+#[cfg(feature = "wasm")]
 #[wasm_bindgen]
 impl Agari {
     #[wasm_bindgen(js_name = "toString")]
@@ -357,6 +407,15 @@ impl Agari {
     }
 }
 
+#[cfg(all(feature = "wasm", feature = "serde"))]
+#[wasm_bindgen]
+impl Agari {
+    #[wasm_bindgen(js_name = "toJSON")]
+    pub fn to_json_js(&self) -> Result<JsValue, JsValue> {
+        crate::serde_str::to_js(self)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -368,11 +427,11 @@ mod tests {
         let ton = Hai::from_str("1j").unwrap();
         let nan = Hai::from_str("2j").unwrap();
         fn comp(s: &str, bakaze: Hai, jikaze: Hai) -> u32 {
-            let env = Env::new_empty(bakaze, jikaze);
+            let env = Env::new(bakaze, jikaze);
             let tehai = Tehai::from_str(s).unwrap();
             let comb = tehai.to_agari_combinations();
             assert_eq!(comb.len(), 1);
-            comb[0].compute_fu(&env)
+            comb[0].compute_fu_detail(&env).0
         }
 
         // https://ja.wikipedia.org/wiki/%E9%BA%BB%E9%9B%80%E3%81%AE%E5%BE%97%E7%82%B9%E8%A8%88%E7%AE%97#%E7%AC%A6%E3%81%AE%E8%A8%88%E7%AE%97
@@ -440,4 +499,44 @@ mod tests {
         // 平和ツモはかならず20符になる https://mj-king.net/tensu/tensu/4_13.html
         assert_eq!(comp("23444m234s234p78s !9s", ton, ton), 20);
     }
+
+    #[test]
+    fn compute_fu_detail() {
+        let ton = Hai::from_str("1j").unwrap();
+        fn detail(s: &str, bakaze: Hai, jikaze: Hai) -> String {
+            let env = Env::new(bakaze, jikaze);
+            let tehai = Tehai::from_str(s).unwrap();
+            let comb = tehai.to_agari_combinations();
+            assert_eq!(comb.len(), 1);
+            let (fu, detail) = comb[0].compute_fu_detail(&env);
+            let detail = detail
+                .into_iter()
+                .map(|(name, fu)| format!("{}:{}", name, fu))
+                .collect::<Vec<_>>()
+                .join(",");
+            format!("{}符 [{}]", fu, detail)
+        }
+
+        assert_eq!(
+            detail("113344m5566s77p1j !1j", ton, ton),
+            "25符 [七対子:25]"
+        );
+        assert_eq!(detail("55s12345678m234p !9m", ton, ton), "20符 [副底:20]");
+        assert_eq!(
+            detail("55s12378m234p <456m ?9m", ton, ton),
+            "30符 [副底:20,食い平和形:10]"
+        );
+        assert_eq!(
+            detail("11p223344m222s46p ?5p", ton, ton),
+            "40符 [副底:20,門前加符:10,暗刻 222s:4,嵌張待ち:2,切り上げ:4]"
+        );
+        assert_eq!(
+            detail("77j12p666j >999m ^888+8p !3p", ton, ton),
+            "50符 [副底:20,ツモ符:2,暗刻 666j:8,雀頭 77j:2,明刻 >999m:4,明槓 ^888+8p:8,辺張待ち:2,切り上げ:4]"
+        );
+        assert_eq!(
+            detail("333m77788p111s77j ?7j", Hai::from_str("2j").unwrap(), ton),
+            "50符 [副底:20,門前加符:10,暗刻 333m:4,暗刻 777p:4,暗刻 111s:8,明刻 777j:4]"
+        );
+    }
 }
@@ -1,13 +1,13 @@
-use crate::{
-    agari_type::AgariType, hai::Hai, hai_image::HaiImage, hai_vec::HaiVec,
-    hai_with_attr::HaiWithAttr,
-};
+#[cfg(feature = "wasm")]
+use crate::hai_image::HaiImage;
+use crate::{agari_type::AgariType, hai::Hai, hai_vec::HaiVec, hai_with_attr::HaiWithAttr};
 use std::{fmt, str::FromStr};
 use thiserror::Error;
+#[cfg(feature = "wasm")]
 use wasm_bindgen::prelude::*;
 
 /// あがり牌
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub struct AgariHai {
     type_: AgariType,
@@ -21,11 +21,15 @@ impl fmt::Display for AgariHai {
 }
 
 impl AgariHai {
-    pub(crate) fn type_(&self) -> AgariType {
+    pub fn new(type_: AgariType, hai: Hai) -> Self {
+        Self { type_, hai }
+    }
+
+    pub fn type_(&self) -> AgariType {
         self.type_
     }
 
-    pub(crate) fn hai(&self) -> Hai {
+    pub fn hai(&self) -> Hai {
         self.hai
     }
 }
@@ -63,12 +67,17 @@ impl FromStr for AgariHai {
     }
 }
 
+#[cfg(feature = "serde")]
+crate::serde_str::impl_serde_str!(AgariHai);
+
+#[cfg(feature = "wasm")]
 impl AgariHai {
     fn to_image(self) -> HaiImage {
         HaiImage::normal(self.hai)
     }
 }
 
+#[cfg(feature = "wasm")]
 #[wasm_bindgen]
 impl AgariHai {
     #[wasm_bindgen(getter = hai)]
@@ -0,0 +1,328 @@
+//! コマンドラインからの点数計算 (`cli` feature)
+//!
+//! 引数で指定した手牌、または引数がない場合は標準入力の各行の手牌について、
+//! すべてのあがり形の役・符・点数を出力する。
+
+use crate::{
+    env::{self, Env, RichiType},
+    hai::Hai,
+    rules::{Rules, SanmaTsumo},
+    tehai::Tehai,
+    yaku::Yaku,
+};
+use std::{
+    io::{self, BufRead, Write},
+    process,
+    str::FromStr,
+};
+use thiserror::Error;
+
+const USAGE: &str = "\
+使い方: mjview [オプション] [手牌...]
+
+手牌を指定しない場合は標準入力から1行ずつ手牌を読み込む (空行と `#` で始まる行は無視する)。
+
+オプション:
+    --rules <名前>       ルール (default, tenhou, m-league, wrc)
+    --sanma <精算方法>   三人麻雀 (tsumozon, setsuhan)
+    --akadora <枚数>     赤ドラの枚数 (萬子/筒子/索子の枚数 `1/2/1` または合計枚数)
+    --bakaze <牌>        場風 (1j-4j, 既定値: 1j)
+    --jikaze <牌>        自風 (1j-4j, 既定値: 1j)
+    --richi              立直
+    --daburi             ダブル立直
+    --ippatsu            一発
+    --rinshan            嶺上開花/槍槓
+    --haitei             海底摸月/河底撈魚
+    --tenho              天和/地和
+    --renho              人和
+    --aotenjo            青天井
+    --dora <牌>          ドラ表示牌
+    --uradora <牌>       裏ドラ表示牌
+    --honba <本数>       本場
+    --kyotaku <本数>     供託 (立直棒の本数)
+    --hoju <他家>        放銃者 (<: 上家, ^: 対面, >: 下家)
+    -h, --help           この説明を表示する
+";
+
+#[derive(Debug, Error)]
+#[error(transparent)]
+pub struct Error(#[from] ErrorKind);
+
+#[derive(Debug, Error)]
+enum ErrorKind {
+    #[error("不明なオプションです: `{0}`")]
+    UnknownOption(String),
+    #[error("オプション `{0}` には値が必要です")]
+    MissingValue(String),
+    #[error("オプション `{0}` の値が不正です: `{1}`")]
+    InvalidValue(String, String),
+    #[error(transparent)]
+    Env(#[from] env::ParseError),
+    #[error("手牌のパースエラー: {0}")]
+    Tehai(#[from] <Tehai as FromStr>::Err),
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+/// コマンドライン引数の解析結果
+#[derive(Debug)]
+struct Args {
+    help: bool,
+    env: Env,
+    tehai: Vec<String>,
+}
+
+impl Args {
+    fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, Error> {
+        use ErrorKind as E;
+        let ton = Hai::from_str("1j").unwrap();
+        let mut env = Env::new(ton, ton);
+        let mut rules = Rules::default();
+        let mut sanma = None;
+        let mut akadora = None;
+        let mut help = false;
+        let mut tehai = vec![];
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            if !arg.starts_with('-') {
+                tehai.push(arg);
+                continue;
+            }
+            let mut value = || args.next().ok_or_else(|| E::MissingValue(arg.clone()));
+            let invalid = |value: &str| E::InvalidValue(arg.clone(), value.into());
+            match arg.as_str() {
+                "-h" | "--help" => help = true,
+                "--rules" => {
+                    let value = value()?;
+                    rules = Rules::from_preset(&value).ok_or_else(|| invalid(&value))?;
+                }
+                "--sanma" => {
+                    let value = value()?;
+                    sanma = Some(SanmaTsumo::from_name(&value).ok_or_else(|| invalid(&value))?);
+                }
+                "--akadora" => {
+                    let value = value()?;
+                    akadora = Some(Rules::akadora_from_str(&value).ok_or_else(|| invalid(&value))?);
+                }
+                "--bakaze" | "--jikaze" => {
+                    let value = value()?;
+                    let hai = Hai::from_str(&value)
+                        .ok()
+                        .filter(Hai::is_kazehai)
+                        .ok_or_else(|| invalid(&value))?;
+                    match arg.as_str() {
+                        "--bakaze" => env.bakaze = hai,
+                        _ => env.jikaze = hai,
+                    }
+                }
+                "--richi" => env.richi = Some(RichiType::Richi),
+                "--daburi" => env.richi = Some(RichiType::Daburi),
+                "--ippatsu" => env.ippatsu = true,
+                "--rinshan" => env.rinshan = true,
+                "--haitei" => env.haitei = true,
+                "--tenho" => env.tenho = true,
+                "--renho" => env.renho = true,
+                "--aotenjo" => env.aotenjo = true,
+                "--dora" => env.set_dora(&value()?).map_err(E::from)?,
+                "--uradora" => env.set_uradora(&value()?).map_err(E::from)?,
+                "--honba" | "--kyotaku" => {
+                    let value = value()?;
+                    let n = u32::from_str(&value).map_err(|_| invalid(&value))?;
+                    match arg.as_str() {
+                        "--honba" => env.honba = n,
+                        _ => env.kyotaku = n,
+                    }
+                }
+                "--hoju" => env.set_hoju(&value()?).map_err(E::from)?,
+                _ => return Err(E::UnknownOption(arg).into()),
+            }
+        }
+
+        if sanma.is_some() {
+            rules.sanma = sanma;
+        }
+        if let Some(akadora) = akadora {
+            rules.akadora = akadora;
+        }
+        env.rules = rules;
+        Ok(Self { help, env, tehai })
+    }
+}
+
+/// 手牌のすべてのあがり形について、点数の高い順に役・符・点数を出力する
+fn write_tehai(out: &mut impl Write, s: &str, env: &Env) -> Result<(), Error> {
+    let tehai = Tehai::from_str_with_rules(s, &env.rules).map_err(ErrorKind::from)?;
+    let mut comb = tehai
+        .to_agari_combinations_with_env(env)
+        .into_iter()
+        .map(|agari| {
+            let yaku = Yaku::new(&agari, env);
+            (agari, yaku)
+        })
+        .collect::<Vec<_>>();
+    comb.sort_by(|(agari_a, yaku_a), (agari_b, yaku_b)| {
+        yaku_b.cmp(yaku_a).then_with(|| agari_a.cmp(agari_b))
+    });
+
+    writeln!(out, "{}", tehai).map_err(ErrorKind::from)?;
+    for (agari, yaku) in &comb {
+        write!(
+            out,
+            "  {} ({}{}点 {} {}符)",
+            agari,
+            yaku.name(),
+            yaku.point(),
+            yaku.rank(),
+            yaku.fu()
+        )
+        .map_err(ErrorKind::from)?;
+        if !yaku.payments().is_empty() {
+            let payments = yaku
+                .payments()
+                .iter()
+                .map(|payment| payment.to_string())
+                .collect::<Vec<_>>();
+            write!(out, " [{}]", payments.join(", ")).map_err(ErrorKind::from)?;
+        }
+        if yaku.total() != yaku.point() {
+            write!(out, " 収入{}点", yaku.total()).map_err(ErrorKind::from)?;
+        }
+        writeln!(out).map_err(ErrorKind::from)?;
+        for (name, rank) in yaku.detail() {
+            writeln!(out, "    {} ({})", name, rank).map_err(ErrorKind::from)?;
+        }
+        let fu_detail = yaku
+            .fu_detail()
+            .iter()
+            .map(|(name, fu)| format!("{} {}符", name, fu))
+            .collect::<Vec<_>>();
+        writeln!(out, "    符の内訳: {}", fu_detail.join(", ")).map_err(ErrorKind::from)?;
+    }
+    Ok(())
+}
+
+/// 各手牌の結果を出力し、エラーがあった手牌の数を返す
+fn run(args: &Args, stdin: impl BufRead, out: &mut impl Write, err: &mut impl Write) -> usize {
+    let mut num_errors = 0;
+    let mut report = |label: &str, res: Result<(), Error>| {
+        if let Err(e) = res {
+            num_errors += 1;
+            let _ = writeln!(err, "{}: {}", label, e);
+        }
+    };
+
+    if !args.tehai.is_empty() {
+        for s in &args.tehai {
+            report(s, write_tehai(out, s, &args.env));
+        }
+        return num_errors;
+    }
+
+    for (i, line) in stdin.lines().enumerate() {
+        let label = format!("{}行目", i + 1);
+        let line = match line {
+            Ok(line) => line,
+            Err(e) => {
+                report(&label, Err(ErrorKind::from(e).into()));
+                break;
+            }
+        };
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        report(&label, write_tehai(out, line, &args.env));
+    }
+    num_errors
+}
+
+/// `mjview` コマンドのエントリポイント
+pub fn main() {
+    let args = match Args::parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            process::exit(2);
+        }
+    };
+    if args.help {
+        print!("{}", USAGE);
+        return;
+    }
+
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    let stderr = io::stderr();
+    let num_errors = run(&args, stdin.lock(), &mut stdout.lock(), &mut stderr.lock());
+    if num_errors > 0 {
+        process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tacha::Tacha;
+    use assert_matches::assert_matches;
+
+    fn parse(args: &[&str]) -> Result<Args, ErrorKind> {
+        Args::parse(args.iter().map(|s| s.to_string())).map_err(|e| e.0)
+    }
+
+    #[test]
+    fn parse_args() {
+        use ErrorKind::*;
+        let args = parse(&[
+            "--rules",
+            "tenhou",
+            "--jikaze",
+            "2j",
+            "--richi",
+            "--dora",
+            "1m",
+            "--honba",
+            "2",
+            "--hoju",
+            "<",
+            "--akadora",
+            "0/2/1",
+            "123m",
+        ])
+        .unwrap();
+        assert_eq!(
+            args.env.rules,
+            Rules {
+                akadora: [0, 2, 1],
+                ..Rules::tenhou()
+            }
+        );
+        assert_eq!(args.env.jikaze, Hai::from_str("2j").unwrap());
+        assert_eq!(args.env.richi, Some(RichiType::Richi));
+        assert_eq!(args.env.dora, [Hai::from_str("1m").unwrap()]);
+        assert_eq!(args.env.honba, 2);
+        assert_eq!(args.env.hoju, Some(Tacha::Kamicha));
+        assert_eq!(args.tehai, ["123m"]);
+
+        assert_matches!(parse(&["--foo"]), Err(UnknownOption(..)));
+        assert_matches!(parse(&["--honba"]), Err(MissingValue(..)));
+        assert_matches!(parse(&["--honba", "x"]), Err(InvalidValue(..)));
+        assert_matches!(parse(&["--bakaze", "5j"]), Err(InvalidValue(..)));
+        assert_matches!(parse(&["--akadora", "1/1"]), Err(InvalidValue(..)));
+        assert_matches!(parse(&["--dora", "x"]), Err(Env(..)));
+    }
+
+    #[test]
+    fn batch() {
+        let args = parse(&["--jikaze", "2j"]).unwrap();
+        let input = "# コメント\n\n123456789m1234p ?1p\n123m\n";
+        let mut out = vec![];
+        let mut err = vec![];
+        assert_eq!(run(&args, input.as_bytes(), &mut out, &mut err), 1);
+        let out = String::from_utf8(out).unwrap();
+        let err = String::from_utf8(err).unwrap();
+        assert_eq!(out.lines().next(), Some("123456789m1234p ?1p"));
+        assert!(out.contains("一気通貫 (2飜)"));
+        assert!(err.starts_with("4行目: 手牌のパースエラー: "));
+    }
+}
@@ -1,30 +1,55 @@
 use crate::{
-    agari_type::AgariType, hai::Hai, hai_category::HaiCategory, jun_tehai::JunTehai, tehai::Tehai,
+    agari_type::AgariType,
+    furo::Furo,
+    hai::Hai,
+    hai_category::HaiCategory,
+    jun_tehai::JunTehai,
+    nukidora::Nukidora,
+    rules::{self, Rules},
+    tacha::Tacha,
+    tehai::Tehai,
+    tenpai,
 };
+use enum_iterator::IntoEnumIterator;
 use enumflags2::{bitflags, BitFlags};
+#[cfg(feature = "wasm")]
 use js_sys::Array;
-use std::{collections::HashMap, str::FromStr};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    str::FromStr,
+};
+use thiserror::Error;
+#[cfg(feature = "wasm")]
 use wasm_bindgen::prelude::*;
 
 #[bitflags]
 #[repr(u16)]
+/// 状況の項目 (警告の対象)
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum Item {
+pub enum Item {
     Tehai,
     Richi,
     Ippatsu,
     Rinshan,
     Haitei,
     Tenho,
+    Renho,
     Bakaze,
     Jikaze,
     Dora,
     Uradora,
     Aotenjo,
+    Hoju,
+    Kawa,
+    Minogashi,
+    /// 他家の河・副露・抜きドラ
+    Tacha,
 }
 
 impl Item {
-    fn as_str(&self) -> &'static str {
+    pub fn as_str(&self) -> &'static str {
         use Item::*;
         match self {
             Tehai => "tehai",
@@ -33,17 +58,32 @@ impl Item {
             Rinshan => "rinshan",
             Haitei => "haitei",
             Tenho => "tenho",
+            Renho => "renho",
             Bakaze => "bakaze",
             Jikaze => "jikaze",
             Dora => "dora",
             Uradora => "uradora",
             Aotenjo => "aotenjo",
+            Hoju => "hoju",
+            Kawa => "kawa",
+            Minogashi => "minogashi",
+            Tacha => "tacha",
         }
     }
 }
 
+/// 自風 (東:1, 南:2, 西:3, 北:4) の席の名前
+pub(crate) fn kaze_name(kaze: u8) -> &'static str {
+    ["東家", "南家", "西家", "北家"][usize::from(kaze - 1)]
+}
+
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "lowercase")
+)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub(crate) enum RichiType {
+pub enum RichiType {
     /// 立直
     Richi,
     /// ダブル立直
@@ -51,7 +91,7 @@ pub(crate) enum RichiType {
 }
 
 impl RichiType {
-    fn as_str(&self) -> &'static str {
+    pub fn as_str(&self) -> &'static str {
         match self {
             RichiType::Richi => "richi",
             RichiType::Daburi => "daburi",
@@ -59,47 +99,236 @@ impl RichiType {
     }
 }
 
-#[wasm_bindgen]
+#[derive(Debug, Error)]
+#[error(transparent)]
+pub struct ParseError(#[from] ParseErrorKind);
+
+#[derive(Debug, Error)]
+enum ParseErrorKind {
+    #[error("不明な立直の種類です: `{0}`")]
+    UnknownRichi(String),
+    #[error("不明な放銃者です: `{0}`")]
+    UnknownHoju(String),
+    #[error("ドラ表示牌のパースエラー: {0}")]
+    Dora(#[from] <JunTehai as FromStr>::Err),
+    #[error("河のパースエラー: {0}")]
+    Kawa(#[source] <JunTehai as FromStr>::Err),
+    #[error("見逃した牌のパースエラー: {0}")]
+    Minogashi(#[source] <JunTehai as FromStr>::Err),
+    #[error("副露のパースエラー: {0}")]
+    Furo(#[source] <Furo as FromStr>::Err),
+    #[error("抜きドラのパースエラー: {0}")]
+    Nukidora(#[source] <Nukidora as FromStr>::Err),
+}
+
+/// 他家の見えている牌
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(default))]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TachaHai {
+    /// 河 (鳴かれた牌を含む)
+    pub kawa: Vec<Hai>,
+    pub furo: Vec<Furo>,
+    pub nukidora: Vec<Nukidora>,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone)]
 pub struct Env {
-    pub(crate) richi: Option<RichiType>,
-    pub(crate) ippatsu: bool,
-    pub(crate) rinshan: bool,
-    pub(crate) haitei: bool,
-    pub(crate) tenho: bool,
-    pub(crate) bakaze: Hai,
-    pub(crate) jikaze: Hai,
-    pub(crate) dora: Vec<Hai>,
-    pub(crate) uradora: Vec<Hai>,
-    pub(crate) aotenjo: bool,
+    #[cfg_attr(feature = "wasm", wasm_bindgen(skip))]
+    pub richi: Option<RichiType>,
+    #[cfg_attr(feature = "wasm", wasm_bindgen(skip))]
+    pub ippatsu: bool,
+    #[cfg_attr(feature = "wasm", wasm_bindgen(skip))]
+    pub rinshan: bool,
+    #[cfg_attr(feature = "wasm", wasm_bindgen(skip))]
+    pub haitei: bool,
+    #[cfg_attr(feature = "wasm", wasm_bindgen(skip))]
+    pub tenho: bool,
+    /// 人和
+    #[cfg_attr(feature = "wasm", wasm_bindgen(skip))]
+    pub renho: bool,
+    #[cfg_attr(feature = "wasm", wasm_bindgen(skip))]
+    pub bakaze: Hai,
+    #[cfg_attr(feature = "wasm", wasm_bindgen(skip))]
+    pub jikaze: Hai,
+    #[cfg_attr(feature = "wasm", wasm_bindgen(skip))]
+    pub dora: Vec<Hai>,
+    #[cfg_attr(feature = "wasm", wasm_bindgen(skip))]
+    pub uradora: Vec<Hai>,
+    #[cfg_attr(feature = "wasm", wasm_bindgen(skip))]
+    pub aotenjo: bool,
+    /// 本場
+    #[cfg_attr(feature = "wasm", wasm_bindgen(skip))]
+    pub honba: u32,
+    /// 供託 (立直棒の本数)
+    #[cfg_attr(feature = "wasm", wasm_bindgen(skip))]
+    pub kyotaku: u32,
+    /// 放銃者 (ロンあがりの場合。不明な場合は `None`)
+    #[cfg_attr(feature = "wasm", wasm_bindgen(skip))]
+    pub hoju: Option<Tacha>,
+    #[cfg_attr(feature = "wasm", wasm_bindgen(skip))]
+    pub rules: Rules,
+    /// 河 (自分の捨て牌。鳴かれた牌を含む)
+    #[cfg_attr(feature = "wasm", wasm_bindgen(skip))]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub kawa: Vec<Hai>,
+    /// 見逃した牌 (直前の自分の打牌以降。立直している場合は立直以降)
+    #[cfg_attr(feature = "wasm", wasm_bindgen(skip))]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub minogashi: Vec<Hai>,
+    /// 他家の見えている牌 (卓全体の牌の枚数の確認用)
+    #[cfg_attr(feature = "wasm", wasm_bindgen(skip))]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub tacha_hai: BTreeMap<Tacha, TachaHai>,
 }
 
 #[derive(Debug, Clone, Default)]
 struct HaiCount {
-    all: HashMap<(HaiCategory, u8), (BitFlags<Item>, usize)>,
     tehai: HashMap<(HaiCategory, u8), usize>,
     dora: HashMap<(HaiCategory, u8), usize>,
     uradora: HashMap<(HaiCategory, u8), usize>,
 }
 
 impl Env {
-    #[cfg(test)]
-    pub(crate) fn new_empty(bakaze: Hai, jikaze: Hai) -> Env {
+    pub fn new(bakaze: Hai, jikaze: Hai) -> Env {
         Env {
             richi: None,
             ippatsu: false,
             rinshan: false,
             haitei: false,
             tenho: false,
+            renho: false,
             bakaze,
             jikaze,
             dora: vec![],
             uradora: vec![],
             aotenjo: false,
+            honba: 0,
+            kyotaku: 0,
+            hoju: None,
+            rules: Rules::default(),
+            kawa: vec![],
+            minogashi: vec![],
+            tacha_hai: BTreeMap::new(),
+        }
+    }
+
+    /// 親の他家 (自家が親の場合は `None`)
+    pub fn oya_tacha(&self) -> Option<Tacha> {
+        match (self.jikaze.number(), self.rules.sanma.is_some()) {
+            (2, _) => Some(Tacha::Kamicha),
+            (3, false) => Some(Tacha::Toimen),
+            (3, true) => Some(Tacha::Shimocha),
+            (4, _) => Some(Tacha::Shimocha),
+            _ => None,
+        }
+    }
+
+    /// 卓にいるプレイヤーの人数
+    pub fn num_players(&self) -> u8 {
+        if self.rules.sanma.is_some() {
+            3
+        } else {
+            4
         }
     }
 
-    fn check_props(&self, tehai: Option<&Tehai>) -> Vec<(BitFlags<Item>, String)> {
+    /// 他家の自風 (東:1, 南:2, 西:3, 北:4)
+    pub fn tacha_kaze(&self, tacha: Tacha) -> u8 {
+        let n = self.num_players();
+        let offset = match tacha {
+            Tacha::Shimocha => 1,
+            Tacha::Toimen => 2,
+            Tacha::Kamicha => n - 1,
+        };
+        (self.jikaze.number() - 1 + offset) % n + 1
+    }
+
+    /// 卓にいる他家 (三人麻雀の場合は対面がいない)
+    pub fn tacha(&self) -> impl Iterator<Item = Tacha> {
+        let sanma = self.rules.sanma.is_some();
+        Tacha::into_enum_iter().filter(move |tacha| !sanma || *tacha != Tacha::Toimen)
+    }
+
+    /// 立直の種類を文字列 (`richi`, `daburi`, 立直なしの場合は空文字列) で設定する
+    pub fn set_richi(&mut self, value: &str) -> Result<(), ParseError> {
+        self.richi = match value {
+            "richi" => Some(RichiType::Richi),
+            "daburi" => Some(RichiType::Daburi),
+            "" => None,
+            _ => return Err(ParseErrorKind::UnknownRichi(value.into()).into()),
+        };
+        Ok(())
+    }
+
+    /// 放銃者を文字列 (`<`: 上家, `^`: 対面, `>`: 下家, 不明な場合は空文字列) で設定する
+    pub fn set_hoju(&mut self, value: &str) -> Result<(), ParseError> {
+        self.hoju = match value {
+            "" => None,
+            _ => Some(
+                Tacha::into_enum_iter()
+                    .find(|tacha| tacha.to_str() == value)
+                    .ok_or_else(|| ParseErrorKind::UnknownHoju(value.into()))?,
+            ),
+        };
+        Ok(())
+    }
+
+    /// ドラ表示牌を `13m5p` のような文字列で設定する
+    pub fn set_dora(&mut self, value: &str) -> Result<(), ParseError> {
+        let tehai = JunTehai::from_str(value).map_err(ParseErrorKind::from)?;
+        self.dora = tehai.as_slice().into();
+        Ok(())
+    }
+
+    /// 裏ドラ表示牌を `13m5p` のような文字列で設定する
+    pub fn set_uradora(&mut self, value: &str) -> Result<(), ParseError> {
+        let tehai = JunTehai::from_str(value).map_err(ParseErrorKind::from)?;
+        self.uradora = tehai.as_slice().into();
+        Ok(())
+    }
+
+    /// 河を `13m5p` のような文字列で設定する
+    pub fn set_kawa(&mut self, value: &str) -> Result<(), ParseError> {
+        let tehai = JunTehai::from_str(value).map_err(ParseErrorKind::Kawa)?;
+        self.kawa = tehai.as_slice().into();
+        Ok(())
+    }
+
+    /// 見逃した牌を `13m5p` のような文字列で設定する
+    pub fn set_minogashi(&mut self, value: &str) -> Result<(), ParseError> {
+        let tehai = JunTehai::from_str(value).map_err(ParseErrorKind::Minogashi)?;
+        self.minogashi = tehai.as_slice().into();
+        Ok(())
+    }
+
+    /// 他家の河を `13m5p` のような文字列で設定する
+    pub fn set_tacha_kawa(&mut self, tacha: Tacha, value: &str) -> Result<(), ParseError> {
+        let tehai = JunTehai::from_str(value).map_err(ParseErrorKind::Kawa)?;
+        self.tacha_hai.entry(tacha).or_default().kawa = tehai.as_slice().into();
+        Ok(())
+    }
+
+    /// 他家の副露と抜きドラを `<213p ^5$55m *4j` のような文字列で設定する
+    pub fn set_tacha_furo(&mut self, tacha: Tacha, value: &str) -> Result<(), ParseError> {
+        let mut furo = vec![];
+        let mut nukidora = vec![];
+        for chunk in value.split_whitespace() {
+            if Nukidora::is_nukidora_str(chunk) {
+                nukidora.push(Nukidora::from_str(chunk).map_err(ParseErrorKind::Nukidora)?);
+            } else {
+                furo.push(Furo::from_str(chunk).map_err(ParseErrorKind::Furo)?);
+            }
+        }
+        let tacha_hai = self.tacha_hai.entry(tacha).or_default();
+        tacha_hai.furo = furo;
+        tacha_hai.nukidora = nukidora;
+        Ok(())
+    }
+
+    /// 状況の矛盾を調べ、警告の対象の項目とメッセージを返す
+    pub fn check_props(&self, tehai: Option<&Tehai>) -> Vec<(BitFlags<Item>, String)> {
         use {AgariType::*, Item::*, RichiType as RT};
 
         let mut res = vec![];
@@ -131,6 +360,9 @@ impl Env {
         };
 
         let hai_count = self.check_hai_count(tehai, &mut res);
+        if let (Some(tehai), Some(Ron)) = (tehai, agari_type) {
+            self.check_furiten(tehai, richi_name, &mut res);
+        }
 
         if self.ippatsu {
             let item = Ippatsu;
@@ -192,6 +424,27 @@ impl Env {
                 ));
             }
         }
+        if self.renho {
+            let item = Renho;
+            if self.rules.renho == rules::Renho::Off {
+                res.push((item.into(), "人和なしのルールです".into()));
+            }
+            if agari_type == Some(Tsumo) {
+                res.push((item | Tehai, "人和はロンあがりの場合のみ成立します".into()));
+            }
+            if oya {
+                res.push((item | Jikaze, "人和は子の場合のみ成立します".into()));
+            }
+            if has_furo {
+                res.push((item | Tehai, "副露がある場合人和にはなりません".into()));
+            }
+            if self.richi.is_some() {
+                res.push((item | Richi, format!("人和と{}は複合しません", richi_name)));
+            }
+            if self.tenho {
+                res.push((item | Tenho, format!("{}と人和は複合しません", tenho_name)));
+            }
+        }
         if self.rinshan && agari_type == Some(Ron) {
             let agari_hai = agari_hai.unwrap();
             let agari_hai = (agari_hai.category(), agari_hai.number());
@@ -250,121 +503,268 @@ impl Env {
                 "裏ドラが有効なのは立直/ダブル立直時のみです".into(),
             ));
         }
+        if let Some(hoju) = self.hoju {
+            if agari_type == Some(Tsumo) {
+                res.push((
+                    Hoju | Tehai,
+                    "放銃者はロンあがりの場合のみ指定できます".into(),
+                ));
+            }
+            if self.tacha().all(|tacha| tacha != hoju) {
+                res.push((Hoju.into(), format!("{}は卓にいません", hoju.to_name())));
+            }
+        }
+        if self.rules.sanma.is_some() {
+            if self.bakaze.number() == 4 {
+                res.push((Bakaze.into(), "三人麻雀に北場はありません".into()));
+            }
+            if self.jikaze.number() == 4 {
+                res.push((Jikaze.into(), "三人麻雀に北家はありません".into()));
+            }
+            if let Some(hai) = tehai.and_then(|tehai| tehai.all_hai().find(Hai::is_unused_in_sanma))
+            {
+                res.push((
+                    Tehai.into(),
+                    format!("三人麻雀で使用しない `{}` が手牌に含まれています", hai),
+                ));
+            }
+            for (item, name, list) in [
+                (Dora, "ドラ表示牌", &self.dora),
+                (Uradora, "裏ドラ表示牌", &self.uradora),
+            ] {
+                if let Some(hai) = list.iter().find(|hai| hai.is_unused_in_sanma()) {
+                    res.push((
+                        item.into(),
+                        format!("三人麻雀で使用しない `{}` が{}に含まれています", hai, name),
+                    ));
+                }
+            }
+        } else if tehai
+            .map(|tehai| !tehai.nukidora().is_empty())
+            .unwrap_or_default()
+        {
+            res.push((Tehai.into(), "抜きドラは三人麻雀でのみ有効です".into()));
+        }
         res
     }
 
+    /// フリテンのロンあがりを調べる
+    fn check_furiten(
+        &self,
+        tehai: &Tehai,
+        richi_name: &str,
+        res: &mut Vec<(BitFlags<Item>, String)>,
+    ) {
+        use Item::*;
+
+        if tehai.jun_tehai().as_slice().len() + tehai.furo().len() * 3 != 13 {
+            return;
+        }
+        let machi_hai = tenpai::machi_hai(tehai.jun_tehai(), tehai.furo(), &self.rules);
+        let find = |list: &[Hai]| {
+            machi_hai
+                .iter()
+                .map(|machi| machi.hai())
+                .find(|hai| list.iter().any(|h| h.is_same(hai)))
+        };
+        if let Some(hai) = find(&self.kawa) {
+            res.push((
+                Kawa | Tehai,
+                format!("待ち牌 `{}` が河にあるためフリテンです", hai),
+            ));
+        }
+        if let Some(hai) = find(&self.minogashi) {
+            if self.richi.is_some() {
+                res.push((
+                    Minogashi | Richi,
+                    format!(
+                        "{}後に待ち牌 `{}` を見逃したためフリテンです",
+                        richi_name, hai
+                    ),
+                ));
+            } else {
+                res.push((
+                    Minogashi | Tehai,
+                    format!("待ち牌 `{}` を見逃したため同巡内フリテンです", hai),
+                ));
+            }
+        }
+    }
+
     fn check_hai_count(
         &self,
         tehai: Option<&Tehai>,
         res: &mut Vec<(BitFlags<Item>, String)>,
     ) -> HaiCount {
-        use Item::*;
-
         let mut count = HaiCount::default();
         if let Some(tehai) = tehai {
-            for hai in tehai.all_hai() {
-                let hai = (hai.category(), hai.number());
-                *count.tehai.entry(hai).or_default() += 1;
-                let (item, count) = count.all.entry(hai).or_default();
-                *item |= Tehai;
-                *count += 1;
+            let nukidora = tehai.nukidora().iter().map(|nukidora| nukidora.hai());
+            for hai in tehai.all_hai().chain(nukidora) {
+                *count
+                    .tehai
+                    .entry((hai.category(), hai.number()))
+                    .or_default() += 1;
             }
         }
         for hai in &self.dora {
-            let hai = (hai.category(), hai.number());
-            *count.dora.entry(hai).or_default() += 1;
-            let (item, count) = count.all.entry(hai).or_default();
-            *item |= Dora;
-            *count += 1;
+            *count
+                .dora
+                .entry((hai.category(), hai.number()))
+                .or_default() += 1;
         }
         for hai in &self.uradora {
-            let hai = (hai.category(), hai.number());
-            *count.uradora.entry(hai).or_default() += 1;
-            let (item, count) = count.all.entry(hai).or_default();
-            *item |= Uradora;
-            *count += 1;
+            *count
+                .uradora
+                .entry((hai.category(), hai.number()))
+                .or_default() += 1;
         }
 
-        for (item, map) in &[
-            (Tehai, &count.tehai),
-            (Dora, &count.dora),
-            (Uradora, &count.uradora),
-        ] {
-            for (key, value) in *map {
-                if *value == 0 {
-                    continue;
-                }
-                if *value > 4 {
-                    res.push((
-                        (*item).into(),
-                        format!("`{}{}` が5枚以上あります ({}枚)", key.1, key.0, *value),
-                    ));
-                    continue;
-                }
-                let (items, all_count) = count.all[key];
-                if all_count > 4 {
-                    let name = items
-                        .iter()
-                        .map(|item| match item {
-                            Tehai => "手牌",
-                            Dora => "ドラ表示牌",
-                            Uradora => "裏ドラ表示牌",
-                            _ => unreachable!(),
-                        })
-                        .fold(String::new(), |mut out, item| {
-                            if !out.is_empty() {
-                                out.push('、');
-                            }
-                            out.push_str(item);
-                            out
-                        });
+        let sources = self.visible_hai(tehai);
+        for (item, name, list) in &sources {
+            let mut map = BTreeMap::<_, usize>::new();
+            for hai in list {
+                *map.entry((hai.category(), hai.number())).or_default() += 1;
+            }
+            for (key, value) in map {
+                if value > 4 {
                     res.push((
                         (*item).into(),
                         format!(
-                            "{}合わせて `{}{}` が5枚以上あります ({}枚)",
-                            name, key.1, key.0, all_count
+                            "{}に `{}{}` が5枚以上あります ({}枚)",
+                            name, key.1, key.0, value
                         ),
                     ));
                 }
             }
         }
+
+        let all_kinds = Hai::all_kinds().map(|hai| (hai, false));
+        let akadora = Hai::all_kinds()
+            .filter_map(|hai| Hai::try_new(hai.category(), hai.number(), true).ok())
+            .map(|hai| (hai, true));
+        for (hai, aka) in all_kinds.chain(akadora) {
+            let limit = if aka {
+                usize::from(self.rules.akadora_limit(hai.category()))
+            } else {
+                4
+            };
+            // 枚数が上限を超えた場所と、場所ごとの枚数
+            let mut items = BitFlags::empty();
+            let mut total = 0;
+            let mut pushed_by = None;
+            let mut breakdown = vec![];
+            for (item, name, list) in &sources {
+                let n = list
+                    .iter()
+                    .filter(|h| h.is_same(&hai) && (!aka || h.akadora()))
+                    .count();
+                if n == 0 {
+                    continue;
+                }
+                if n > 4 {
+                    // 1か所で5枚以上ある場合は上で警告済み
+                    total = 0;
+                    break;
+                }
+                total += n;
+                items |= *item;
+                breakdown.push(format!("{}{}枚", name, n));
+                if total > limit && pushed_by.is_none() {
+                    pushed_by = Some(name);
+                }
+            }
+            if let (Some(name), true) = (pushed_by, total > limit) {
+                let message = if aka && limit == 0 {
+                    format!(
+                        "{}に赤ドラの `{}` があります (赤ドラなしのルールです)",
+                        name, hai
+                    )
+                } else if aka {
+                    format!(
+                        "{}で赤ドラの `{}` が{}枚を超えます ({})",
+                        name,
+                        hai,
+                        limit,
+                        breakdown.join("、")
+                    )
+                } else {
+                    format!(
+                        "{}で `{}` が5枚以上になります ({})",
+                        name,
+                        hai,
+                        breakdown.join("、")
+                    )
+                };
+                res.push((items, message));
+            }
+        }
         count
     }
+
+    /// 見えている牌を、警告の対象の項目と場所の名前ごとに列挙する
+    ///
+    /// 他家の牌が指定されている場合は卓全体の牌を数える。鳴いた牌とロンあがりのあがり牌は、打牌した席の河 (槍槓の場合は加槓) で数える。
+    fn visible_hai(&self, tehai: Option<&Tehai>) -> Vec<(Item, String, Vec<Hai>)> {
+        use Item::*;
+
+        let taku = !self.tacha_hai.is_empty();
+        let furo_hai = |furo: &[Furo]| -> Vec<Hai> {
+            furo.iter()
+                .flat_map(|furo| -> Vec<Hai> {
+                    if taku {
+                        furo.iter_from_tehai().collect()
+                    } else {
+                        furo.iter().collect()
+                    }
+                })
+                .collect()
+        };
+
+        let mut sources = vec![];
+        if let Some(tehai) = tehai {
+            let agari_hai = tehai.agari_hai();
+            let mut list = tehai.jun_tehai().as_slice().to_vec();
+            list.extend(furo_hai(tehai.furo()));
+            if !taku || agari_hai.type_() == AgariType::Tsumo {
+                list.push(agari_hai.hai());
+            }
+            list.extend(tehai.nukidora().iter().map(|nukidora| nukidora.hai()));
+            sources.push((Tehai, "手牌".into(), list));
+        }
+        sources.push((Kawa, "河".into(), self.kawa.clone()));
+        for (tacha, tacha_hai) in &self.tacha_hai {
+            let name = tacha.to_name();
+            sources.push((Tacha, format!("{}の河", name), tacha_hai.kawa.clone()));
+            sources.push((Tacha, format!("{}の副露", name), furo_hai(&tacha_hai.furo)));
+            let nukidora = tacha_hai.nukidora.iter().map(|nukidora| nukidora.hai());
+            sources.push((Tacha, format!("{}の抜きドラ", name), nukidora.collect()));
+        }
+        sources.push((Dora, "ドラ表示牌".into(), self.dora.clone()));
+        sources.push((Uradora, "裏ドラ表示牌".into(), self.uradora.clone()));
+        sources
+    }
 }
 
+#[cfg(feature = "wasm")]
 #[wasm_bindgen]
 impl Env {
     #[wasm_bindgen(constructor)]
     pub fn new_js() -> Env {
-        Env {
-            richi: None,
-            ippatsu: false,
-            rinshan: false,
-            haitei: false,
-            tenho: false,
-            bakaze: Hai::from_str("1j").unwrap(),
-            jikaze: Hai::from_str("1j").unwrap(),
-            dora: vec![],
-            uradora: vec![],
-            aotenjo: false,
-        }
+        let ton = Hai::from_str("1j").unwrap();
+        Self::new(ton, ton)
     }
 
-    #[wasm_bindgen(getter)]
-    pub fn richi(&self) -> String {
+    #[wasm_bindgen(getter = richi)]
+    pub fn richi_js(&self) -> String {
         self.richi
             .map(|r| r.as_str().to_string())
             .unwrap_or_default()
     }
 
-    #[wasm_bindgen(setter)]
-    pub fn set_richi(&mut self, value: &str) {
-        self.richi = match value {
-            "richi" => Some(RichiType::Richi),
-            "daburi" => Some(RichiType::Daburi),
-            "" => None,
-            _ => panic!("Invalid richi type str: {}", value),
-        };
+    #[wasm_bindgen(setter = richi)]
+    pub fn set_richi_js(&mut self, value: &str) -> Result<(), JsValue> {
+        self.set_richi(value).map_err(|e| e.to_string())?;
+        Ok(())
     }
 
     #[wasm_bindgen(getter)]
@@ -407,6 +807,29 @@ impl Env {
         self.tenho = value;
     }
 
+    #[wasm_bindgen(getter)]
+    pub fn renho(&self) -> bool {
+        self.renho
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_renho(&mut self, value: bool) {
+        self.renho = value;
+    }
+
+    /// 放銃者 (`<`: 上家, `^`: 対面, `>`: 下家)
+    #[wasm_bindgen(getter = hoju)]
+    pub fn hoju_js(&self) -> Option<String> {
+        self.hoju.map(|tacha| tacha.to_str().into())
+    }
+
+    #[wasm_bindgen(setter = hoju)]
+    pub fn set_hoju_js(&mut self, value: Option<String>) -> Result<(), JsValue> {
+        self.set_hoju(value.as_deref().unwrap_or_default())
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
     #[wasm_bindgen(getter)]
     pub fn bakaze(&self) -> Hai {
         self.bakaze
@@ -429,8 +852,7 @@ impl Env {
 
     #[wasm_bindgen(js_name = "setDora")]
     pub fn set_dora_js(&mut self, value: String) -> Result<(), JsValue> {
-        let tehai = JunTehai::from_str(&value).map_err(|e| e.to_string())?;
-        self.dora = tehai.as_slice().into();
+        self.set_dora(&value).map_err(|e| e.to_string())?;
         Ok(())
     }
 
@@ -441,8 +863,7 @@ impl Env {
 
     #[wasm_bindgen(js_name = "setUradora")]
     pub fn set_uradora_js(&mut self, value: String) -> Result<(), JsValue> {
-        let tehai = JunTehai::from_str(&value).map_err(|e| e.to_string())?;
-        self.uradora = tehai.as_slice().into();
+        self.set_uradora(&value).map_err(|e| e.to_string())?;
         Ok(())
     }
 
@@ -451,6 +872,49 @@ impl Env {
         self.uradora.iter().copied().map(JsValue::from).collect()
     }
 
+    #[wasm_bindgen(js_name = "setKawa")]
+    pub fn set_kawa_js(&mut self, value: String) -> Result<(), JsValue> {
+        self.set_kawa(&value).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    #[wasm_bindgen(getter = kawa)]
+    pub fn kawa_js(&self) -> Box<[JsValue]> {
+        self.kawa.iter().copied().map(JsValue::from).collect()
+    }
+
+    #[wasm_bindgen(js_name = "setMinogashi")]
+    pub fn set_minogashi_js(&mut self, value: String) -> Result<(), JsValue> {
+        self.set_minogashi(&value).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    #[wasm_bindgen(getter = minogashi)]
+    pub fn minogashi_js(&self) -> Box<[JsValue]> {
+        self.minogashi.iter().copied().map(JsValue::from).collect()
+    }
+
+    /// 他家 (`<`: 上家, `^`: 対面, `>`: 下家) の河を設定する
+    #[wasm_bindgen(js_name = "setTachaKawa")]
+    pub fn set_tacha_kawa_js(&mut self, tacha: &str, value: &str) -> Result<(), JsValue> {
+        self.set_tacha_kawa(tacha_from_str_js(tacha)?, value)
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    /// 他家 (`<`: 上家, `^`: 対面, `>`: 下家) の副露と抜きドラを設定する
+    #[wasm_bindgen(js_name = "setTachaFuro")]
+    pub fn set_tacha_furo_js(&mut self, tacha: &str, value: &str) -> Result<(), JsValue> {
+        self.set_tacha_furo(tacha_from_str_js(tacha)?, value)
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = "clearTachaHai")]
+    pub fn clear_tacha_hai_js(&mut self) {
+        self.tacha_hai.clear();
+    }
+
     #[wasm_bindgen(getter)]
     pub fn aotenjo(&self) -> bool {
         self.aotenjo
@@ -461,6 +925,36 @@ impl Env {
         self.aotenjo = value;
     }
 
+    #[wasm_bindgen(getter)]
+    pub fn honba(&self) -> u32 {
+        self.honba
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_honba(&mut self, value: u32) {
+        self.honba = value;
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn kyotaku(&self) -> u32 {
+        self.kyotaku
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_kyotaku(&mut self, value: u32) {
+        self.kyotaku = value;
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn rules(&self) -> Rules {
+        self.rules.clone()
+    }
+
+    #[wasm_bindgen(setter = rules)]
+    pub fn set_rules_js(&mut self, value: &Rules) {
+        self.rules = value.clone();
+    }
+
     fn check_props_common_js(&self, tehai: Option<&Tehai>) -> Box<[JsValue]> {
         self.check_props(tehai)
             .into_iter()
@@ -486,3 +980,193 @@ impl Env {
         self.check_props_common_js(None)
     }
 }
+
+#[cfg(feature = "wasm")]
+fn tacha_from_str_js(value: &str) -> Result<Tacha, JsValue> {
+    Tacha::into_enum_iter()
+        .find(|tacha| tacha.to_str() == value)
+        .ok_or_else(|| format!("不明な他家です: `{}`", value).into())
+}
+
+#[cfg(all(feature = "wasm", feature = "serde"))]
+#[wasm_bindgen]
+impl Env {
+    #[wasm_bindgen(js_name = "toJSON")]
+    pub fn to_json_js(&self) -> Result<JsValue, JsValue> {
+        crate::serde_str::to_js(self)
+    }
+
+    #[wasm_bindgen(js_name = "fromJSON")]
+    pub fn from_json_js(value: &JsValue) -> Result<Env, JsValue> {
+        crate::serde_str::from_js(value)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use assert_matches::assert_matches;
+
+    #[test]
+    fn parse_props() {
+        use ParseErrorKind::*;
+        let ton = Hai::from_str("1j").unwrap();
+        let mut env = Env::new(ton, ton);
+
+        env.set_richi("daburi").unwrap();
+        assert_eq!(env.richi, Some(RichiType::Daburi));
+        env.set_richi("").unwrap();
+        assert_eq!(env.richi, None);
+        assert_matches!(env.set_richi("foo").unwrap_err().0, UnknownRichi(..));
+
+        env.set_hoju("^").unwrap();
+        assert_eq!(env.hoju, Some(Tacha::Toimen));
+        assert_matches!(env.set_hoju("x").unwrap_err().0, UnknownHoju(..));
+
+        env.set_dora("1m5p").unwrap();
+        assert_eq!(
+            env.dora,
+            [Hai::from_str("1m").unwrap(), Hai::from_str("5p").unwrap()]
+        );
+        assert_matches!(env.set_uradora("x").unwrap_err().0, Dora(..));
+
+        env.set_kawa("19m5$s").unwrap();
+        assert_eq!(env.kawa.len(), 3);
+        assert_matches!(env.set_kawa("x").unwrap_err().0, Kawa(..));
+        assert_matches!(env.set_minogashi("x").unwrap_err().0, Minogashi(..));
+    }
+
+    #[test]
+    fn hai_count() {
+        fn check(tehai: &str, f: impl FnOnce(&mut Env)) -> Vec<String> {
+            let tehai = Tehai::from_str(tehai).unwrap();
+            let ton = Hai::from_str("1j").unwrap();
+            let mut env = Env::new(ton, ton);
+            env.set_dora("9p").unwrap();
+            f(&mut env);
+            env.check_props(Some(&tehai))
+                .into_iter()
+                .map(|(_, message)| message)
+                .collect()
+        }
+
+        assert_eq!(
+            check("11111m3p456s789s1j !1j", |_| {}),
+            ["手牌に `1m` が5枚以上あります (5枚)"]
+        );
+        assert_eq!(
+            check("1111m23p456s789s1j !1j", |env| env.set_dora("1m").unwrap()),
+            ["ドラ表示牌で `1m` が5枚以上になります (手牌4枚、ドラ表示牌1枚)"]
+        );
+
+        // 卓全体の牌 (ロンあがりのあがり牌と鳴いた牌は河で数える)
+        let tehai = "23m456p567s789s11j ?1m";
+        assert!(check(tehai, |env| {
+            env.set_tacha_kawa(Tacha::Kamicha, "1m").unwrap();
+            env.set_tacha_furo(Tacha::Toimen, "<111m").unwrap();
+        })
+        .is_empty());
+        assert_eq!(
+            check(tehai, |env| {
+                env.set_tacha_kawa(Tacha::Kamicha, "111m").unwrap();
+                env.set_tacha_furo(Tacha::Toimen, "<111m").unwrap();
+            }),
+            ["対面の副露で `1m` が5枚以上になります (上家の河3枚、対面の副露2枚)"]
+        );
+
+        // 赤ドラ
+        let tehai = "34m456p567s789s11j !5$m";
+        assert!(check(tehai, |env| env
+            .set_tacha_kawa(Tacha::Kamicha, "5m")
+            .unwrap())
+        .is_empty());
+        assert_eq!(
+            check(tehai, |env| env
+                .set_tacha_kawa(Tacha::Kamicha, "5$m5$p")
+                .unwrap()),
+            ["上家の河で赤ドラの `5$m` が1枚を超えます (手牌1枚、上家の河1枚)"]
+        );
+        assert_eq!(
+            check("5$m5$m5m123p456s789s1j !1j", |_| {}),
+            ["手牌で赤ドラの `5$m` が1枚を超えます (手牌2枚)"]
+        );
+        assert_eq!(
+            check("5$m5$m5m123p456s789s1j !1j", |env| env.rules.akadora =
+                [0, 0, 0]),
+            ["手牌に赤ドラの `5$m` があります (赤ドラなしのルールです)"]
+        );
+    }
+
+    #[test]
+    fn sanma() {
+        use crate::{agari_hai::AgariHai, rules::SanmaTsumo};
+
+        fn check(tehai: &str) -> Vec<String> {
+            let tehai = Tehai::new(
+                JunTehai::from_str(tehai).unwrap(),
+                vec![],
+                AgariHai::from_str("!1j").unwrap(),
+            );
+            let ton = Hai::from_str("1j").unwrap();
+            let mut env = Env::new(ton, ton);
+            env.rules.sanma = Some(SanmaTsumo::Tsumozon);
+            env.set_dora("9p").unwrap();
+            env.check_props(Some(&tehai))
+                .into_iter()
+                .map(|(_, message)| message)
+                .collect()
+        }
+
+        assert!(check("99m123p456s789s1j").is_empty());
+        // パース以外で作った手牌でも 2m〜8m を調べる
+        assert_eq!(
+            check("23m4p123p456s789s1j"),
+            ["三人麻雀で使用しない `2m` が手牌に含まれています"]
+        );
+    }
+
+    #[test]
+    fn furiten() {
+        fn check(tehai: &str, richi: &str, kawa: &str, minogashi: &str) -> Vec<String> {
+            let tehai = Tehai::from_str(tehai).unwrap();
+            let ton = Hai::from_str("1j").unwrap();
+            let mut env = Env::new(ton, Hai::from_str("2j").unwrap());
+            env.set_dora("9p").unwrap();
+            env.set_richi(richi).unwrap();
+            if !richi.is_empty() {
+                env.set_uradora("9p").unwrap();
+            }
+            env.set_kawa(kawa).unwrap();
+            env.set_minogashi(minogashi).unwrap();
+            env.check_props(Some(&tehai))
+                .into_iter()
+                .map(|(_, message)| message)
+                .collect()
+        }
+
+        // 捨て牌フリテン (あがり牌以外の待ち牌を含む)
+        assert_eq!(
+            check("23m456p567s789s11j ?1m", "", "9s4m", ""),
+            ["待ち牌 `4m` が河にあるためフリテンです"]
+        );
+        // 赤ドラも同じ牌として扱う
+        assert_eq!(
+            check("34m456p567s789s11j ?2m", "", "5$m", ""),
+            ["待ち牌 `5m` が河にあるためフリテンです"]
+        );
+        // 同巡内フリテン
+        assert_eq!(
+            check("23m456p567s789s11j ?1m", "", "9s", "4m"),
+            ["待ち牌 `4m` を見逃したため同巡内フリテンです"]
+        );
+        // 立直後の見逃し
+        assert_eq!(
+            check("23m456p567s789s11j ?1m", "richi", "9s", "1m"),
+            ["立直後に待ち牌 `1m` を見逃したためフリテンです"]
+        );
+        // 待ち牌でない牌は関係しない
+        assert!(check("23m456p567s789s11j ?1m", "", "9s7m", "5m").is_empty());
+        // ツモあがりはフリテンでもあがれる
+        assert!(check("23m456p567s789s11j !1m", "", "4m", "1m").is_empty());
+    }
+}
@@ -1,18 +1,21 @@
+#[cfg(feature = "wasm")]
+use crate::hai_image::HaiImage;
 use crate::{
-    env::Env, hai::Hai, hai_category::HaiCategory, hai_image::HaiImage, hai_vec::HaiVec,
-    hai_with_attr::HaiWithAttr, mentsu::Mentsu, tacha::Tacha,
+    env::Env, hai::Hai, hai_category::HaiCategory, hai_vec::HaiVec, hai_with_attr::HaiWithAttr,
+    mentsu::Mentsu, tacha::Tacha,
 };
 use std::{fmt, str::FromStr};
 use thiserror::Error;
+#[cfg(feature = "wasm")]
 use wasm_bindgen::prelude::*;
 
 /// 副露
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Furo(FuroKind);
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
-pub(crate) enum FuroKind {
+pub enum FuroKind {
     /// チー
     Chi {
         from_tehai: [Hai; 2], //< 手牌にあった牌
@@ -49,19 +52,34 @@ impl fmt::Display for Furo {
     }
 }
 
+impl From<FuroKind> for Furo {
+    fn from(kind: FuroKind) -> Self {
+        Self(kind)
+    }
+}
+
 impl Furo {
-    pub(crate) fn kind(&self) -> &FuroKind {
+    pub fn kind(&self) -> &FuroKind {
         &self.0
     }
 
-    pub(crate) fn is_menzen(&self) -> bool {
+    pub fn is_menzen(&self) -> bool {
         matches!(self.0, FuroKind::Ankan { .. })
     }
 
-    pub(crate) fn iter(&self) -> impl Iterator<Item = Hai> + '_ {
+    pub fn iter(&self) -> impl Iterator<Item = Hai> + '_ {
         self.to_vec().0.into_iter().map(|hai| *hai.hai())
     }
 
+    /// 手牌から出した牌 (他家から取得した牌を除く)
+    pub fn iter_from_tehai(&self) -> impl Iterator<Item = Hai> + '_ {
+        self.to_vec()
+            .0
+            .into_iter()
+            .filter(|hai| !matches!(hai, HaiWithAttr::FromTacha(..)))
+            .map(|hai| *hai.hai())
+    }
+
     pub(crate) fn to_vec(self) -> HaiVec {
         use HaiWithAttr::*;
         match self.0 {
@@ -101,6 +119,7 @@ impl Furo {
         }
     }
 
+    #[cfg(feature = "wasm")]
     fn to_image(self) -> Vec<HaiImage> {
         use HaiImage as H;
         match self.0 {
@@ -154,11 +173,12 @@ impl Furo {
         }
     }
 
-    pub(crate) fn compute_fu(&self, env: &Env) -> u32 {
+    pub fn compute_fu(&self, env: &Env) -> u32 {
         Mentsu::from(*self).compute_fu(self.is_menzen(), env)
     }
 }
 
+#[cfg(feature = "wasm")]
 #[wasm_bindgen]
 impl Furo {
     #[wasm_bindgen(js_name = "toHaiArray")]
@@ -329,6 +349,9 @@ impl FromStr for Furo {
     }
 }
 
+#[cfg(feature = "serde")]
+crate::serde_str::impl_serde_str!(Furo);
+
 impl From<Furo> for Mentsu {
     fn from(furo: Furo) -> Self {
         match furo.0 {
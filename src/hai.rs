@@ -3,12 +3,14 @@ use crate::{
     hai_category::HaiCategory,
     hai_with_attr::HaiWithAttr,
 };
-use std::{fmt, str::FromStr};
+use enum_iterator::IntoEnumIterator;
+use std::{fmt, ops::RangeInclusive, str::FromStr};
 use thiserror::Error;
+#[cfg(feature = "wasm")]
 use wasm_bindgen::prelude::*;
 
 /// 牌
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Hai {
     category: HaiCategory,
@@ -36,9 +38,12 @@ impl FromStr for Hai {
     }
 }
 
+#[cfg(feature = "serde")]
+crate::serde_str::impl_serde_str!(Hai);
+
 #[derive(Debug, Error)]
 #[error(transparent)]
-pub(crate) struct NewError(#[from] NewErrorKind);
+pub struct NewError(#[from] NewErrorKind);
 
 #[derive(Debug, Error)]
 enum NewErrorKind {
@@ -49,19 +54,9 @@ enum NewErrorKind {
 }
 
 impl Hai {
-    pub(crate) fn try_new(
-        category: HaiCategory,
-        number: u8,
-        akadora: bool,
-    ) -> Result<Self, NewError> {
+    pub fn try_new(category: HaiCategory, number: u8, akadora: bool) -> Result<Self, NewError> {
         use {HaiCategory::*, NewErrorKind::*};
-        let range = match category {
-            Manzu => (1..=9), // 1-9
-            Pinzu => (1..=9),
-            Souzu => (1..=9),
-            Jihai => (1..=7), // 1-7: 東南西北白發中
-        };
-        if !range.contains(&number) {
+        if !Self::number_range(category).contains(&number) {
             return Err(InvalidNumber { number, category }.into());
         }
         if akadora && (number != 5 || category == Jihai) {
@@ -74,23 +69,44 @@ impl Hai {
         })
     }
 
-    pub(crate) fn category(&self) -> HaiCategory {
+    fn number_range(category: HaiCategory) -> RangeInclusive<u8> {
+        use HaiCategory::*;
+        match category {
+            Manzu => 1..=9, // 1-9
+            Pinzu => 1..=9,
+            Souzu => 1..=9,
+            Jihai => 1..=7, // 1-7: 東南西北白發中
+        }
+    }
+
+    /// 全種類の牌 (赤ドラを除く34種)
+    pub fn all_kinds() -> impl Iterator<Item = Hai> {
+        HaiCategory::into_enum_iter().flat_map(|category| {
+            Self::number_range(category).map(move |number| Hai {
+                category,
+                number,
+                akadora: false,
+            })
+        })
+    }
+
+    pub fn category(&self) -> HaiCategory {
         self.category
     }
 
-    pub(crate) fn number(&self) -> u8 {
+    pub fn number(&self) -> u8 {
         self.number
     }
 
-    pub(crate) fn akadora(&self) -> bool {
+    pub fn akadora(&self) -> bool {
         self.akadora
     }
 
-    pub(crate) fn is_same(&self, other: &Hai) -> bool {
+    pub fn is_same(&self, other: &Hai) -> bool {
         self.category == other.category && self.number == other.number
     }
 
-    pub(crate) fn is_next_to(&self, other: &Hai) -> bool {
+    pub fn is_next_to(&self, other: &Hai) -> bool {
         use HaiCategory::*;
         match (self.category, other.category) {
             (Jihai, Jihai) => matches!(
@@ -104,21 +120,26 @@ impl Hai {
     }
 
     /// 么九牌か否か
-    pub(crate) fn is_yaochuhai(&self) -> bool {
+    pub fn is_yaochuhai(&self) -> bool {
         self.category == HaiCategory::Jihai || self.number == 1 || self.number == 9
     }
 
     /// 風牌か否か
-    pub(crate) fn is_kazehai(&self) -> bool {
+    pub fn is_kazehai(&self) -> bool {
         self.category == HaiCategory::Jihai && (1..=4).contains(&self.number)
     }
 
     /// 三元牌か否か
-    pub(crate) fn is_sangenpai(&self) -> bool {
+    pub fn is_sangenpai(&self) -> bool {
         self.category == HaiCategory::Jihai && (5..=7).contains(&self.number)
     }
 
-    pub(crate) fn to_dora_str(self) -> &'static str {
+    /// 三人麻雀で使用しない牌 (2m〜8m) か否か
+    pub fn is_unused_in_sanma(&self) -> bool {
+        self.category == HaiCategory::Manzu && (2..=8).contains(&self.number)
+    }
+
+    pub fn to_dora_str(self) -> &'static str {
         if self.akadora {
             "$"
         } else {
@@ -127,6 +148,7 @@ impl Hai {
     }
 }
 
+#[cfg(feature = "wasm")]
 #[wasm_bindgen]
 impl Hai {
     #[wasm_bindgen(js_name = "toString")]
@@ -177,6 +199,14 @@ mod test {
         invalid_number(Jihai, 8, false);
     }
 
+    #[test]
+    fn all_kinds() {
+        let all = Hai::all_kinds().collect::<Vec<_>>();
+        assert_eq!(all.len(), 34);
+        assert!(all.windows(2).all(|w| w[0] < w[1]));
+        assert!(all.iter().all(|hai| !hai.akadora()));
+    }
+
     #[test]
     fn parse() {
         use ParseErrorKind::*;
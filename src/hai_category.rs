@@ -3,7 +3,7 @@ use std::fmt;
 
 /// 牌の種類
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, IntoEnumIterator)]
-pub(crate) enum HaiCategory {
+pub enum HaiCategory {
     Manzu, //< 萬子
     Pinzu, //< 筒子
     Souzu, //< 索子
@@ -11,7 +11,7 @@ pub(crate) enum HaiCategory {
 }
 
 impl HaiCategory {
-    pub(crate) fn to_str(self) -> &'static str {
+    pub fn to_str(self) -> &'static str {
         use HaiCategory::*;
         match self {
             Manzu => "m",
@@ -1,7 +1,8 @@
 use crate::hai::Hai;
+#[cfg(feature = "wasm")]
 use wasm_bindgen::prelude::*;
 
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub struct HaiImage(HaiImageKind);
 
@@ -31,6 +32,7 @@ impl HaiImage {
     }
 }
 
+#[cfg(feature = "wasm")]
 #[wasm_bindgen]
 impl HaiImage {
     #[wasm_bindgen(getter, js_name = "type")]
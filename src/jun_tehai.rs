@@ -1,10 +1,13 @@
-use crate::{hai::Hai, hai_image::HaiImage, hai_vec::HaiVec, hai_with_attr::HaiWithAttr};
+#[cfg(feature = "wasm")]
+use crate::hai_image::HaiImage;
+use crate::{hai::Hai, hai_vec::HaiVec, hai_with_attr::HaiWithAttr};
 use std::{fmt, str::FromStr};
 use thiserror::Error;
+#[cfg(feature = "wasm")]
 use wasm_bindgen::prelude::*;
 
 /// 純手牌 (狭義の手牌。手牌のうち副露でないもの)
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct JunTehai(Vec<Hai>);
 
@@ -15,11 +18,15 @@ impl fmt::Display for JunTehai {
 }
 
 impl JunTehai {
-    pub(crate) fn as_slice(&self) -> &[Hai] {
+    pub fn new(hai: Vec<Hai>) -> Self {
+        Self(hai)
+    }
+
+    pub fn as_slice(&self) -> &[Hai] {
         &self.0
     }
 
-    pub(crate) fn iter(&self) -> impl Iterator<Item = Hai> + '_ {
+    pub fn iter(&self) -> impl Iterator<Item = Hai> + '_ {
         self.0.iter().copied()
     }
 
@@ -33,11 +40,13 @@ impl JunTehai {
         )
     }
 
+    #[cfg(feature = "wasm")]
     fn to_image(&self) -> Vec<HaiImage> {
         self.0.iter().copied().map(HaiImage::normal).collect()
     }
 }
 
+#[cfg(feature = "wasm")]
 #[wasm_bindgen]
 impl JunTehai {
     #[wasm_bindgen(js_name = "toHaiArray")]
@@ -87,6 +96,9 @@ impl FromStr for JunTehai {
     }
 }
 
+#[cfg(feature = "serde")]
+crate::serde_str::impl_serde_str!(JunTehai);
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -0,0 +1,192 @@
+use crate::{
+    agari_hai::AgariHai, agari_type::AgariType, env::Env, furo::Furo, hai::Hai,
+    jun_tehai::JunTehai, tehai::Tehai, tenpai, tenpai::MachiHai, yaku::Yaku,
+};
+use num_bigint::BigUint;
+use std::fmt;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+/// 聴牌となる打牌候補の打点の期待値
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Kitaichi {
+    /// 打牌
+    dahai: Hai,
+    /// 待ち牌ごとのツモあがり・ロンあがりの点数
+    machi: Vec<(MachiHai, BigUint, BigUint)>,
+}
+
+impl fmt::Display for Kitaichi {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "打{}: 待ち ", self.dahai)?;
+        for (idx, (machi_hai, _, _)) in self.machi.iter().enumerate() {
+            if idx > 0 {
+                write!(f, "/")?;
+            }
+            write!(f, "{}", machi_hai.hai())?;
+        }
+        write!(f, ", 平均{}点, 最高{}点", self.average(), self.max())
+    }
+}
+
+impl Kitaichi {
+    /// 残り枚数で重み付けした点数の平均 (ツモあがりとロンあがりは同じ重み)
+    pub fn average(&self) -> BigUint {
+        let nokori = self
+            .machi
+            .iter()
+            .map(|(machi_hai, _, _)| machi_hai.nokori())
+            .sum::<usize>();
+        if nokori == 0 {
+            return 0u32.into();
+        }
+        let total = self
+            .machi
+            .iter()
+            .map(|(machi_hai, tsumo, ron)| (tsumo + ron) * machi_hai.nokori())
+            .sum::<BigUint>();
+        total / (nokori * 2)
+    }
+
+    /// 最高点
+    pub fn max(&self) -> BigUint {
+        self.machi
+            .iter()
+            .flat_map(|(_, tsumo, ron)| [tsumo, ron])
+            .max()
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+impl Kitaichi {
+    #[wasm_bindgen(getter = dahai)]
+    pub fn dahai_js(&self) -> Hai {
+        self.dahai
+    }
+
+    #[wasm_bindgen(getter = machi)]
+    pub fn machi_js(&self) -> Box<[JsValue]> {
+        self.machi
+            .iter()
+            .map(|(machi_hai, _, _)| JsValue::from(machi_hai.clone()))
+            .collect()
+    }
+
+    #[wasm_bindgen(getter = average)]
+    pub fn average_js(&self) -> String {
+        self.average().to_string()
+    }
+
+    #[wasm_bindgen(getter = max)]
+    pub fn max_js(&self) -> String {
+        self.max().to_string()
+    }
+
+    #[wasm_bindgen(js_name = "toString")]
+    pub fn to_string_js(&self) -> String {
+        self.to_string()
+    }
+}
+
+/// 14枚の手牌から聴牌となる打牌候補を、打点の期待値が大きい順に列挙する
+pub(crate) fn kitaichi(jun_tehai: &JunTehai, furo: &[Furo], env: &Env) -> Vec<Kitaichi> {
+    assert_eq!(jun_tehai.as_slice().len() + furo.len() * 3, 14);
+
+    let mut dahai_list = Vec::from(jun_tehai.as_slice());
+    dahai_list.sort();
+    dahai_list.dedup();
+
+    let mut res = dahai_list
+        .into_iter()
+        .filter_map(|dahai| {
+            let mut rest = Vec::from(jun_tehai.as_slice());
+            let idx = rest.iter().position(|hai| *hai == dahai).unwrap();
+            rest.remove(idx);
+            let rest = JunTehai::new(rest);
+
+            let machi = tenpai::machi_hai(&rest, furo, &env.rules)
+                .into_iter()
+                .map(|machi_hai| {
+                    let point = |type_| {
+                        let agari_hai = AgariHai::new(type_, machi_hai.hai());
+                        Tehai::new(rest.clone(), furo.to_vec(), agari_hai)
+                            .to_agari_combinations()
+                            .iter()
+                            .map(|agari| Yaku::new(agari, env))
+                            .max()
+                            .map(|yaku| yaku.point().clone())
+                            .unwrap_or_default()
+                    };
+                    let tsumo = point(AgariType::Tsumo);
+                    let ron = point(AgariType::Ron);
+                    (machi_hai, tsumo, ron)
+                })
+                .collect::<Vec<_>>();
+            (!machi.is_empty()).then(|| Kitaichi { dahai, machi })
+        })
+        .collect::<Vec<_>>();
+
+    res.sort_by(|a, b| {
+        b.average()
+            .cmp(&a.average())
+            .then_with(|| b.max().cmp(&a.max()))
+    });
+    res
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{env::RichiType, mochihai::Mochihai, rules::SanmaTsumo};
+    use std::str::FromStr;
+
+    #[test]
+    fn kitaichi() {
+        fn kitaichi(s: &str, env: &Env) -> Vec<String> {
+            Mochihai::from_str(s)
+                .unwrap()
+                .kitaichi(env)
+                .unwrap()
+                .iter()
+                .map(|kitaichi| kitaichi.to_string())
+                .collect()
+        }
+
+        let mut env = Env::new(Hai::from_str("1j").unwrap(), Hai::from_str("2j").unwrap());
+        env.dora = vec![Hai::from_str("1p").unwrap()];
+
+        // 役のないロンあがりは0点
+        assert_eq!(
+            kitaichi("23m456p567s789s11j9m", &env),
+            ["打9m: 待ち 1m/4m, 平均550点, 最高1100点"],
+        );
+
+        // 立直した場合
+        env.richi = Some(RichiType::Richi);
+        assert_eq!(
+            kitaichi("23m456p567s789s11j9m", &env),
+            ["打9m: 待ち 1m/4m, 平均1650点, 最高2000点"],
+        );
+        env.richi = None;
+
+        // 期待値の大きい順 (ドラの2pを残す方が高い)
+        assert_eq!(
+            kitaichi("234m2345p678s5577s", &env),
+            [
+                "打5p: 待ち 5s/7s, 平均3300点, 最高4000点",
+                "打2p: 待ち 5s/7s, 平均1650点, 最高2000点",
+            ],
+        );
+
+        // 三人麻雀では 2m〜8m を待ち牌に数えない
+        env.rules.sanma = Some(SanmaTsumo::Tsumozon);
+        assert_eq!(
+            kitaichi("23m456p567s789s11j9m", &env),
+            ["打9m: 待ち 1m, 平均400点, 最高800点"],
+        );
+    }
+}
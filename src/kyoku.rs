@@ -0,0 +1,876 @@
+//! 局の進行
+//!
+//! 自摸・打牌・副露・立直などのイベントを順に与えて、各席の手牌・副露・河とドラ表示牌を管理する。
+//! あがりの時点の状況 (立直・一発・嶺上開花・海底・天和など) はイベントの履歴から求める。
+
+use crate::{
+    agari_hai::AgariHai,
+    agari_type::AgariType,
+    env::{Env, RichiType, TachaHai},
+    furo::{Furo, FuroKind},
+    hai::Hai,
+    hai_category::HaiCategory,
+    jun_tehai::JunTehai,
+    nukidora::Nukidora,
+    rules::Rules,
+    tacha::Tacha,
+    tehai::Tehai,
+};
+use std::{convert::TryFrom, mem};
+use thiserror::Error;
+#[cfg(feature = "wasm")]
+use {std::str::FromStr, wasm_bindgen::prelude::*};
+
+/// 局の進行のイベント
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    /// 自摸 (他家の自摸など、牌が不明な場合は `None`)
+    Tsumo { seat: u8, hai: Option<Hai> },
+    /// 打牌
+    Dahai { seat: u8, hai: Hai },
+    /// 立直の宣言 (次の打牌が宣言牌)
+    Richi { seat: u8 },
+    /// 副露 (チー・ポン・大明槓・加槓・暗槓)
+    Furo { seat: u8, furo: Furo },
+    /// 抜きドラ (三人麻雀の北抜き)
+    Nukidora { seat: u8 },
+    /// 槓による新ドラ表示牌
+    Dora { hai: Hai },
+}
+
+/// 局の状態
+///
+/// 席は起家から順に 0〜3 (三人麻雀の場合は 0〜2) で表す。
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone)]
+pub struct Kyoku {
+    rules: Rules,
+    bakaze: Hai,
+    oya: u8,
+    honba: u32,
+    kyotaku: u32,
+    dora: Vec<Hai>,
+    players: Vec<Player>,
+    /// 局の開始からの自摸の回数 (嶺上牌を含む)
+    num_tsumo: u32,
+    /// 局の開始から副露 (暗槓を含む) があったか
+    furo_occurred: bool,
+    /// あがりの判定に使う直前のイベント
+    last: Option<Last>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Last {
+    Tsumo { seat: u8 },
+    Dahai { seat: u8, hai: Hai, richi: bool },
+    Kakan { seat: u8, hai: Hai },
+}
+
+#[derive(Debug, Clone, Default)]
+struct Player {
+    /// 手牌 (不明な牌を含む場合は `None`)
+    hand: Option<Vec<Hai>>,
+    furo: Vec<Furo>,
+    nukidora: Vec<Nukidora>,
+    kawa: Vec<Hai>,
+    /// 見逃した牌 (直前の打牌以降。立直している場合は立直以降)
+    minogashi: Vec<Hai>,
+    richi: Option<RichiType>,
+    /// 立直を宣言して、宣言牌をまだ切っていない
+    declaring_richi: bool,
+    ippatsu: bool,
+    num_tsumo: u32,
+    num_dahai: u32,
+    /// 直前に自摸した牌
+    drawn: Option<Hai>,
+    /// 直前の自摸が嶺上牌か
+    rinshan: bool,
+    /// 槓または北抜きをして、嶺上牌をまだ自摸していない
+    kan: bool,
+}
+
+#[derive(Debug, Error)]
+#[error(transparent)]
+pub struct Error(#[from] ErrorKind);
+
+#[derive(Debug, Error)]
+enum ErrorKind {
+    #[error("不正な席です: `{0}`")]
+    InvalidSeat(u8),
+    #[error("配牌の枚数が不正です: {0}枚")]
+    InvalidHaipai(usize),
+    #[error("手牌に `{0}` がありません")]
+    HaiNotFound(Hai),
+    #[error("加槓するポンがありません: `{0}`")]
+    PonNotFound(Furo),
+    #[error("直前の打牌を副露していません: `{0}`")]
+    InvalidFuro(Furo),
+    #[error("{0}番の席はすでに立直しています")]
+    AlreadyRichi(u8),
+    #[error("ドラ表示牌が多すぎます")]
+    TooManyDora,
+    #[error("{0}番の席の手牌が不明です")]
+    UnknownTehai(u8),
+    #[error("手牌の枚数が不正です: {0}枚")]
+    InvalidHaiCount(usize),
+    #[error("{0}番の席があがれる状況ではありません")]
+    NotAgari(u8),
+}
+
+impl Kyoku {
+    /// 局を開始する
+    ///
+    /// `oya` は親の席、`dora` は最初のドラ表示牌。配牌は `set_haipai` で設定する (設定しない席の手牌は不明とする)。
+    pub fn new(rules: Rules, bakaze: Hai, oya: u8, honba: u32, kyotaku: u32, dora: Hai) -> Self {
+        let num_players = if rules.sanma.is_some() { 3 } else { 4 };
+        Self {
+            rules,
+            bakaze,
+            oya,
+            honba,
+            kyotaku,
+            dora: vec![dora],
+            players: vec![Player::default(); num_players],
+            num_tsumo: 0,
+            furo_occurred: false,
+            last: None,
+        }
+    }
+
+    /// 配牌 (13枚) を設定する
+    pub fn set_haipai(&mut self, seat: u8, haipai: Vec<Hai>) -> Result<(), Error> {
+        if haipai.len() != 13 {
+            return Err(ErrorKind::InvalidHaipai(haipai.len()).into());
+        }
+        self.player_mut(seat)?.hand = Some(haipai);
+        Ok(())
+    }
+
+    pub fn num_players(&self) -> u8 {
+        self.players.len() as u8
+    }
+
+    /// 供託 (立直棒の本数、この局で成立した立直を含む)
+    pub fn kyotaku(&self) -> u32 {
+        self.kyotaku
+    }
+
+    /// ドラ表示牌
+    pub fn dora(&self) -> &[Hai] {
+        &self.dora
+    }
+
+    /// 純手牌 (不明な場合は `None`)
+    pub fn jun_tehai(&self, seat: u8) -> Option<JunTehai> {
+        let mut hand = self.player(seat).ok()?.hand.clone()?;
+        hand.sort();
+        Some(JunTehai::new(hand))
+    }
+
+    pub fn furo(&self, seat: u8) -> &[Furo] {
+        self.player(seat).map_or(&[], |player| &player.furo)
+    }
+
+    pub fn nukidora(&self, seat: u8) -> &[Nukidora] {
+        self.player(seat).map_or(&[], |player| &player.nukidora)
+    }
+
+    /// 河 (副露された牌を含む)
+    pub fn kawa(&self, seat: u8) -> &[Hai] {
+        self.player(seat).map_or(&[], |player| &player.kawa)
+    }
+
+    pub fn richi(&self, seat: u8) -> Option<RichiType> {
+        self.player(seat).ok()?.richi
+    }
+
+    fn player(&self, seat: u8) -> Result<&Player, Error> {
+        self.players
+            .get(seat as usize)
+            .ok_or_else(|| ErrorKind::InvalidSeat(seat).into())
+    }
+
+    fn player_mut(&mut self, seat: u8) -> Result<&mut Player, Error> {
+        self.players
+            .get_mut(seat as usize)
+            .ok_or_else(|| ErrorKind::InvalidSeat(seat).into())
+    }
+
+    /// 席の自風 (東:1, 南:2, 西:3, 北:4)
+    fn kaze(&self, seat: u8) -> u8 {
+        let n = self.num_players();
+        (seat + n - self.oya % n) % n + 1
+    }
+
+    /// 海底牌までの自摸の回数 (王牌14枚と配牌を除いた枚数)
+    fn num_wall_tsumo(&self) -> u32 {
+        let n = self.num_players() as u32;
+        let total = if n == 3 { 108 } else { 136 };
+        total - n * 13 - 14
+    }
+
+    /// イベントを反映する
+    pub fn apply(&mut self, event: Event) -> Result<(), Error> {
+        use ErrorKind as E;
+        // 立直の宣言とドラ表示牌はあがりの判定に影響しない
+        match event {
+            Event::Richi { seat } => {
+                let player = self.player_mut(seat)?;
+                if player.richi.is_some() {
+                    return Err(E::AlreadyRichi(seat).into());
+                }
+                player.declaring_richi = true;
+                return Ok(());
+            }
+            Event::Dora { hai } => {
+                if self.dora.len() >= 5 {
+                    return Err(E::TooManyDora.into());
+                }
+                self.dora.push(hai);
+                return Ok(());
+            }
+            _ => {}
+        }
+
+        if let Some(seat) = event.seat() {
+            self.player(seat)?;
+        }
+        if let Event::Furo { seat, furo } = event {
+            self.check_furo(seat, furo)?;
+        }
+        match self.last.take() {
+            Some(Last::Dahai { seat, hai, richi }) => {
+                // 宣言牌が通った時点で立直が成立する
+                if richi {
+                    self.kyotaku += 1;
+                }
+                self.pass(seat, hai);
+            }
+            Some(Last::Kakan { seat, hai }) => {
+                // 槍槓の一発は有効なので、加槓による一発消しは次のイベントで行う
+                self.clear_ippatsu();
+                self.pass(seat, hai);
+            }
+            _ => {}
+        }
+
+        match event {
+            Event::Tsumo { seat, hai } => {
+                self.num_tsumo += 1;
+                let player = self.player_mut(seat)?;
+                player.num_tsumo += 1;
+                player.rinshan = mem::take(&mut player.kan);
+                player.drawn = hai;
+                match hai {
+                    Some(hai) => player.push(hai),
+                    None => player.hand = None,
+                }
+                self.last = Some(Last::Tsumo { seat });
+            }
+            Event::Dahai { seat, hai } => {
+                let first = !self.furo_occurred;
+                let player = self.player_mut(seat)?;
+                player.remove(hai)?;
+                player.kawa.push(hai);
+                if player.richi.is_none() {
+                    player.minogashi.clear();
+                }
+                let richi = mem::take(&mut player.declaring_richi);
+                if richi {
+                    player.richi = Some(if first && player.num_dahai == 0 {
+                        RichiType::Daburi
+                    } else {
+                        RichiType::Richi
+                    });
+                }
+                player.ippatsu = richi;
+                player.num_dahai += 1;
+                player.rinshan = false;
+                self.last = Some(Last::Dahai { seat, hai, richi });
+            }
+            Event::Furo { seat, furo } => self.apply_furo(seat, furo)?,
+            Event::Nukidora { seat } => {
+                let pei = Hai::try_new(HaiCategory::Jihai, 4, false).unwrap();
+                let player = self.player_mut(seat)?;
+                player.remove(pei)?;
+                player.nukidora.push(Nukidora::try_from(pei).unwrap());
+                player.kan = true;
+            }
+            Event::Richi { .. } | Event::Dora { .. } => unreachable!(),
+        }
+        Ok(())
+    }
+
+    /// 直前の打牌を鳴いているか
+    fn check_furo(&self, seat: u8, furo: Furo) -> Result<(), Error> {
+        use ErrorKind as E;
+        let called = |tacha| {
+            let n = self.num_players();
+            let offset = match tacha {
+                Tacha::Shimocha => 1,
+                Tacha::Toimen => 2,
+                Tacha::Kamicha => n - 1,
+            };
+            (seat + offset) % n
+        };
+        let from = match *furo.kind() {
+            FuroKind::Chi { from_kamicha, .. } => Some((called(Tacha::Kamicha), from_kamicha)),
+            FuroKind::Pon {
+                from_tacha, tacha, ..
+            }
+            | FuroKind::Daiminkan {
+                from_tacha, tacha, ..
+            } => Some((called(tacha), from_tacha)),
+            FuroKind::Kakan { .. } | FuroKind::Ankan { .. } => None,
+        };
+        if let Some(from) = from {
+            match self.last {
+                Some(Last::Dahai { seat, hai, .. }) if (seat, hai) == from => {}
+                _ => return Err(E::InvalidFuro(furo).into()),
+            }
+        }
+        Ok(())
+    }
+
+    fn apply_furo(&mut self, seat: u8, furo: Furo) -> Result<(), Error> {
+        use ErrorKind as E;
+        let player = self.player_mut(seat)?;
+        match *furo.kind() {
+            FuroKind::Kakan {
+                from_tehai,
+                from_tacha,
+                tacha,
+                added,
+            } => {
+                let pon = FuroKind::Pon {
+                    from_tehai,
+                    from_tacha,
+                    tacha,
+                };
+                let idx = player
+                    .furo
+                    .iter()
+                    .position(|f| *f.kind() == pon)
+                    .ok_or(E::PonNotFound(furo))?;
+                player.remove(added)?;
+                player.furo[idx] = furo;
+                self.last = Some(Last::Kakan { seat, hai: added });
+            }
+            kind => {
+                let from_tehai: &[Hai] = match &kind {
+                    FuroKind::Chi { from_tehai, .. } | FuroKind::Pon { from_tehai, .. } => {
+                        from_tehai
+                    }
+                    FuroKind::Daiminkan { from_tehai, .. } => from_tehai,
+                    FuroKind::Ankan { from_tehai } => from_tehai,
+                    FuroKind::Kakan { .. } => unreachable!(),
+                };
+                for hai in from_tehai {
+                    player.remove(*hai)?;
+                }
+                player.furo.push(furo);
+            }
+        }
+        let player = self.player_mut(seat)?;
+        player.kan = !matches!(furo.kind(), FuroKind::Chi { .. } | FuroKind::Pon { .. });
+        self.furo_occurred = true;
+        if !matches!(furo.kind(), FuroKind::Kakan { .. }) {
+            self.clear_ippatsu();
+        }
+        Ok(())
+    }
+
+    /// `seat` の席の打牌または加槓の牌を、他家があがらずに見逃す
+    fn pass(&mut self, seat: u8, hai: Hai) {
+        for (idx, player) in self.players.iter_mut().enumerate() {
+            if idx != seat as usize {
+                player.minogashi.push(hai);
+            }
+        }
+    }
+
+    fn clear_ippatsu(&mut self) {
+        for player in &mut self.players {
+            player.ippatsu = false;
+        }
+    }
+
+    /// `seat` の席のあがりの手牌と状況を求める
+    ///
+    /// 直前のイベントが `seat` の自摸ならツモあがり、他家の打牌ならロンあがり、他家の加槓なら槍槓とする。
+    /// 裏ドラ表示牌は局の進行からは分からないため、必要に応じて `Env::uradora` に設定する。
+    pub fn agari(&self, seat: u8) -> Result<(Tehai, Env), Error> {
+        use ErrorKind as E;
+        let player = self.player(seat)?;
+        let mut hand = player.hand.clone().ok_or(E::UnknownTehai(seat))?;
+        let hai = match self.last {
+            Some(Last::Tsumo { .. }) => {
+                let hai = player.drawn.ok_or(E::UnknownTehai(seat))?;
+                player.remove_from(&mut hand, hai)?;
+                hai
+            }
+            Some(Last::Dahai { hai, .. }) | Some(Last::Kakan { hai, .. }) => hai,
+            None => return Err(E::NotAgari(seat).into()),
+        };
+        self.agari_with(seat, hand, hai)
+    }
+
+    /// 和了牌を除いた純手牌と和了牌を指定して、`seat` の席のあがりの手牌と状況を求める
+    ///
+    /// 他家の手牌など、局の進行から手牌が分からない席のあがりに使う。
+    pub fn agari_with(
+        &self,
+        seat: u8,
+        jun_tehai: Vec<Hai>,
+        hai: Hai,
+    ) -> Result<(Tehai, Env), Error> {
+        use ErrorKind as E;
+        let player = self.player(seat)?;
+        let (type_, from, chankan) = match self.last {
+            Some(Last::Tsumo { seat: s }) if s == seat => (AgariType::Tsumo, seat, false),
+            Some(Last::Dahai { seat: s, .. }) if s != seat => (AgariType::Ron, s, false),
+            Some(Last::Kakan { seat: s, .. }) if s != seat => (AgariType::Ron, s, true),
+            _ => return Err(E::NotAgari(seat).into()),
+        };
+        let num_hai = jun_tehai.len() + player.furo.len() * 3;
+        if num_hai != 13 {
+            return Err(E::InvalidHaiCount(num_hai + 1).into());
+        }
+
+        let mut jun_tehai = jun_tehai;
+        jun_tehai.sort();
+        let tehai = Tehai::new(
+            JunTehai::new(jun_tehai),
+            player.furo.clone(),
+            AgariHai::new(type_, hai),
+        )
+        .with_nukidora(player.nukidora.clone());
+
+        let jikaze = |seat| Hai::try_new(HaiCategory::Jihai, self.kaze(seat), false).unwrap();
+        let mut env = Env::new(self.bakaze, jikaze(seat));
+        env.rules = self.rules.clone();
+        env.honba = self.honba;
+        env.kyotaku = self.kyotaku;
+        env.dora = self.dora.clone();
+        env.kawa = player.kawa.clone();
+        env.minogashi = player.minogashi.clone();
+        for (idx, tacha_player) in self.players.iter().enumerate() {
+            let kaze = self.kaze(idx as u8);
+            if let Some(tacha) = env.tacha().find(|tacha| env.tacha_kaze(*tacha) == kaze) {
+                env.tacha_hai.insert(
+                    tacha,
+                    TachaHai {
+                        kawa: tacha_player.kawa.clone(),
+                        furo: tacha_player.furo.clone(),
+                        nukidora: tacha_player.nukidora.clone(),
+                    },
+                );
+            }
+        }
+        if type_ == AgariType::Ron {
+            env.hoju = env
+                .tacha()
+                .find(|tacha| env.tacha_kaze(*tacha) == self.kaze(from));
+        }
+        env.richi = player.richi;
+        env.ippatsu = player.ippatsu;
+        env.rinshan = (type_ == AgariType::Tsumo && player.rinshan) || chankan;
+        env.haitei = self.num_tsumo == self.num_wall_tsumo() && !env.rinshan;
+        let first = !self.furo_occurred && player.num_dahai == 0;
+        env.tenho = type_ == AgariType::Tsumo && first;
+        env.renho = type_ == AgariType::Ron && first && player.num_tsumo == 0;
+        Ok((tehai, env))
+    }
+}
+
+impl Event {
+    /// イベントを起こした席 (ドラ表示牌の場合は `None`)
+    pub fn seat(&self) -> Option<u8> {
+        match *self {
+            Event::Tsumo { seat, .. }
+            | Event::Dahai { seat, .. }
+            | Event::Richi { seat }
+            | Event::Furo { seat, .. }
+            | Event::Nukidora { seat } => Some(seat),
+            Event::Dora { .. } => None,
+        }
+    }
+}
+
+impl Player {
+    fn push(&mut self, hai: Hai) {
+        if let Some(hand) = &mut self.hand {
+            hand.push(hai);
+        }
+    }
+
+    fn remove(&mut self, hai: Hai) -> Result<(), Error> {
+        if let Some(mut hand) = self.hand.take() {
+            let res = self.remove_from(&mut hand, hai);
+            self.hand = Some(hand);
+            res?;
+        }
+        Ok(())
+    }
+
+    fn remove_from(&self, hand: &mut Vec<Hai>, hai: Hai) -> Result<(), Error> {
+        let idx = hand
+            .iter()
+            .position(|h| *h == hai)
+            .ok_or(ErrorKind::HaiNotFound(hai))?;
+        hand.remove(idx);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+impl Kyoku {
+    #[wasm_bindgen(constructor)]
+    pub fn new_js(
+        rules: &Rules,
+        bakaze: Hai,
+        oya: u8,
+        honba: u32,
+        kyotaku: u32,
+        dora: Hai,
+    ) -> Kyoku {
+        Self::new(rules.clone(), bakaze, oya, honba, kyotaku, dora)
+    }
+
+    #[wasm_bindgen(js_name = "setHaipai")]
+    pub fn set_haipai_js(&mut self, seat: u8, haipai: &str) -> Result<(), JsValue> {
+        let haipai = JunTehai::from_str(haipai).map_err(|e| e.to_string())?;
+        self.set_haipai(seat, haipai.as_slice().to_vec())
+            .map_err(|e| e.to_string().into())
+    }
+
+    /// 牌が不明な自摸は `hai` を省略する
+    #[wasm_bindgen(js_name = "tsumo")]
+    pub fn tsumo_js(&mut self, seat: u8, hai: Option<Hai>) -> Result<(), JsValue> {
+        self.apply_js(Event::Tsumo { seat, hai })
+    }
+
+    #[wasm_bindgen(js_name = "dahai")]
+    pub fn dahai_js(&mut self, seat: u8, hai: Hai) -> Result<(), JsValue> {
+        self.apply_js(Event::Dahai { seat, hai })
+    }
+
+    #[wasm_bindgen(js_name = "richi")]
+    pub fn richi_js(&mut self, seat: u8) -> Result<(), JsValue> {
+        self.apply_js(Event::Richi { seat })
+    }
+
+    #[wasm_bindgen(js_name = "furo")]
+    pub fn furo_js(&mut self, seat: u8, furo: &str) -> Result<(), JsValue> {
+        let furo = Furo::from_str(furo).map_err(|e| e.to_string())?;
+        self.apply_js(Event::Furo { seat, furo })
+    }
+
+    #[wasm_bindgen(js_name = "nukidora")]
+    pub fn nukidora_js(&mut self, seat: u8) -> Result<(), JsValue> {
+        self.apply_js(Event::Nukidora { seat })
+    }
+
+    #[wasm_bindgen(js_name = "dora")]
+    pub fn dora_js(&mut self, hai: Hai) -> Result<(), JsValue> {
+        self.apply_js(Event::Dora { hai })
+    }
+
+    fn apply_js(&mut self, event: Event) -> Result<(), JsValue> {
+        self.apply(event).map_err(|e| e.to_string().into())
+    }
+
+    #[wasm_bindgen(js_name = "agariTehai")]
+    pub fn agari_tehai_js(&self, seat: u8) -> Result<Tehai, JsValue> {
+        let (tehai, _) = self.agari(seat).map_err(|e| e.to_string())?;
+        Ok(tehai)
+    }
+
+    #[wasm_bindgen(js_name = "agariEnv")]
+    pub fn agari_env_js(&self, seat: u8) -> Result<Env, JsValue> {
+        let (_, env) = self.agari(seat).map_err(|e| e.to_string())?;
+        Ok(env)
+    }
+
+    #[wasm_bindgen(js_name = "junTehai")]
+    pub fn jun_tehai_js(&self, seat: u8) -> Option<JunTehai> {
+        self.jun_tehai(seat)
+    }
+
+    #[wasm_bindgen(js_name = "kawa")]
+    pub fn kawa_js(&self, seat: u8) -> Box<[JsValue]> {
+        self.kawa(seat).iter().copied().map(JsValue::from).collect()
+    }
+
+    #[wasm_bindgen(getter = kyotaku)]
+    pub fn kyotaku_js(&self) -> u32 {
+        self.kyotaku
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::yaku::Yaku;
+    use assert_matches::assert_matches;
+    use std::str::FromStr;
+
+    fn hai(s: &str) -> Hai {
+        Hai::from_str(s).unwrap()
+    }
+
+    fn haipai(s: &str) -> Vec<Hai> {
+        JunTehai::from_str(s).unwrap().as_slice().to_vec()
+    }
+
+    fn new_kyoku() -> Kyoku {
+        Kyoku::new(Rules::default(), hai("1j"), 0, 0, 0, hai("9m"))
+    }
+
+    fn point(tehai: &Tehai, env: &Env) -> u32 {
+        let yaku = tehai
+            .to_agari_combinations()
+            .iter()
+            .map(|agari| Yaku::new(agari, env))
+            .max()
+            .unwrap();
+        yaku.point().to_string().parse().unwrap()
+    }
+
+    #[test]
+    fn ron() {
+        use Event::*;
+        let mut kyoku = new_kyoku();
+        kyoku.set_haipai(1, haipai("123456m234p6799s")).unwrap();
+        let events = [
+            Tsumo { seat: 0, hai: None },
+            Dahai {
+                seat: 0,
+                hai: hai("1j"),
+            },
+            Tsumo {
+                seat: 1,
+                hai: Some(hai("1j")),
+            },
+            Richi { seat: 1 },
+            Dahai {
+                seat: 1,
+                hai: hai("1j"),
+            },
+            Tsumo { seat: 2, hai: None },
+            Dahai {
+                seat: 2,
+                hai: hai("2j"),
+            },
+            Tsumo { seat: 3, hai: None },
+            Dahai {
+                seat: 3,
+                hai: hai("5s"),
+            },
+        ];
+        for event in events.iter() {
+            kyoku.apply(*event).unwrap();
+        }
+        assert_eq!(kyoku.kawa(1), &[hai("1j")]);
+        assert_eq!(kyoku.richi(1), Some(RichiType::Daburi));
+        assert_eq!(kyoku.kyotaku(), 1);
+
+        let (tehai, env) = kyoku.agari(1).unwrap();
+        assert_eq!(tehai.to_string(), "123456m234p6799s ?5s");
+        assert_eq!(env.jikaze, hai("2j"));
+        assert_eq!(env.hoju, Some(Tacha::Toimen));
+        assert_eq!(env.richi, Some(RichiType::Daburi));
+        assert!(env.ippatsu && !env.rinshan && !env.haitei && !env.renho);
+        assert_matches!(kyoku.agari(0), Err(_));
+
+        // 一巡して一発は消える
+        kyoku.apply(Tsumo { seat: 0, hai: None }).unwrap();
+        kyoku
+            .apply(Dahai {
+                seat: 0,
+                hai: hai("8s"),
+            })
+            .unwrap();
+        kyoku
+            .apply(Tsumo {
+                seat: 1,
+                hai: Some(hai("1p")),
+            })
+            .unwrap();
+        kyoku
+            .apply(Dahai {
+                seat: 1,
+                hai: hai("1p"),
+            })
+            .unwrap();
+        kyoku.apply(Tsumo { seat: 2, hai: None }).unwrap();
+        kyoku
+            .apply(Dahai {
+                seat: 2,
+                hai: hai("8s"),
+            })
+            .unwrap();
+        let (tehai, env) = kyoku.agari(1).unwrap();
+        assert_eq!(env.hoju, Some(Tacha::Shimocha));
+        assert!(!env.ippatsu);
+        // 立直後に 5s を見逃している
+        assert_eq!(env.kawa, [hai("1j"), hai("1p")]);
+        assert_eq!(env.minogashi, [hai("2j"), hai("5s"), hai("8s")]);
+        assert!(env
+            .check_props(Some(&tehai))
+            .iter()
+            .any(|(_, message)| message.contains("見逃したためフリテン")));
+    }
+
+    #[test]
+    fn chankan() {
+        use Event::*;
+        let mut kyoku = new_kyoku();
+        kyoku.set_haipai(0, haipai("123m456p55789s99p")).unwrap();
+        kyoku.set_haipai(3, haipai("123456m123p456s9p")).unwrap();
+        kyoku
+            .apply(Tsumo {
+                seat: 0,
+                hai: Some(hai("1m")),
+            })
+            .unwrap();
+        kyoku
+            .apply(Dahai {
+                seat: 0,
+                hai: hai("9s"),
+            })
+            .unwrap();
+        kyoku.apply(Tsumo { seat: 1, hai: None }).unwrap();
+        kyoku
+            .apply(Dahai {
+                seat: 1,
+                hai: hai("9p"),
+            })
+            .unwrap();
+        let pon = super::Furo::from_str(">999p").unwrap();
+        assert_matches!(kyoku.apply(Furo { seat: 2, furo: pon }), Err(_));
+        kyoku.apply(Furo { seat: 0, furo: pon }).unwrap();
+        kyoku
+            .apply(Dahai {
+                seat: 0,
+                hai: hai("1m"),
+            })
+            .unwrap();
+        kyoku.apply(Tsumo { seat: 1, hai: None }).unwrap();
+        kyoku
+            .apply(Dahai {
+                seat: 1,
+                hai: hai("1j"),
+            })
+            .unwrap();
+        kyoku.apply(Tsumo { seat: 2, hai: None }).unwrap();
+        kyoku
+            .apply(Dahai {
+                seat: 2,
+                hai: hai("1j"),
+            })
+            .unwrap();
+        kyoku
+            .apply(Tsumo {
+                seat: 3,
+                hai: Some(hai("1p")),
+            })
+            .unwrap();
+        kyoku
+            .apply(Dahai {
+                seat: 3,
+                hai: hai("1p"),
+            })
+            .unwrap();
+        kyoku
+            .apply(Tsumo {
+                seat: 0,
+                hai: Some(hai("9p")),
+            })
+            .unwrap();
+        let kakan = super::Furo::from_str(">999+9p").unwrap();
+        kyoku
+            .apply(Furo {
+                seat: 0,
+                furo: kakan,
+            })
+            .unwrap();
+        assert_eq!(kyoku.furo(0), &[kakan]);
+
+        // 槍槓
+        let (tehai, env) = kyoku.agari(3).unwrap();
+        assert_eq!(tehai.agari_hai().type_(), AgariType::Ron);
+        assert_eq!(env.hoju, Some(Tacha::Shimocha));
+        assert!(env.rinshan);
+
+        // 嶺上開花
+        kyoku.apply(Dora { hai: hai("1s") }).unwrap();
+        kyoku
+            .apply(Tsumo {
+                seat: 0,
+                hai: Some(hai("6s")),
+            })
+            .unwrap();
+        let (tehai, env) = kyoku.agari(0).unwrap();
+        assert_eq!(tehai.to_string(), "123m456p5578s >999+9p !6s");
+        assert!(env.rinshan && !env.tenho);
+        assert_eq!(env.dora, vec![hai("9m"), hai("1s")]);
+        assert_eq!(point(&tehai, &env), 3900);
+    }
+
+    #[test]
+    fn haitei() {
+        use Event::*;
+        let mut kyoku = new_kyoku();
+        kyoku.apply(Tsumo { seat: 0, hai: None }).unwrap();
+        let (_, env) = kyoku
+            .agari_with(0, haipai("123456m234p6799s"), hai("9s"))
+            .unwrap();
+        assert!(env.tenho);
+        for i in 1..70 {
+            let seat = (i % 4) as u8;
+            kyoku
+                .apply(Dahai {
+                    seat: (seat + 3) % 4,
+                    hai: hai("2j"),
+                })
+                .unwrap();
+            kyoku.apply(Tsumo { seat, hai: None }).unwrap();
+        }
+        let (_, env) = kyoku
+            .agari_with(1, haipai("123456m234p6799s"), hai("8s"))
+            .unwrap();
+        assert!(env.haitei && !env.tenho);
+    }
+
+    #[test]
+    fn error() {
+        use Event::*;
+        let mut kyoku = new_kyoku();
+        assert_matches!(kyoku.set_haipai(0, haipai("123m")), Err(_));
+        assert_matches!(kyoku.set_haipai(4, haipai("123456m234p6799s")), Err(_));
+        assert_matches!(kyoku.apply(Tsumo { seat: 4, hai: None }), Err(_));
+        kyoku.set_haipai(0, haipai("123456m234p6799s")).unwrap();
+        assert_matches!(
+            kyoku.apply(Dahai {
+                seat: 0,
+                hai: hai("1j")
+            }),
+            Err(_)
+        );
+        kyoku.apply(Richi { seat: 0 }).unwrap();
+        kyoku
+            .apply(Dahai {
+                seat: 0,
+                hai: hai("9s"),
+            })
+            .unwrap();
+        assert_matches!(kyoku.apply(Richi { seat: 0 }), Err(_));
+        for _ in 0..4 {
+            kyoku.apply(Dora { hai: hai("1m") }).unwrap();
+        }
+        assert_matches!(kyoku.apply(Dora { hai: hai("1m") }), Err(_));
+        assert_matches!(kyoku.agari(1), Err(_));
+    }
+}
@@ -1,28 +1,49 @@
+#[cfg(feature = "wasm")]
 use wasm_bindgen::prelude::*;
 
-mod agari;
-mod agari_hai;
-mod agari_type;
-mod env;
-mod furo;
-mod hai;
+pub mod agari;
+pub mod agari_hai;
+pub mod agari_type;
+#[cfg(feature = "cli")]
+pub mod cli;
+pub mod env;
+pub mod furo;
+pub mod hai;
 mod hai_builder;
-mod hai_category;
+pub mod hai_category;
+#[cfg(feature = "wasm")]
 mod hai_image;
 mod hai_vec;
 mod hai_with_attr;
-mod jun_tehai;
+pub mod jun_tehai;
+pub mod kitaichi;
 mod kotsu_candidates;
-mod machi;
+pub mod kyoku;
+pub mod machi;
 mod machi_combinations;
-mod mentsu;
+pub mod mentsu;
 mod mentsu_combinations;
-mod rank;
+#[cfg(feature = "mjai")]
+pub mod mjai;
+pub mod mochihai;
+pub mod multi_ron;
+pub mod nukidora;
+pub mod payment;
+pub mod rank;
+pub mod rules;
+pub mod ryukyoku;
+#[cfg(feature = "serde")]
+mod serde_str;
+pub mod shanten;
 mod shuntsu_candidates;
-mod tacha;
-mod tehai;
+pub mod tacha;
+pub mod tehai;
+#[cfg(feature = "tenhou")]
+pub mod tenhou;
+pub mod tenpai;
 mod toitsu_candidates;
-mod yaku;
+pub mod ukeire;
+pub mod yaku;
 
 // When the `wee_alloc` feature is enabled, this uses `wee_alloc` as the global
 // allocator.
@@ -33,6 +54,7 @@ mod yaku;
 static ALLOC: wee_alloc::WeeAlloc = wee_alloc::WeeAlloc::INIT;
 
 // This is like the `main` function, except for JavaScript.
+#[cfg(feature = "wasm")]
 #[wasm_bindgen(start)]
 pub fn main_js() -> Result<(), JsValue> {
     // This provides better error messages in debug mode.
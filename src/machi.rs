@@ -1,6 +1,11 @@
+#[cfg(feature = "serde")]
+use serde::Serialize;
+use std::fmt;
+
 /// 待ち
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "lowercase"))]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
-pub(crate) enum Machi {
+pub enum Machi {
     /// 両面待ち
     Ryanmen,
     /// 双碰待ち
@@ -14,10 +19,26 @@ pub(crate) enum Machi {
 }
 
 impl Machi {
-    pub(crate) fn compute_fu(&self) -> u32 {
+    pub fn to_str(self) -> &'static str {
+        match self {
+            Machi::Ryanmen => "両面",
+            Machi::Shanpon => "双碰",
+            Machi::Kanchan => "嵌張",
+            Machi::Penchan => "辺張",
+            Machi::Tanki => "単騎",
+        }
+    }
+
+    pub fn compute_fu(&self) -> u32 {
         match self {
             Machi::Ryanmen | Machi::Shanpon => 0,
             Machi::Kanchan | Machi::Penchan | Machi::Tanki => 2,
         }
     }
 }
+
+impl fmt::Display for Machi {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_str())
+    }
+}
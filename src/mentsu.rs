@@ -1,13 +1,14 @@
 use crate::{env::Env, hai::Hai, hai_vec::HaiVec, hai_with_attr::HaiWithAttr, machi::Machi};
 use std::fmt;
+#[cfg(feature = "wasm")]
 use wasm_bindgen::prelude::*;
 
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Mentsu(MentsuKind);
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
-pub(crate) enum MentsuKind {
+pub enum MentsuKind {
     /// 順子
     Shuntsu([Hai; 3]),
     /// 刻子
@@ -21,11 +22,11 @@ pub(crate) enum MentsuKind {
 }
 
 impl Mentsu {
-    pub(crate) fn kind(&self) -> &MentsuKind {
+    pub fn kind(&self) -> &MentsuKind {
         &self.0
     }
 
-    pub(crate) fn head(&self) -> Hai {
+    pub fn head(&self) -> Hai {
         match self.0 {
             MentsuKind::Shuntsu([h0, ..]) => h0,
             MentsuKind::Kotsu([h0, ..]) => h0,
@@ -35,29 +36,29 @@ impl Mentsu {
         }
     }
 
-    pub(crate) fn shuntsu(hai: [Hai; 3]) -> Self {
+    pub fn shuntsu(hai: [Hai; 3]) -> Self {
         debug_assert!(hai[1].is_next_to(&hai[0]) && hai[2].is_next_to(&hai[1]));
         Self(MentsuKind::Shuntsu(hai))
     }
 
-    pub(crate) fn kotsu(hai: [Hai; 3]) -> Self {
+    pub fn kotsu(hai: [Hai; 3]) -> Self {
         debug_assert!(hai[0].is_same(&hai[1]) && hai[0].is_same(&hai[2]));
         Self(MentsuKind::Kotsu(hai))
     }
 
-    pub(crate) fn kantsu(hai: [Hai; 4]) -> Self {
+    pub fn kantsu(hai: [Hai; 4]) -> Self {
         debug_assert!(
             hai[0].is_same(&hai[1]) && hai[0].is_same(&hai[2]) && hai[0].is_same(&hai[3])
         );
         Self(MentsuKind::Kantsu(hai))
     }
 
-    pub(crate) fn toitsu(hai: [Hai; 2]) -> Self {
+    pub fn toitsu(hai: [Hai; 2]) -> Self {
         debug_assert!(hai[0].is_same(&hai[1]));
         Self(MentsuKind::Toitsu(hai))
     }
 
-    pub(crate) fn single(hai: [Hai; 1]) -> Self {
+    pub fn single(hai: [Hai; 1]) -> Self {
         Self(MentsuKind::Single(hai))
     }
 
@@ -71,7 +72,7 @@ impl Mentsu {
         }
     }
 
-    pub(crate) fn iter(&self) -> impl Iterator<Item = Hai> + '_ {
+    pub fn iter(&self) -> impl Iterator<Item = Hai> + '_ {
         self.as_slice().iter().copied()
     }
 
@@ -87,7 +88,7 @@ impl Mentsu {
         }
     }
 
-    pub(crate) fn to_machi(self, agari: Hai) -> Option<Machi> {
+    pub fn to_machi(self, agari: Hai) -> Option<Machi> {
         match self.0 {
             MentsuKind::Shuntsu([h0, h1, h2]) => {
                 if h2.is_same(&agari) {
@@ -114,7 +115,7 @@ impl Mentsu {
         }
     }
 
-    pub(crate) fn compute_fu(&self, is_menzen: bool, env: &Env) -> u32 {
+    pub fn compute_fu(&self, is_menzen: bool, env: &Env) -> u32 {
         match self.0 {
             MentsuKind::Shuntsu(_) | MentsuKind::Single(_) => 0,
             MentsuKind::Kotsu([h0, ..]) => match (is_menzen, h0.is_yaochuhai()) {
@@ -134,9 +135,9 @@ impl Mentsu {
                     2
                 } else {
                     match (h0 == env.bakaze, h0 == env.jikaze) {
-                        (true, true) => 4,                  // 連風牌
-                        (false, true) | (true, false) => 2, // 自風牌 or 場風牌
-                        (false, false) => 0,                // 他
+                        (true, true) => env.rules.renpuhai_fu, // 連風牌
+                        (false, true) | (true, false) => 2,    // 自風牌 or 場風牌
+                        (false, false) => 0,                   // 他
                     }
                 }
             }
@@ -144,6 +145,7 @@ impl Mentsu {
     }
 }
 
+#[cfg(feature = "wasm")]
 #[wasm_bindgen]
 impl Mentsu {
     #[wasm_bindgen(js_name = "toString")]
@@ -197,7 +199,7 @@ mod test {
     }
 
     fn new_env(bakaze: Hai, jikaze: Hai) -> Env {
-        Env::new_empty(bakaze, jikaze)
+        Env::new(bakaze, jikaze)
     }
 
     #[test]
@@ -323,5 +325,9 @@ mod test {
         for (s, k) in &[("11j", ton), ("22j", nan), ("33j", sha), ("44j", pe)] {
             assert_eq!(toitsu(s, *k, *k), 4);
         }
+        // 連風牌を2符とするルール
+        let mut env = new_env(nan, nan);
+        env.rules.renpuhai_fu = 2;
+        assert_eq!(new_toitsu("22j").compute_fu(true, &env), 2);
     }
 }
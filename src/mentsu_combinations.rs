@@ -1,6 +1,10 @@
 use crate::{
-    hai::Hai, hai_category::HaiCategory, kotsu_candidates::KotsuCandidates, mentsu::Mentsu,
-    shuntsu_candidates::ShuntsuCandidates, toitsu_candidates::ToitsuCandidates,
+    hai::Hai,
+    hai_category::HaiCategory,
+    kotsu_candidates::KotsuCandidates,
+    mentsu::{Mentsu, MentsuKind},
+    shuntsu_candidates::ShuntsuCandidates,
+    toitsu_candidates::ToitsuCandidates,
 };
 
 pub(crate) fn combinations(hai: &[Hai]) -> Vec<Vec<Mentsu>> {
@@ -94,6 +98,46 @@ fn to_kokushi(hai: &[Hai]) -> Option<Vec<Mentsu>> {
     Some(tehai_mentsu)
 }
 
+/// 十三不塔の形 (1組の対子と、面子・塔子にならない12枚) の場合は対子と単独牌に分ける
+///
+/// 和了形ではないため `combinations` には含めない。国士無双の形は国士無双として扱うため含めない。
+pub(crate) fn to_shisanputa(hai: &[Hai]) -> Option<Vec<Mentsu>> {
+    if hai.len() != 14 || to_kokushi(hai).is_some() {
+        return None;
+    }
+    let mut hai = hai.to_vec();
+    hai.sort();
+    let mut res: Vec<Mentsu> = vec![];
+    for h in hai {
+        match res.last() {
+            Some(prev) if prev.head().is_same(&h) => {
+                // 対子は1組のみ (刻子は不可)
+                if res
+                    .iter()
+                    .any(|m| matches!(m.kind(), MentsuKind::Toitsu(..)))
+                {
+                    return None;
+                }
+                let prev = res.pop().unwrap().head();
+                res.push(Mentsu::toitsu([prev, h]));
+            }
+            // 同じ種類の数牌で2つ以内の差は塔子になる
+            Some(prev)
+                if h.category() != HaiCategory::Jihai
+                    && prev.head().category() == h.category()
+                    && h.number() - prev.head().number() <= 2 =>
+            {
+                return None;
+            }
+            _ => res.push(Mentsu::single([h])),
+        }
+    }
+    (res.len() == 13).then(|| {
+        res.sort();
+        res
+    })
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -199,6 +243,30 @@ mod test {
         }
     }
 
+    #[test]
+    fn shisanputa() {
+        fn test(input: &str) -> Option<String> {
+            let hai_vec = JunTehai::from_str(input).unwrap();
+            to_shisanputa(hai_vec.as_slice()).map(|comb| {
+                comb.iter()
+                    .map(|mentsu| mentsu.to_string())
+                    .collect::<Vec<_>>()
+                    .join(",")
+            })
+        }
+
+        assert_eq!(
+            test("147m258p369s1235j5j").as_deref(),
+            Some("55j,1m,4m,7m,2p,5p,8p,3s,6s,9s,1j,2j,3j")
+        );
+        // 塔子がある
+        assert_eq!(test("146m258p369s1235j5j"), None);
+        // 対子が2組ある
+        assert_eq!(test("147m258p369s1135j5j"), None);
+        // 国士無双
+        assert_eq!(test("19m19p19s12345677j"), None);
+    }
+
     // test data from https://www.engineer-log.com/entry/2018/06/14/mahjong-algorithm
     const DATA: &[&str] = &[
         "23333444556688m",
@@ -0,0 +1,567 @@
+//! mjai プロトコルとの相互変換 (`mjai` feature)
+//!
+//! mjai の牌の名前 (`5mr`, `E`, `P` など) と `Hai`、副露のイベントと `Furo` を相互に変換する。
+//! `MjaiGame` に局のイベントを順に与えると、`hora` イベントから点数計算用の手牌と状況を復元できる。
+//! 四人麻雀のみに対応する。
+
+use crate::{
+    env::Env,
+    furo::{Furo, FuroKind},
+    hai::Hai,
+    hai_category::HaiCategory,
+    kyoku::{self, Event as KyokuEvent, Kyoku},
+    rules::Rules,
+    tacha::Tacha,
+    tehai::Tehai,
+};
+use serde_json::{json, Value};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+#[error(transparent)]
+pub struct Error(#[from] ErrorKind);
+
+#[derive(Debug, Error)]
+enum ErrorKind {
+    #[error("不正な牌です: `{0}`")]
+    InvalidPai(String),
+    #[error("イベント `{0}` に `{1}` がないか、値が不正です")]
+    InvalidField(String, &'static str),
+    #[error("不正な席です: `{0}`")]
+    InvalidSeat(u64),
+    #[error("副露の牌の組み合わせが不正です: `{0}`")]
+    InvalidFuro(String),
+    #[error("加槓するポンがありません: `{0}`")]
+    PonNotFound(Hai),
+    #[error("手牌に `{0}` がありません")]
+    HaiNotFound(Hai),
+    #[error("{0}番の席の手牌が不明です")]
+    UnknownTehai(u8),
+    #[error("局が開始していません")]
+    NoKyoku,
+    #[error(transparent)]
+    Kyoku(#[from] kyoku::Error),
+}
+
+/// mjai の牌の名前を牌に変換する
+///
+/// 数牌は `1m`〜`9s` (赤ドラは `5mr` のように末尾に `r`)、字牌は `E`, `S`, `W`, `N`, `P` (白), `F` (發), `C` (中)。
+pub fn from_pai(pai: &str) -> Result<Hai, Error> {
+    let invalid = || Error::from(ErrorKind::InvalidPai(pai.into()));
+    let jihai = |number| Hai::try_new(HaiCategory::Jihai, number, false).unwrap();
+    let hai = match pai {
+        "E" => jihai(1),
+        "S" => jihai(2),
+        "W" => jihai(3),
+        "N" => jihai(4),
+        "P" => jihai(5),
+        "F" => jihai(6),
+        "C" => jihai(7),
+        _ => {
+            let (s, akadora) = match pai.strip_suffix('r') {
+                Some(s) => (s, true),
+                None => (pai, false),
+            };
+            let mut chars = s.chars();
+            let (number, category) = match (chars.next(), chars.next(), chars.next()) {
+                (Some(number), Some(category), None) => (number, category),
+                _ => return Err(invalid()),
+            };
+            let number = number.to_digit(10).ok_or_else(invalid)? as u8;
+            let category = match category {
+                'm' => HaiCategory::Manzu,
+                'p' => HaiCategory::Pinzu,
+                's' => HaiCategory::Souzu,
+                _ => return Err(invalid()),
+            };
+            Hai::try_new(category, number, akadora).map_err(|_| invalid())?
+        }
+    };
+    Ok(hai)
+}
+
+/// 牌を mjai の牌の名前に変換する
+pub fn to_pai(hai: Hai) -> String {
+    match hai.category() {
+        HaiCategory::Jihai => ["E", "S", "W", "N", "P", "F", "C"][hai.number() as usize - 1].into(),
+        category => format!(
+            "{}{}{}",
+            hai.number(),
+            category,
+            if hai.akadora() { "r" } else { "" }
+        ),
+    }
+}
+
+/// 副露を `actor` の席の mjai の副露イベント (`chi`, `pon`, `daiminkan`, `kakan`, `ankan`) に変換する
+pub fn furo_to_event(furo: &Furo, actor: u8) -> Value {
+    let target = |tacha| match tacha {
+        Tacha::Shimocha => (actor + 1) % 4,
+        Tacha::Toimen => (actor + 2) % 4,
+        Tacha::Kamicha => (actor + 3) % 4,
+    };
+    let pais = |hai: &[Hai]| hai.iter().map(|hai| to_pai(*hai)).collect::<Vec<_>>();
+    match *furo.kind() {
+        FuroKind::Chi {
+            from_tehai,
+            from_kamicha,
+        } => json!({
+            "type": "chi",
+            "actor": actor,
+            "target": target(Tacha::Kamicha),
+            "pai": to_pai(from_kamicha),
+            "consumed": pais(&from_tehai),
+        }),
+        FuroKind::Pon {
+            from_tehai,
+            from_tacha,
+            tacha,
+        } => json!({
+            "type": "pon",
+            "actor": actor,
+            "target": target(tacha),
+            "pai": to_pai(from_tacha),
+            "consumed": pais(&from_tehai),
+        }),
+        FuroKind::Kakan {
+            from_tehai,
+            from_tacha,
+            added,
+            ..
+        } => json!({
+            "type": "kakan",
+            "actor": actor,
+            "pai": to_pai(added),
+            "consumed": pais(&[from_tacha, from_tehai[0], from_tehai[1]]),
+        }),
+        FuroKind::Daiminkan {
+            from_tehai,
+            from_tacha,
+            tacha,
+        } => json!({
+            "type": "daiminkan",
+            "actor": actor,
+            "target": target(tacha),
+            "pai": to_pai(from_tacha),
+            "consumed": pais(&from_tehai),
+        }),
+        FuroKind::Ankan { from_tehai } => json!({
+            "type": "ankan",
+            "actor": actor,
+            "consumed": pais(&from_tehai),
+        }),
+    }
+}
+
+/// mjai の副露イベントを副露に変換する
+///
+/// `kakan` の場合は、`furo` (副露した席のそれまでの副露) から加槓するポンを探す。
+pub fn furo_from_event(event: &Value, furo: &[Furo]) -> Result<Furo, Error> {
+    let ev = Event::new(event);
+    let actor = ev.seat("actor")?;
+    let consumed = ev.pais("consumed")?;
+    let invalid = || Error::from(ErrorKind::InvalidFuro(event.to_string()));
+    let tacha = || -> Result<Tacha, Error> {
+        match (ev.seat("target")? + 4 - actor) % 4 {
+            1 => Ok(Tacha::Shimocha),
+            2 => Ok(Tacha::Toimen),
+            3 => Ok(Tacha::Kamicha),
+            _ => Err(invalid()),
+        }
+    };
+    let kind = match (ev.type_(), &consumed[..]) {
+        ("chi", &[t0, t1]) if tacha()? == Tacha::Kamicha => FuroKind::Chi {
+            from_tehai: [t0, t1],
+            from_kamicha: ev.pai("pai")?,
+        },
+        ("pon", &[t0, t1]) => FuroKind::Pon {
+            from_tehai: [t0, t1],
+            from_tacha: ev.pai("pai")?,
+            tacha: tacha()?,
+        },
+        ("daiminkan", &[t0, t1, t2]) => FuroKind::Daiminkan {
+            from_tehai: [t0, t1, t2],
+            from_tacha: ev.pai("pai")?,
+            tacha: tacha()?,
+        },
+        ("ankan", &[t0, t1, t2, t3]) => FuroKind::Ankan {
+            from_tehai: [t0, t1, t2, t3],
+        },
+        ("kakan", _) => {
+            let added = ev.pai("pai")?;
+            furo.iter()
+                .find_map(|furo| match *furo.kind() {
+                    FuroKind::Pon {
+                        from_tehai,
+                        from_tacha,
+                        tacha,
+                    } if from_tacha.is_same(&added) => Some(FuroKind::Kakan {
+                        from_tehai,
+                        from_tacha,
+                        tacha,
+                        added,
+                    }),
+                    _ => None,
+                })
+                .ok_or(ErrorKind::PonNotFound(added))?
+        }
+        _ => return Err(invalid()),
+    };
+    if !kind_is_valid(&kind) {
+        return Err(invalid());
+    }
+    Ok(kind.into())
+}
+
+/// 副露の牌が同じ種類の刻子・槓子、または順子になっているか
+fn kind_is_valid(kind: &FuroKind) -> bool {
+    match *kind {
+        FuroKind::Chi {
+            from_tehai: [t0, t1],
+            from_kamicha,
+        } => {
+            let mut hai = [from_kamicha, t0, t1];
+            hai.sort();
+            hai.iter()
+                .all(|h| h.category() == hai[0].category() && h.category() != HaiCategory::Jihai)
+                && hai[1].number() == hai[0].number() + 1
+                && hai[2].number() == hai[1].number() + 1
+        }
+        FuroKind::Pon {
+            from_tehai,
+            from_tacha,
+            ..
+        }
+        | FuroKind::Kakan {
+            from_tehai,
+            from_tacha,
+            ..
+        } => from_tehai.iter().all(|hai| hai.is_same(&from_tacha)),
+        FuroKind::Daiminkan {
+            from_tehai,
+            from_tacha,
+            ..
+        } => from_tehai.iter().all(|hai| hai.is_same(&from_tacha)),
+        FuroKind::Ankan { from_tehai } => from_tehai.iter().all(|hai| hai.is_same(&from_tehai[0])),
+    }
+}
+
+/// mjai の対局の状態
+///
+/// 局のイベントを `apply` で順に与え、`hora` イベントを `hora` に渡すと点数計算用の手牌と状況を返す。
+/// 局の進行は `Kyoku` で管理する。
+#[derive(Debug, Clone)]
+pub struct MjaiGame {
+    rules: Rules,
+    kyoku: Option<Kyoku>,
+}
+
+impl MjaiGame {
+    pub fn new(rules: Rules) -> Self {
+        Self { rules, kyoku: None }
+    }
+
+    /// `seat` の席の副露
+    pub fn furo(&self, seat: u8) -> &[Furo] {
+        self.kyoku.as_ref().map_or(&[], |kyoku| kyoku.furo(seat))
+    }
+
+    /// 進行中の局
+    pub fn kyoku(&self) -> Option<&Kyoku> {
+        self.kyoku.as_ref()
+    }
+
+    /// イベントを反映する
+    ///
+    /// 点数計算に関係しないイベントや、未知のイベントは無視する。
+    pub fn apply(&mut self, event: &Value) -> Result<(), Error> {
+        let ev = Event::new(event);
+        if ev.type_() == "start_kyoku" {
+            let mut kyoku = Kyoku::new(
+                self.rules.clone(),
+                ev.pai("bakaze")?,
+                ev.seat("oya")?,
+                ev.num("honba")?,
+                ev.num("kyotaku")?,
+                ev.pai("dora_marker")?,
+            );
+            let tehais = ev.array("tehais")?;
+            if tehais.len() != 4 {
+                return Err(ev.invalid("tehais"));
+            }
+            for (seat, tehai) in tehais.iter().enumerate() {
+                if let Some(haipai) = Event::pai_array(tehai) {
+                    kyoku
+                        .set_haipai(seat as u8, haipai)
+                        .map_err(ErrorKind::from)?;
+                }
+            }
+            self.kyoku = Some(kyoku);
+            return Ok(());
+        }
+
+        let kyoku_event = match ev.type_() {
+            "tsumo" => KyokuEvent::Tsumo {
+                seat: ev.seat("actor")?,
+                hai: ev.pai("pai").ok(),
+            },
+            "dahai" => KyokuEvent::Dahai {
+                seat: ev.seat("actor")?,
+                hai: ev.pai("pai")?,
+            },
+            "reach" => KyokuEvent::Richi {
+                seat: ev.seat("actor")?,
+            },
+            "dora" => KyokuEvent::Dora {
+                hai: ev.pai("dora_marker")?,
+            },
+            "chi" | "pon" | "daiminkan" | "kakan" | "ankan" => {
+                let seat = ev.seat("actor")?;
+                KyokuEvent::Furo {
+                    seat,
+                    furo: furo_from_event(event, self.furo(seat))?,
+                }
+            }
+            // 立直棒は `Kyoku` が宣言牌の次のイベントで数える
+            _ => return Ok(()),
+        };
+        let kyoku = self.kyoku.as_mut().ok_or(ErrorKind::NoKyoku)?;
+        kyoku.apply(kyoku_event).map_err(ErrorKind::from)?;
+        Ok(())
+    }
+
+    /// `hora` イベントから点数計算用の手牌と状況を求める
+    ///
+    /// 手牌は `hora_tehais` があればそれを、なければそれまでのイベントから復元した手牌を使う。
+    /// 裏ドラ表示牌は `uradora_markers` から求める。
+    pub fn hora(&self, event: &Value) -> Result<(Tehai, Env), Error> {
+        let ev = Event::new(event);
+        let kyoku = self.kyoku.as_ref().ok_or(ErrorKind::NoKyoku)?;
+        let actor = ev.seat("actor")?;
+        let pai = ev.pai("pai")?;
+
+        let mut hand = match event.get("hora_tehais") {
+            Some(value) => Event::pai_array(value).ok_or_else(|| ev.invalid("hora_tehais"))?,
+            None => kyoku
+                .jun_tehai(actor)
+                .ok_or(ErrorKind::UnknownTehai(actor))?
+                .as_slice()
+                .to_vec(),
+        };
+        // ツモあがりの場合などはあがり牌を含む
+        if hand.len() + kyoku.furo(actor).len() * 3 == 14 {
+            let idx = hand
+                .iter()
+                .position(|hai| *hai == pai)
+                .ok_or(ErrorKind::HaiNotFound(pai))?;
+            hand.remove(idx);
+        }
+        let (tehai, mut env) = kyoku
+            .agari_with(actor, hand, pai)
+            .map_err(ErrorKind::from)?;
+        if let Some(value) = event.get("uradora_markers") {
+            env.uradora = Event::pai_array(value).ok_or_else(|| ev.invalid("uradora_markers"))?;
+        }
+        Ok((tehai, env))
+    }
+}
+
+/// mjai のイベント (JSON オブジェクト) のフィールドの読み出し
+struct Event<'a>(&'a Value);
+
+impl<'a> Event<'a> {
+    fn new(value: &'a Value) -> Self {
+        Self(value)
+    }
+
+    fn type_(&self) -> &'a str {
+        self.0["type"].as_str().unwrap_or_default()
+    }
+
+    fn invalid(&self, name: &'static str) -> Error {
+        ErrorKind::InvalidField(self.type_().into(), name).into()
+    }
+
+    fn num(&self, name: &'static str) -> Result<u32, Error> {
+        self.0[name]
+            .as_u64()
+            .map(|n| n as u32)
+            .ok_or_else(|| self.invalid(name))
+    }
+
+    fn seat(&self, name: &'static str) -> Result<u8, Error> {
+        let seat = self.0[name].as_u64().ok_or_else(|| self.invalid(name))?;
+        if seat >= 4 {
+            return Err(ErrorKind::InvalidSeat(seat).into());
+        }
+        Ok(seat as u8)
+    }
+
+    fn pai(&self, name: &'static str) -> Result<Hai, Error> {
+        from_pai(self.0[name].as_str().ok_or_else(|| self.invalid(name))?)
+    }
+
+    fn array(&self, name: &'static str) -> Result<&'a [Value], Error> {
+        self.0[name]
+            .as_array()
+            .map(Vec::as_slice)
+            .ok_or_else(|| self.invalid(name))
+    }
+
+    fn pais(&self, name: &'static str) -> Result<Vec<Hai>, Error> {
+        self.array(name)?
+            .iter()
+            .map(|pai| from_pai(pai.as_str().unwrap_or_default()))
+            .collect()
+    }
+
+    /// 牌の名前の配列 (不明な牌 `?` を含む場合は `None`)
+    fn pai_array(value: &Value) -> Option<Vec<Hai>> {
+        value
+            .as_array()?
+            .iter()
+            .map(|pai| from_pai(pai.as_str()?).ok())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{env::RichiType, yaku::Yaku};
+    use num_bigint::BigUint;
+    use std::str::FromStr;
+
+    fn hai(s: &str) -> Hai {
+        Hai::from_str(s).unwrap()
+    }
+
+    #[test]
+    fn pai() {
+        assert_eq!(from_pai("5mr").unwrap(), hai("5$m"));
+        assert_eq!(from_pai("9s").unwrap(), hai("9s"));
+        assert_eq!(from_pai("E").unwrap(), hai("1j"));
+        assert_eq!(from_pai("C").unwrap(), hai("7j"));
+        for pai in &["?", "0m", "1mr", "5z", "5mm", "Er"] {
+            assert!(from_pai(pai).is_err(), "{}", pai);
+        }
+        for pai in &["1m", "5pr", "5s", "N", "P", "F"] {
+            assert_eq!(to_pai(from_pai(pai).unwrap()), *pai);
+        }
+    }
+
+    #[test]
+    fn furo() {
+        let pon = Furo::from_str("^5$55m").unwrap();
+        let pons = [pon, Furo::from_str("<111j").unwrap()];
+        for s in &["<213p", "^5$55m", "<111+1j", ">5$555m", "3333p"] {
+            let furo = Furo::from_str(s).unwrap();
+            let event = furo_to_event(&furo, 1);
+            assert_eq!(furo_from_event(&event, &pons).unwrap(), furo, "{}", s);
+        }
+        assert_eq!(
+            furo_to_event(&pon, 1),
+            json!({"type": "pon", "actor": 1, "target": 3, "pai": "5mr", "consumed": ["5m", "5m"]})
+        );
+
+        let invalid = |event: Value| furo_from_event(&event, &[]).is_err();
+        assert!(invalid(
+            json!({"type": "chi", "actor": 1, "target": 2, "pai": "1m", "consumed": ["2m", "3m"]})
+        ));
+        assert!(invalid(
+            json!({"type": "chi", "actor": 1, "target": 0, "pai": "1m", "consumed": ["2m", "4m"]})
+        ));
+        assert!(invalid(
+            json!({"type": "pon", "actor": 1, "target": 1, "pai": "1m", "consumed": ["1m", "1m"]})
+        ));
+        assert!(invalid(
+            json!({"type": "kakan", "actor": 1, "pai": "1m", "consumed": ["1m", "1m", "1m"]})
+        ));
+    }
+
+    fn play(events: &[Value]) -> (MjaiGame, Tehai, Env) {
+        let mut game = MjaiGame::new(Rules::default());
+        let (hora, events) = events.split_last().unwrap();
+        for event in events {
+            game.apply(event).unwrap();
+        }
+        let (tehai, env) = game.hora(hora).unwrap();
+        (game, tehai, env)
+    }
+
+    fn point(tehai: &Tehai, env: &Env) -> BigUint {
+        let yaku = tehai
+            .to_agari_combinations()
+            .iter()
+            .map(|agari| Yaku::new(agari, env))
+            .max()
+            .unwrap();
+        yaku.point().clone()
+    }
+
+    #[test]
+    fn hora_ron() {
+        let unknown = vec!["?"; 13];
+        let (_, tehai, env) = play(&[
+            json!({"type": "start_kyoku", "bakaze": "E", "kyoku": 1, "honba": 0, "kyotaku": 0, "oya": 0,
+                "dora_marker": "1s", "tehais": [
+                    ["1m", "2m", "3m", "4p", "5pr", "6p", "7s", "8s", "E", "E", "E", "C", "C"],
+                    unknown, unknown, unknown]}),
+            json!({"type": "tsumo", "actor": 0, "pai": "5s"}),
+            json!({"type": "reach", "actor": 0}),
+            json!({"type": "dahai", "actor": 0, "pai": "5s", "tsumogiri": true}),
+            json!({"type": "reach_accepted", "actor": 0}),
+            json!({"type": "tsumo", "actor": 1, "pai": "?"}),
+            json!({"type": "dahai", "actor": 1, "pai": "9m", "tsumogiri": true}),
+            json!({"type": "tsumo", "actor": 2, "pai": "?"}),
+            json!({"type": "dahai", "actor": 2, "pai": "1p", "tsumogiri": true}),
+            json!({"type": "tsumo", "actor": 3, "pai": "?"}),
+            json!({"type": "dahai", "actor": 3, "pai": "9s", "tsumogiri": true}),
+            json!({"type": "hora", "actor": 0, "target": 3, "pai": "9s", "uradora_markers": ["4m"]}),
+        ]);
+        assert_eq!(tehai, Tehai::from_str("123m45$6p78s111j77j ?9s").unwrap());
+        assert_eq!(env.jikaze, hai("1j"));
+        assert_eq!(env.richi, Some(RichiType::Daburi));
+        assert!(env.ippatsu);
+        assert_eq!(env.hoju, Some(Tacha::Kamicha));
+        assert_eq!(env.kyotaku, 1);
+        assert_eq!(env.uradora, [hai("4m")]);
+        assert_eq!(point(&tehai, &env), BigUint::from(18000u32));
+    }
+
+    #[test]
+    fn hora_rinshan() {
+        let unknown = vec!["?"; 13];
+        let (game, tehai, env) = play(&[
+            json!({"type": "start_kyoku", "bakaze": "S", "kyoku": 1, "honba": 1, "kyotaku": 0, "oya": 0,
+                "dora_marker": "1s", "tehais": [
+                    unknown,
+                    ["2m", "3m", "4m", "2p", "3p", "4p", "1s", "1s", "7s", "8s", "F", "F", "9m"],
+                    unknown, unknown]}),
+            json!({"type": "tsumo", "actor": 0, "pai": "?"}),
+            json!({"type": "dahai", "actor": 0, "pai": "F", "tsumogiri": false}),
+            json!({"type": "pon", "actor": 1, "target": 0, "pai": "F", "consumed": ["F", "F"]}),
+            json!({"type": "dahai", "actor": 1, "pai": "9m", "tsumogiri": false}),
+            json!({"type": "tsumo", "actor": 2, "pai": "?"}),
+            json!({"type": "dahai", "actor": 2, "pai": "1p", "tsumogiri": true}),
+            json!({"type": "tsumo", "actor": 3, "pai": "?"}),
+            json!({"type": "dahai", "actor": 3, "pai": "1p", "tsumogiri": true}),
+            json!({"type": "tsumo", "actor": 0, "pai": "?"}),
+            json!({"type": "dahai", "actor": 0, "pai": "2s", "tsumogiri": true}),
+            json!({"type": "tsumo", "actor": 1, "pai": "F"}),
+            json!({"type": "kakan", "actor": 1, "pai": "F", "consumed": ["F", "F", "F"]}),
+            json!({"type": "dora", "dora_marker": "3m"}),
+            json!({"type": "tsumo", "actor": 1, "pai": "6s"}),
+            json!({"type": "hora", "actor": 1, "target": 1, "pai": "6s"}),
+        ]);
+        assert_eq!(game.furo(1), [Furo::from_str("<666+6j").unwrap()]);
+        assert_eq!(tehai, Tehai::from_str("234m234p1178s <666+6j !6s").unwrap());
+        assert_eq!(env.bakaze, hai("2j"));
+        assert_eq!(env.jikaze, hai("2j"));
+        assert!(env.rinshan);
+        assert!(!env.tenho);
+        assert_eq!(env.dora, [hai("1s"), hai("3m")]);
+        assert_eq!(env.honba, 1);
+        assert_eq!(point(&tehai, &env), BigUint::from(5200u32));
+    }
+}
@@ -0,0 +1,226 @@
+use crate::{
+    agari_type::AgariType, env::Env, furo::Furo, jun_tehai::JunTehai, kitaichi, kitaichi::Kitaichi,
+    rules::Rules, shanten::Shanten, tenpai, tenpai::MachiHai, ukeire, ukeire::Ukeire,
+};
+use std::{fmt, str::FromStr};
+use thiserror::Error;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+/// 持ち牌 (あがり牌を含まない手牌。純手牌と副露からなる)
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Mochihai {
+    jun_tehai: JunTehai,
+    furo: Vec<Furo>,
+}
+
+impl fmt::Display for Mochihai {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.jun_tehai)?;
+        for furo in &self.furo {
+            write!(f, " {}", furo)?;
+        }
+        Ok(())
+    }
+}
+
+impl Mochihai {
+    /// 牌の数 (槓子は3枚として数える)
+    pub fn hai_count(&self) -> usize {
+        self.jun_tehai.as_slice().len() + self.furo.len() * 3
+    }
+
+    /// 待ち牌 (13枚の手牌のみ)
+    pub fn machi_hai(&self, rules: &Rules) -> Result<Vec<MachiHai>, Error> {
+        self.check_hai_count("待ち牌の判定", 13)?;
+        Ok(tenpai::machi_hai(&self.jun_tehai, &self.furo, rules))
+    }
+
+    pub fn shanten(&self) -> Shanten {
+        Shanten::new(&self.jun_tehai, &self.furo)
+    }
+
+    /// 打牌候補ごとの受け入れ (14枚の手牌のみ)
+    pub fn ukeire(&self, env: &Env) -> Result<Vec<Ukeire>, Error> {
+        self.check_hai_count("受け入れの判定", 14)?;
+        Ok(ukeire::ukeire(&self.jun_tehai, &self.furo, env))
+    }
+
+    /// 聴牌となる打牌候補の打点の期待値 (14枚の手牌のみ)
+    pub fn kitaichi(&self, env: &Env) -> Result<Vec<Kitaichi>, Error> {
+        self.check_hai_count("期待値の判定", 14)?;
+        Ok(kitaichi::kitaichi(&self.jun_tehai, &self.furo, env))
+    }
+
+    fn check_hai_count(&self, name: &'static str, expected: usize) -> Result<(), Error> {
+        let hai_count = self.hai_count();
+        if hai_count != expected {
+            return Err(ErrorKind::InvalidHaiCount(name, expected, hai_count).into());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+impl Mochihai {
+    #[wasm_bindgen(js_name = "toString")]
+    pub fn to_string_js(&self) -> String {
+        self.to_string()
+    }
+
+    #[wasm_bindgen(getter = junTehai)]
+    pub fn jun_tehai_js(&self) -> JunTehai {
+        self.jun_tehai.clone()
+    }
+
+    #[wasm_bindgen(getter = furo)]
+    pub fn furo_js(&self) -> Box<[JsValue]> {
+        self.furo.iter().copied().map(JsValue::from).collect()
+    }
+
+    #[wasm_bindgen(js_name = "machiHai")]
+    pub fn machi_hai_js(&self, rules: &Rules) -> Result<Box<[JsValue]>, JsValue> {
+        let res = self.machi_hai(rules).map_err(|e| e.to_string())?;
+        Ok(res.into_iter().map(JsValue::from).collect())
+    }
+
+    #[wasm_bindgen(js_name = "shanten")]
+    pub fn shanten_js(&self) -> Shanten {
+        self.shanten()
+    }
+
+    #[wasm_bindgen(js_name = "ukeire")]
+    pub fn ukeire_js(&self, env: &Env) -> Result<Box<[JsValue]>, JsValue> {
+        let res = self.ukeire(env).map_err(|e| e.to_string())?;
+        Ok(res.into_iter().map(JsValue::from).collect())
+    }
+
+    #[wasm_bindgen(js_name = "kitaichi")]
+    pub fn kitaichi_js(&self, env: &Env) -> Result<Box<[JsValue]>, JsValue> {
+        let res = self.kitaichi(env).map_err(|e| e.to_string())?;
+        Ok(res.into_iter().map(JsValue::from).collect())
+    }
+
+    #[wasm_bindgen(js_name = "fromStr")]
+    pub fn from_str_js(s: &str) -> Result<Mochihai, JsValue> {
+        let res = Self::from_str(s).map_err(|e| e.to_string())?;
+        Ok(res)
+    }
+}
+
+#[derive(Debug, Error)]
+#[error(transparent)]
+pub struct Error(#[from] ErrorKind);
+
+#[derive(Debug, Error)]
+enum ErrorKind {
+    #[error("{0}には{1}枚の手牌が必要です (牌の数: `{2}`)")]
+    InvalidHaiCount(&'static str, usize, usize),
+}
+
+#[derive(Debug, Error)]
+#[error(transparent)]
+pub struct ParseError(#[from] ParseErrorKind);
+
+#[derive(Debug, Error)]
+enum ParseErrorKind {
+    #[error("純手牌がありません")]
+    NoJunTehai,
+    #[error("持ち牌にあがり牌は含められません: `{0}`")]
+    AgariHai(String),
+    #[error("多牌です (牌の数: `{0}`)")]
+    Tahai(usize),
+    #[error("少牌です (牌の数: `{0}`)")]
+    Shohai(usize),
+    #[error("純手牌のパースエラー: {0}")]
+    JunTehai(#[from] <JunTehai as FromStr>::Err),
+    #[error("副露のパースエラー: {0}")]
+    Furo(#[from] <Furo as FromStr>::Err),
+}
+
+impl FromStr for Mochihai {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use ParseErrorKind as E;
+        let mut chunks = s.split_whitespace();
+
+        let tehai_chunk = chunks.next().ok_or(E::NoJunTehai)?;
+        let jun_tehai = JunTehai::from_str(tehai_chunk).map_err(E::from)?;
+
+        let mut furo = vec![];
+        for chunk in chunks {
+            if AgariType::is_agari_str(chunk) {
+                return Err(E::AgariHai(chunk.into()).into());
+            }
+            furo.push(Furo::from_str(chunk).map_err(E::from)?);
+        }
+
+        let res = Mochihai { jun_tehai, furo };
+
+        // 打牌前 (14枚) と打牌後 (13枚) のどちらも許容する
+        let hai_count = res.hai_count();
+        if hai_count < 13 {
+            return Err(E::Shohai(hai_count).into());
+        }
+        if hai_count > 14 {
+            return Err(E::Tahai(hai_count).into());
+        }
+
+        Ok(res)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use assert_matches::assert_matches;
+
+    #[test]
+    fn parse() {
+        use ParseErrorKind::*;
+        fn ok(s: &str) -> String {
+            Mochihai::from_str(s).unwrap().to_string()
+        }
+        fn err(s: &str) -> ParseErrorKind {
+            Mochihai::from_str(s).unwrap_err().0
+        }
+
+        assert_eq!(ok("123m4p <555m <666m <777m"), "123m4p <555m <666m <777m");
+        assert_eq!(ok("123m45p <555m <666m <777m"), "123m45p <555m <666m <777m");
+        assert_eq!(ok("1112345678999m"), "1112345678999m");
+
+        assert_matches!(err(""), NoJunTehai);
+        assert_matches!(err("123m4p <555m <666m <777m ?4p"), AgariHai(s) if s == "?4p");
+        assert_matches!(err("111222333444555m"), Tahai(15));
+        assert_matches!(err("11122233344m"), Shohai(11));
+        assert_matches!(err("x"), JunTehai(..));
+        assert_matches!(err("123m <x"), Furo(..));
+    }
+
+    #[test]
+    fn hai_count() {
+        use ErrorKind::*;
+        let env = Env::new("1j".parse().unwrap(), "2j".parse().unwrap());
+        let mochihai13 = Mochihai::from_str("23m456p567s789s11j").unwrap();
+        let mochihai14 = Mochihai::from_str("23m456p567s789s11j9m").unwrap();
+
+        assert!(mochihai13.machi_hai(&env.rules).is_ok());
+        assert_matches!(
+            mochihai14.machi_hai(&env.rules).unwrap_err().0,
+            InvalidHaiCount(_, 13, 14)
+        );
+        assert!(mochihai14.ukeire(&env).is_ok());
+        assert_matches!(
+            mochihai13.ukeire(&env).unwrap_err().0,
+            InvalidHaiCount(_, 14, 13)
+        );
+        assert!(mochihai14.kitaichi(&env).is_ok());
+        assert_matches!(
+            mochihai13.kitaichi(&env).unwrap_err().0,
+            InvalidHaiCount(_, 14, 13)
+        );
+    }
+}
@@ -0,0 +1,338 @@
+use crate::{
+    agari_type::AgariType,
+    env::{self, Env},
+    hai::Hai,
+    rules::MultiRonAllocation,
+    tehai::Tehai,
+    yaku::Yaku,
+};
+use num_bigint::BigUint;
+use std::fmt;
+use thiserror::Error;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+/// 複数人のロンあがり (ダブロン/トリロン)
+///
+/// 放銃者の自風と、あがり者ごとの手牌と状況を指定して精算する。
+/// あがり者の状況の本場・供託・場風・ルールはすべて同じである必要がある。
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone)]
+pub struct MultiRon {
+    hoju: Hai,
+    winners: Vec<(Tehai, Env)>,
+}
+
+/// 複数人のロンあがりのうち、1人のあがり者の精算結果
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone)]
+pub struct RonResult {
+    jikaze: Hai,
+    yaku: Yaku,
+    /// 受け取る本場の点数
+    honba: u32,
+    /// 受け取る供託の点数
+    kyotaku: u32,
+}
+
+impl fmt::Display for RonResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {}{}点 (本場: {}点, 供託: {}点)",
+            env::kaze_name(self.jikaze.number()),
+            self.yaku.name(),
+            self.yaku.point(),
+            self.honba,
+            self.kyotaku
+        )
+    }
+}
+
+#[derive(Debug, Error)]
+#[error(transparent)]
+pub struct Error(#[from] ErrorKind);
+
+#[derive(Debug, Error)]
+enum ErrorKind {
+    #[error("あがり者がいません")]
+    NoWinner,
+    #[error("卓にいない席が指定されています: `{0}`")]
+    InvalidSeat(Hai),
+    #[error("同じ席が複数回指定されています: `{0}`")]
+    DuplicatedSeat(Hai),
+    #[error("放銃者はあがれません: `{0}`")]
+    HojuIsWinner(Hai),
+    #[error("ロンあがりではありません: `{0}`")]
+    NotRon(Hai),
+    #[error("あがり者の本場・供託・場風・ルールが一致しません: `{0}`")]
+    MismatchedEnv(Hai),
+    #[error("役がありません: `{0}`")]
+    NoYaku(Hai),
+}
+
+impl MultiRon {
+    pub fn new(hoju: Hai) -> Self {
+        Self {
+            hoju,
+            winners: vec![],
+        }
+    }
+
+    pub fn add_winner(&mut self, tehai: Tehai, env: Env) {
+        self.winners.push((tehai, env));
+    }
+
+    /// 各あがり者の精算結果を放銃者の下家側から順に返す
+    ///
+    /// あがり者ごとの点数は放銃者が支払う。本場・供託は `Rules::multi_ron_allocation` に従って配分する。
+    pub fn settle(&self) -> Result<Vec<RonResult>, Error> {
+        use ErrorKind as E;
+        let (_, first) = self.winners.first().ok_or(E::NoWinner)?;
+        let n = first.num_players();
+        if !self.hoju.is_kazehai() || self.hoju.number() > n {
+            return Err(E::InvalidSeat(self.hoju).into());
+        }
+
+        let mut seats = vec![];
+        let mut res = vec![];
+        for (tehai, env) in &self.winners {
+            let jikaze = env.jikaze;
+            if !jikaze.is_kazehai() || jikaze.number() > n {
+                return Err(E::InvalidSeat(jikaze).into());
+            }
+            if jikaze.is_same(&self.hoju) {
+                return Err(E::HojuIsWinner(jikaze).into());
+            }
+            if seats.contains(&jikaze.number()) {
+                return Err(E::DuplicatedSeat(jikaze).into());
+            }
+            seats.push(jikaze.number());
+            if tehai.agari_hai().type_() != AgariType::Ron {
+                return Err(E::NotRon(jikaze).into());
+            }
+            if env.honba != first.honba
+                || env.kyotaku != first.kyotaku
+                || env.bakaze != first.bakaze
+                || env.rules != first.rules
+            {
+                return Err(E::MismatchedEnv(jikaze).into());
+            }
+
+            // 本場・供託は別途配分する
+            let mut env = env.clone();
+            env.honba = 0;
+            env.kyotaku = 0;
+            env.hoju = env
+                .tacha()
+                .find(|tacha| env.tacha_kaze(*tacha) == self.hoju.number());
+            let yaku = tehai
+                .to_agari_combinations_with_env(&env)
+                .iter()
+                .map(|agari| Yaku::new(agari, &env))
+                .max()
+                .filter(|yaku| !yaku.payments().is_empty())
+                .ok_or(E::NoYaku(jikaze))?;
+            res.push(RonResult {
+                jikaze,
+                yaku,
+                honba: 0,
+                kyotaku: 0,
+            });
+        }
+
+        // 放銃者から見て順番が近い順
+        let distance = |jikaze: &Hai| (jikaze.number() + n - self.hoju.number()) % n;
+        res.sort_by_key(|result| distance(&result.jikaze));
+
+        let honba = first.honba * 300;
+        let kyotaku = first.kyotaku * 1000;
+        match first.rules.multi_ron_allocation {
+            MultiRonAllocation::Atamahane => {
+                res[0].honba = honba;
+                res[0].kyotaku = kyotaku;
+            }
+            MultiRonAllocation::Split => {
+                let count = res.len() as u32;
+                let split = |total: u32| total / count / 100 * 100;
+                for result in &mut res {
+                    result.honba = split(honba);
+                    result.kyotaku = split(kyotaku);
+                }
+                // 端数は放銃者に最も近いあがり者が受け取る
+                res[0].honba += honba - split(honba) * count;
+                res[0].kyotaku += kyotaku - split(kyotaku) * count;
+            }
+        }
+
+        Ok(res)
+    }
+}
+
+impl RonResult {
+    /// 本場・供託を含めた収入
+    pub fn total(&self) -> BigUint {
+        self.yaku.point() + self.honba + self.kyotaku
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+impl MultiRon {
+    /// `hoju` は放銃者の自風の牌
+    #[wasm_bindgen(constructor)]
+    pub fn new_js(hoju: Hai) -> MultiRon {
+        Self::new(hoju)
+    }
+
+    #[wasm_bindgen(js_name = "addWinner")]
+    pub fn add_winner_js(&mut self, tehai: &Tehai, env: &Env) {
+        self.add_winner(tehai.clone(), env.clone());
+    }
+
+    #[wasm_bindgen(js_name = "settle")]
+    pub fn settle_js(&self) -> Result<Box<[JsValue]>, JsValue> {
+        let res = self.settle().map_err(|e| e.to_string())?;
+        Ok(res.into_iter().map(JsValue::from).collect())
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+impl RonResult {
+    #[wasm_bindgen(getter = jikaze)]
+    pub fn jikaze_js(&self) -> Hai {
+        self.jikaze
+    }
+
+    #[wasm_bindgen(getter = yaku)]
+    pub fn yaku_js(&self) -> Yaku {
+        self.yaku.clone()
+    }
+
+    #[wasm_bindgen(getter = honba)]
+    pub fn honba_js(&self) -> u32 {
+        self.honba
+    }
+
+    #[wasm_bindgen(getter = kyotaku)]
+    pub fn kyotaku_js(&self) -> u32 {
+        self.kyotaku
+    }
+
+    #[wasm_bindgen(getter = total)]
+    pub fn total_js(&self) -> String {
+        self.total().to_string()
+    }
+
+    #[wasm_bindgen(js_name = "toString")]
+    pub fn to_string_js(&self) -> String {
+        self.to_string()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rules::SanmaTsumo;
+    use assert_matches::assert_matches;
+    use std::str::FromStr;
+
+    fn hai(s: &str) -> Hai {
+        Hai::from_str(s).unwrap()
+    }
+
+    fn settle(multi_ron: &MultiRon) -> Vec<String> {
+        multi_ron
+            .settle()
+            .unwrap()
+            .iter()
+            .map(|result| result.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn atamahane() {
+        let mut multi_ron = MultiRon::new(hai("1j"));
+        let mut env = Env::new(hai("1j"), hai("4j"));
+        env.honba = 2;
+        env.kyotaku = 1;
+        multi_ron.add_winner(
+            Tehai::from_str("234567m234p5s666j ?5s").unwrap(),
+            env.clone(),
+        );
+        env.jikaze = hai("2j");
+        multi_ron.add_winner(Tehai::from_str("123456789m1234p ?1p").unwrap(), env);
+        assert_eq!(
+            settle(&multi_ron),
+            [
+                "南家: 2600点 (本場: 600点, 供託: 1000点)",
+                "北家: 1300点 (本場: 0点, 供託: 0点)",
+            ]
+        );
+    }
+
+    #[test]
+    fn split() {
+        let mut multi_ron = MultiRon::new(hai("2j"));
+        let mut env = Env::new(hai("1j"), hai("1j"));
+        env.honba = 1;
+        env.kyotaku = 1;
+        env.rules.multi_ron_allocation = MultiRonAllocation::Split;
+        for jikaze in &["1j", "3j", "4j"] {
+            env.jikaze = hai(jikaze);
+            multi_ron.add_winner(Tehai::from_str("123456789m1234p ?1p").unwrap(), env.clone());
+        }
+        assert_eq!(
+            settle(&multi_ron),
+            [
+                "西家: 2600点 (本場: 100点, 供託: 400点)",
+                "北家: 2600点 (本場: 100点, 供託: 300点)",
+                "東家: 3900点 (本場: 100点, 供託: 300点)",
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        use ErrorKind::*;
+        let err = |multi_ron: &MultiRon| multi_ron.settle().unwrap_err().0;
+        let tehai = Tehai::from_str("123456789m1234p ?1p").unwrap();
+        let env = Env::new(hai("1j"), hai("2j"));
+
+        let mut multi_ron = MultiRon::new(hai("1j"));
+        assert_matches!(err(&multi_ron), NoWinner);
+        multi_ron.add_winner(tehai.clone(), env.clone());
+        multi_ron.add_winner(tehai.clone(), env.clone());
+        assert_matches!(err(&multi_ron), DuplicatedSeat(..));
+
+        let mut multi_ron = MultiRon::new(hai("2j"));
+        multi_ron.add_winner(tehai.clone(), env.clone());
+        assert_matches!(err(&multi_ron), HojuIsWinner(..));
+
+        let mut multi_ron = MultiRon::new(hai("1j"));
+        multi_ron.add_winner(Tehai::from_str("123456789m1234p !1p").unwrap(), env.clone());
+        assert_matches!(err(&multi_ron), NotRon(..));
+
+        let mut multi_ron = MultiRon::new(hai("1j"));
+        multi_ron.add_winner(
+            Tehai::from_str("234567m234p5s <789s ?5s").unwrap(),
+            env.clone(),
+        );
+        assert_matches!(err(&multi_ron), NoYaku(..));
+
+        let mut multi_ron = MultiRon::new(hai("1j"));
+        multi_ron.add_winner(tehai.clone(), env.clone());
+        let mut other = env.clone();
+        other.jikaze = hai("3j");
+        other.honba = 1;
+        multi_ron.add_winner(tehai.clone(), other);
+        assert_matches!(err(&multi_ron), MismatchedEnv(..));
+
+        let mut multi_ron = MultiRon::new(hai("4j"));
+        let mut env = env;
+        env.rules.sanma = Some(SanmaTsumo::Tsumozon);
+        multi_ron.add_winner(tehai, env);
+        assert_matches!(err(&multi_ron), InvalidSeat(..));
+    }
+}
@@ -0,0 +1,127 @@
+#[cfg(feature = "wasm")]
+use crate::hai_image::HaiImage;
+use crate::{hai::Hai, hai_category::HaiCategory, hai_vec::HaiVec, hai_with_attr::HaiWithAttr};
+use std::{convert::TryFrom, fmt, str::FromStr};
+use thiserror::Error;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+/// 抜きドラ (三人麻雀の北抜き)
+///
+/// `*4j` のように表記する。抜いた北は手牌の枚数には含めない。
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Nukidora(Hai);
+
+impl fmt::Display for Nukidora {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", Self::PREFIX, self.0)
+    }
+}
+
+impl Nukidora {
+    pub const PREFIX: &'static str = "*";
+
+    pub fn is_nukidora_str(s: &str) -> bool {
+        s.starts_with(Self::PREFIX)
+    }
+
+    pub fn hai(&self) -> Hai {
+        self.0
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+impl Nukidora {
+    #[wasm_bindgen(getter = hai)]
+    pub fn hai_js(self) -> Hai {
+        self.hai()
+    }
+
+    #[wasm_bindgen(js_name = "toString")]
+    pub fn to_string_js(self) -> String {
+        self.to_string()
+    }
+
+    #[wasm_bindgen(js_name = "toImage")]
+    pub fn to_image_js(self) -> HaiImage {
+        HaiImage::normal(self.0)
+    }
+}
+
+#[derive(Debug, Error)]
+#[error(transparent)]
+pub struct ParseError(#[from] ParseErrorKind);
+
+#[derive(Debug, Error)]
+enum ParseErrorKind {
+    #[error("抜きドラは `{}` で始まる必要があります: `{0}`", Nukidora::PREFIX)]
+    NoPrefix(String),
+    #[error("抜きドラは1枚ずつ指定してください: `{0}`")]
+    InvalidNumberOfHai(HaiVec),
+    #[error("不正な牌 `{0}` があります")]
+    InvalidHai(HaiWithAttr),
+    #[error("北以外の牌は抜きドラになりません: `{0}`")]
+    NotPei(Hai),
+    #[error(transparent)]
+    HaiVec(#[from] <HaiVec as FromStr>::Err),
+}
+
+impl FromStr for Nukidora {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use ParseErrorKind as E;
+        let rest = s
+            .strip_prefix(Self::PREFIX)
+            .ok_or_else(|| E::NoPrefix(s.into()))?;
+        let hai_vec = HaiVec::from_str(rest).map_err(E::from)?;
+        if hai_vec.0.len() != 1 {
+            return Err(E::InvalidNumberOfHai(hai_vec).into());
+        }
+        let hai = match hai_vec.0[0] {
+            HaiWithAttr::FromTehai(hai) => hai,
+            attr => return Err(E::InvalidHai(attr).into()),
+        };
+        Self::try_from(hai)
+    }
+}
+
+impl TryFrom<Hai> for Nukidora {
+    type Error = ParseError;
+
+    fn try_from(hai: Hai) -> Result<Self, Self::Error> {
+        if hai.category() != HaiCategory::Jihai || hai.number() != 4 {
+            return Err(ParseErrorKind::NotPei(hai).into());
+        }
+        Ok(Self(hai))
+    }
+}
+
+#[cfg(feature = "serde")]
+crate::serde_str::impl_serde_str!(Nukidora);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use assert_matches::assert_matches;
+
+    #[test]
+    fn parse() {
+        use ParseErrorKind::*;
+        fn ok(s: &str) -> String {
+            Nukidora::from_str(s).unwrap().to_string()
+        }
+        fn err(s: &str) -> ParseErrorKind {
+            Nukidora::from_str(s).unwrap_err().0
+        }
+
+        assert_eq!(ok("*4j"), "*4j");
+        assert_matches!(err("4j"), NoPrefix(..));
+        assert_matches!(err("*44j"), InvalidNumberOfHai(..));
+        assert_matches!(err("*<4j"), InvalidHai(..));
+        assert_matches!(err("*1j"), NotPei(..));
+        assert_matches!(err("*x"), HaiVec(..));
+    }
+}
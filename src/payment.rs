@@ -0,0 +1,187 @@
+use crate::{env::Env, rules::SanmaTsumo, tacha::Tacha};
+use num_bigint::BigUint;
+#[cfg(feature = "serde")]
+use serde::Serialize;
+use std::fmt;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+/// あがりに対する支払い
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Payment {
+    /// 支払う他家 (ロンあがりで放銃者が不明な場合は `None`)
+    payer: Option<Tacha>,
+    /// 支払う他家が親か
+    oya: bool,
+    /// 包 (責任払い) による支払いを含むか
+    pao: bool,
+    /// 支払う点数 (本場を含む)
+    #[cfg_attr(
+        feature = "serde",
+        serde(serialize_with = "crate::serde_str::serialize")
+    )]
+    amount: BigUint,
+}
+
+impl fmt::Display for Payment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.payer_name())?;
+        if self.oya {
+            write!(f, "(親)")?;
+        }
+        if self.pao {
+            write!(f, "(包)")?;
+        }
+        write!(f, ": {}点", self.amount)
+    }
+}
+
+impl Payment {
+    pub fn new(payer: Option<Tacha>, oya: bool, amount: BigUint) -> Self {
+        Self {
+            payer,
+            oya,
+            pao: false,
+            amount,
+        }
+    }
+
+    /// ロンあがりの支払い (本場を含まない)
+    pub fn ron(env: &Env, base_point: &BigUint) -> Vec<Self> {
+        let amount = match env.jikaze.number() == 1 {
+            true => round(base_point * 6u32),
+            false => round(base_point * 4u32),
+        };
+        let oya = env.hoju.is_some() && env.hoju == env.oya_tacha();
+        vec![Self::new(env.hoju, oya, amount)]
+    }
+
+    /// 包 (責任払い) を含む支払い (本場を含まない)
+    ///
+    /// 役満のうち `pao_count` 倍分を包の対象とし、ツモあがりの場合は包の他家が全額を、
+    /// 包の他家以外からのロンあがりの場合は放銃者と包の他家が半額ずつ支払う。
+    pub fn with_pao(
+        env: &Env,
+        is_ron: bool,
+        yakuman_count: u32,
+        pao_tacha: Tacha,
+        pao_count: u32,
+    ) -> Vec<Self> {
+        let base_point = |count: u32| BigUint::from(count * 8000);
+        let pao_point = if is_ron {
+            Self::ron(env, &base_point(pao_count))
+        } else {
+            Self::tsumo(env, &base_point(pao_count))
+        }
+        .iter()
+        .map(Self::amount)
+        .sum::<BigUint>();
+        let mut payments = match (yakuman_count > pao_count, is_ron) {
+            (true, true) => Self::ron(env, &base_point(yakuman_count - pao_count)),
+            (true, false) => Self::tsumo(env, &base_point(yakuman_count - pao_count)),
+            (false, true) => Self::ron(env, &BigUint::from(0u32)),
+            (false, false) => vec![],
+        };
+        let pao_point = if is_ron && env.hoju != Some(pao_tacha) {
+            // 放銃者と包の他家で折半する
+            let half = pao_point / 2u32;
+            payments[0].amount += &half;
+            half
+        } else {
+            pao_point
+        };
+        match payments
+            .iter_mut()
+            .find(|payment| payment.payer == Some(pao_tacha))
+        {
+            Some(payment) => {
+                payment.pao = true;
+                payment.amount += pao_point;
+            }
+            None => payments.push(Self {
+                payer: Some(pao_tacha),
+                oya: env.oya_tacha() == Some(pao_tacha),
+                pao: true,
+                amount: pao_point,
+            }),
+        }
+        payments
+    }
+
+    /// ツモあがりの支払い (本場を含まない)
+    ///
+    /// 三人麻雀の北家折半の場合、北家の支払い分の半分をそれぞれ加える。
+    pub fn tsumo(env: &Env, base_point: &BigUint) -> Vec<Self> {
+        let is_oya = env.jikaze.number() == 1;
+        let setsuhan = env.rules.sanma == Some(SanmaTsumo::Setsuhan);
+        let oya_tacha = env.oya_tacha();
+        env.tacha()
+            .map(|tacha| {
+                let payer_is_oya = oya_tacha == Some(tacha);
+                // 基本点の半分単位で計算する
+                let mut half = match is_oya || payer_is_oya {
+                    true => 4u32,
+                    false => 2u32,
+                };
+                if setsuhan {
+                    // 北家 (子) の支払い分の半分を加える
+                    half += if is_oya { 2 } else { 1 };
+                }
+                let amount = round(base_point * half / 2u32);
+                Self::new(Some(tacha), payer_is_oya, amount)
+            })
+            .collect()
+    }
+
+    pub fn payer(&self) -> Option<Tacha> {
+        self.payer
+    }
+
+    pub fn amount(&self) -> &BigUint {
+        &self.amount
+    }
+
+    pub fn add_amount(&mut self, amount: u32) {
+        self.amount += amount;
+    }
+
+    fn payer_name(&self) -> &'static str {
+        self.payer.map(Tacha::to_name).unwrap_or("放銃者")
+    }
+}
+
+/// 100点単位に切り上げる
+fn round(point: BigUint) -> BigUint {
+    (point + 99u32) / 100u32 * 100u32
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+impl Payment {
+    #[wasm_bindgen(getter = payer)]
+    pub fn payer_js(&self) -> String {
+        self.payer_name().into()
+    }
+
+    #[wasm_bindgen(getter = oya)]
+    pub fn oya_js(&self) -> bool {
+        self.oya
+    }
+
+    #[wasm_bindgen(getter = pao)]
+    pub fn pao_js(&self) -> bool {
+        self.pao
+    }
+
+    #[wasm_bindgen(getter = amount)]
+    pub fn amount_js(&self) -> String {
+        self.amount.to_string()
+    }
+
+    #[wasm_bindgen(js_name = "toString")]
+    pub fn to_string_js(&self) -> String {
+        self.to_string()
+    }
+}
@@ -1,13 +1,22 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 use std::fmt;
 
+#[cfg(feature = "wasm")]
 use wasm_bindgen::prelude::*;
 
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Rank(RankKind);
 
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "lowercase")
+)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
-pub(crate) enum RankKind {
+pub enum RankKind {
     Fan(u32),
     Yakuman(u32),
 }
@@ -23,19 +32,20 @@ impl fmt::Display for Rank {
 }
 
 impl Rank {
-    pub(crate) fn new_fan(fan: u32) -> Self {
+    pub fn new_fan(fan: u32) -> Self {
         Self(RankKind::Fan(fan))
     }
 
-    pub(crate) fn new_yakuman(count: u32) -> Self {
+    pub fn new_yakuman(count: u32) -> Self {
         Self(RankKind::Yakuman(count))
     }
 
-    pub(crate) fn kind(&self) -> &RankKind {
+    pub fn kind(&self) -> &RankKind {
         &self.0
     }
 }
 
+#[cfg(feature = "wasm")]
 #[wasm_bindgen]
 impl Rank {
     #[wasm_bindgen(getter = fan)]
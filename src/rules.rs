@@ -0,0 +1,574 @@
+use crate::hai_category::HaiCategory;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+/// 四暗刻単騎の扱い
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "kebab-case")
+)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SuankoTanki {
+    /// 役満 (四暗刻として扱う)
+    Single,
+    /// ツモあがりの場合のみダブル役満
+    TsumoDouble,
+    /// ダブル役満
+    Double,
+}
+
+impl SuankoTanki {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SuankoTanki::Single => "single",
+            SuankoTanki::TsumoDouble => "tsumo-double",
+            SuankoTanki::Double => "double",
+        }
+    }
+}
+
+/// 人和の扱い
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "kebab-case")
+)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Renho {
+    /// 人和なし
+    Off,
+    /// 満貫 (他の役とは複合せず、満貫に満たない場合に満貫とする)
+    Mangan,
+    /// 役満
+    Yakuman,
+}
+
+impl Renho {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Renho::Off => "off",
+            Renho::Mangan => "mangan",
+            Renho::Yakuman => "yakuman",
+        }
+    }
+}
+
+/// 複数人のロンあがり (ダブロン/トリロン) の本場・供託の配分
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "kebab-case")
+)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MultiRonAllocation {
+    /// 放銃者から見て最も近い (下家側の) あがり者がすべて受け取る
+    Atamahane,
+    /// あがり者で等分する (端数は放銃者に最も近いあがり者が受け取る)
+    Split,
+}
+
+impl MultiRonAllocation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MultiRonAllocation::Atamahane => "atamahane",
+            MultiRonAllocation::Split => "split",
+        }
+    }
+}
+
+/// 三人麻雀のツモあがりの精算方法
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "kebab-case")
+)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SanmaTsumo {
+    /// ツモ損 (北家の支払い分は誰も支払わない)
+    Tsumozon,
+    /// 北家折半 (北家の支払い分を残りの2人で折半する)
+    Setsuhan,
+}
+
+impl SanmaTsumo {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SanmaTsumo::Tsumozon => "tsumozon",
+            SanmaTsumo::Setsuhan => "setsuhan",
+        }
+    }
+
+    pub fn from_name(s: &str) -> Option<Self> {
+        match s {
+            "tsumozon" => Some(SanmaTsumo::Tsumozon),
+            "setsuhan" => Some(SanmaTsumo::Setsuhan),
+            _ => None,
+        }
+    }
+}
+
+/// ルール設定
+///
+/// `Default` はこれまでの判定と同じルール (喰いタン・ダブル役満・数え役満あり、切り上げ満貫なし、連風牌4符、赤ドラは萬子・筒子・索子に各1枚、人和は満貫、ローカル役なし、ダブロン時の本場・供託は頭ハネ、四人麻雀)。
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(default))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rules {
+    /// 喰いタン
+    #[cfg_attr(feature = "wasm", wasm_bindgen(skip))]
+    pub kuitan: bool,
+    /// 国士無双十三面待ちをダブル役満とする
+    #[cfg_attr(feature = "wasm", wasm_bindgen(skip))]
+    pub kokushi_13men_double: bool,
+    /// 四暗刻単騎の扱い
+    #[cfg_attr(feature = "wasm", wasm_bindgen(skip))]
+    pub suanko_tanki: SuankoTanki,
+    /// 純正九蓮宝燈をダブル役満とする
+    #[cfg_attr(feature = "wasm", wasm_bindgen(skip))]
+    pub junsei_churen_double: bool,
+    /// 大四喜をダブル役満とする
+    #[cfg_attr(feature = "wasm", wasm_bindgen(skip))]
+    pub daisushi_double: bool,
+    /// 緑一色に發を必須とする
+    #[cfg_attr(feature = "wasm", wasm_bindgen(skip))]
+    pub ryuiso_hatsu_required: bool,
+    /// 九蓮宝燈を萬子のみに限る
+    #[cfg_attr(feature = "wasm", wasm_bindgen(skip))]
+    pub churen_manzu_only: bool,
+    /// 数え役満 (なしの場合、13飜以上は三倍満)
+    #[cfg_attr(feature = "wasm", wasm_bindgen(skip))]
+    pub kazoe_yakuman: bool,
+    /// 切り上げ満貫 (4飜30符、3飜60符を満貫とする)
+    #[cfg_attr(feature = "wasm", wasm_bindgen(skip))]
+    pub kiriage_mangan: bool,
+    /// 連風牌の雀頭の符
+    #[cfg_attr(feature = "wasm", wasm_bindgen(skip))]
+    pub renpuhai_fu: u32,
+    /// 赤ドラの枚数 (萬子・筒子・索子の順。すべて0の場合は赤ドラなし)
+    #[cfg_attr(feature = "wasm", wasm_bindgen(skip))]
+    #[cfg_attr(feature = "serde", serde(deserialize_with = "deserialize_akadora"))]
+    pub akadora: [u8; 3],
+    /// 人和の扱い
+    #[cfg_attr(feature = "wasm", wasm_bindgen(skip))]
+    pub renho: Renho,
+    /// ローカル役 (大車輪、三連刻など)
+    #[cfg_attr(feature = "wasm", wasm_bindgen(skip))]
+    pub local_yaku: bool,
+    /// 複数人のロンあがりの本場・供託の配分
+    #[cfg_attr(feature = "wasm", wasm_bindgen(skip))]
+    pub multi_ron_allocation: MultiRonAllocation,
+    /// 三人麻雀 (`None` の場合は四人麻雀)
+    #[cfg_attr(feature = "wasm", wasm_bindgen(skip))]
+    pub sanma: Option<SanmaTsumo>,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Self {
+            kuitan: true,
+            kokushi_13men_double: true,
+            suanko_tanki: SuankoTanki::Double,
+            junsei_churen_double: true,
+            daisushi_double: true,
+            ryuiso_hatsu_required: false,
+            churen_manzu_only: false,
+            kazoe_yakuman: true,
+            kiriage_mangan: false,
+            renpuhai_fu: 4,
+            akadora: [1, 1, 1],
+            renho: Renho::Mangan,
+            local_yaku: false,
+            multi_ron_allocation: MultiRonAllocation::Atamahane,
+            sanma: None,
+        }
+    }
+}
+
+impl Rules {
+    /// 天鳳
+    pub fn tenhou() -> Self {
+        Self {
+            renho: Renho::Off,
+            ..Self::default().without_double_yakuman()
+        }
+    }
+
+    /// Mリーグ
+    pub fn m_league() -> Self {
+        Self {
+            kiriage_mangan: true,
+            renpuhai_fu: 2,
+            renho: Renho::Off,
+            ..Self::default().without_double_yakuman()
+        }
+    }
+
+    /// WRC/EMA
+    pub fn wrc() -> Self {
+        Self {
+            kazoe_yakuman: false,
+            kiriage_mangan: true,
+            akadora: [0, 0, 0],
+            renho: Renho::Off,
+            ..Self::default().without_double_yakuman()
+        }
+    }
+
+    /// ダブル役満をすべて通常の役満として扱う
+    fn without_double_yakuman(self) -> Self {
+        Self {
+            kokushi_13men_double: false,
+            suanko_tanki: SuankoTanki::Single,
+            junsei_churen_double: false,
+            daisushi_double: false,
+            ..self
+        }
+    }
+
+    pub fn from_preset(name: &str) -> Option<Self> {
+        match name {
+            "default" => Some(Self::default()),
+            "tenhou" => Some(Self::tenhou()),
+            "m-league" => Some(Self::m_league()),
+            "wrc" => Some(Self::wrc()),
+            _ => None,
+        }
+    }
+
+    /// 種類ごとの赤ドラの枚数
+    pub fn akadora_limit(&self, category: HaiCategory) -> u8 {
+        match category {
+            HaiCategory::Manzu => self.akadora[0],
+            HaiCategory::Pinzu => self.akadora[1],
+            HaiCategory::Souzu => self.akadora[2],
+            HaiCategory::Jihai => 0,
+        }
+    }
+
+    /// 赤ドラの枚数を `1/2/1` (萬子/筒子/索子) または合計枚数の文字列から読み込む
+    ///
+    /// 合計枚数の場合は萬子・筒子・索子に均等に割り振り、余りは筒子・索子の順に加える (3枚なら各1枚、4枚なら筒子が2枚)。
+    pub fn akadora_from_str(s: &str) -> Option<[u8; 3]> {
+        let parts = s
+            .split('/')
+            .map(|n| n.trim().parse::<u8>().ok().filter(|n| *n <= 4))
+            .collect::<Option<Vec<_>>>()?;
+        match *parts.as_slice() {
+            [total] => Some(akadora_from_total(total)),
+            [m, p, s] => Some([m, p, s]),
+            _ => None,
+        }
+    }
+
+    /// 赤ドラの枚数の `1/2/1` (萬子/筒子/索子) 形式の文字列
+    pub fn akadora_str(&self) -> String {
+        let [m, p, s] = self.akadora;
+        format!("{}/{}/{}", m, p, s)
+    }
+}
+
+fn akadora_from_total(total: u8) -> [u8; 3] {
+    let (base, rest) = (total / 3, total % 3);
+    [base, base + u8::from(rest >= 1), base + u8::from(rest >= 2)]
+}
+
+/// 赤ドラの枚数を合計枚数または種類ごとの枚数の配列から読み込む
+#[cfg(feature = "serde")]
+fn deserialize_akadora<'de, D>(deserializer: D) -> Result<[u8; 3], D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Akadora {
+        Total(u8),
+        Distribution([u8; 3]),
+    }
+    // 合計枚数・種類ごとの枚数はいずれも4枚まで
+    let akadora = match Akadora::deserialize(deserializer)? {
+        Akadora::Total(total) => (total <= 4).then(|| akadora_from_total(total)),
+        Akadora::Distribution(akadora) => akadora.iter().all(|n| *n <= 4).then_some(akadora),
+    };
+    akadora.ok_or_else(|| serde::de::Error::custom("赤ドラの枚数が4枚を超えています"))
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+impl Rules {
+    #[wasm_bindgen(constructor)]
+    pub fn new_js() -> Rules {
+        Self::default()
+    }
+
+    #[wasm_bindgen(js_name = "fromPreset")]
+    pub fn from_preset_js(name: &str) -> Result<Rules, JsValue> {
+        let res = Self::from_preset(name).ok_or(format!("不明なルールです: `{}`", name))?;
+        Ok(res)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn kuitan(&self) -> bool {
+        self.kuitan
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_kuitan(&mut self, value: bool) {
+        self.kuitan = value;
+    }
+
+    #[wasm_bindgen(getter = kokushi13menDouble)]
+    pub fn kokushi_13men_double_js(&self) -> bool {
+        self.kokushi_13men_double
+    }
+
+    #[wasm_bindgen(setter = kokushi13menDouble)]
+    pub fn set_kokushi_13men_double_js(&mut self, value: bool) {
+        self.kokushi_13men_double = value;
+    }
+
+    #[wasm_bindgen(getter = suankoTanki)]
+    pub fn suanko_tanki_js(&self) -> String {
+        self.suanko_tanki.as_str().into()
+    }
+
+    #[wasm_bindgen(setter = suankoTanki)]
+    pub fn set_suanko_tanki_js(&mut self, value: &str) -> Result<(), JsValue> {
+        self.suanko_tanki = match value {
+            "single" => SuankoTanki::Single,
+            "tsumo-double" => SuankoTanki::TsumoDouble,
+            "double" => SuankoTanki::Double,
+            _ => return Err(format!("不明な四暗刻単騎の扱いです: `{}`", value).into()),
+        };
+        Ok(())
+    }
+
+    #[wasm_bindgen(getter = junseiChurenDouble)]
+    pub fn junsei_churen_double_js(&self) -> bool {
+        self.junsei_churen_double
+    }
+
+    #[wasm_bindgen(setter = junseiChurenDouble)]
+    pub fn set_junsei_churen_double_js(&mut self, value: bool) {
+        self.junsei_churen_double = value;
+    }
+
+    #[wasm_bindgen(getter = daisushiDouble)]
+    pub fn daisushi_double_js(&self) -> bool {
+        self.daisushi_double
+    }
+
+    #[wasm_bindgen(setter = daisushiDouble)]
+    pub fn set_daisushi_double_js(&mut self, value: bool) {
+        self.daisushi_double = value;
+    }
+
+    #[wasm_bindgen(getter = ryuisoHatsuRequired)]
+    pub fn ryuiso_hatsu_required_js(&self) -> bool {
+        self.ryuiso_hatsu_required
+    }
+
+    #[wasm_bindgen(setter = ryuisoHatsuRequired)]
+    pub fn set_ryuiso_hatsu_required_js(&mut self, value: bool) {
+        self.ryuiso_hatsu_required = value;
+    }
+
+    #[wasm_bindgen(getter = churenManzuOnly)]
+    pub fn churen_manzu_only_js(&self) -> bool {
+        self.churen_manzu_only
+    }
+
+    #[wasm_bindgen(setter = churenManzuOnly)]
+    pub fn set_churen_manzu_only_js(&mut self, value: bool) {
+        self.churen_manzu_only = value;
+    }
+
+    #[wasm_bindgen(getter = kazoeYakuman)]
+    pub fn kazoe_yakuman_js(&self) -> bool {
+        self.kazoe_yakuman
+    }
+
+    #[wasm_bindgen(setter = kazoeYakuman)]
+    pub fn set_kazoe_yakuman_js(&mut self, value: bool) {
+        self.kazoe_yakuman = value;
+    }
+
+    #[wasm_bindgen(getter = kiriageMangan)]
+    pub fn kiriage_mangan_js(&self) -> bool {
+        self.kiriage_mangan
+    }
+
+    #[wasm_bindgen(setter = kiriageMangan)]
+    pub fn set_kiriage_mangan_js(&mut self, value: bool) {
+        self.kiriage_mangan = value;
+    }
+
+    #[wasm_bindgen(getter = renpuhaiFu)]
+    pub fn renpuhai_fu_js(&self) -> u32 {
+        self.renpuhai_fu
+    }
+
+    #[wasm_bindgen(setter = renpuhaiFu)]
+    pub fn set_renpuhai_fu_js(&mut self, value: u32) -> Result<(), JsValue> {
+        if value != 2 && value != 4 {
+            return Err(format!("連風牌の符は2または4です: `{}`", value).into());
+        }
+        self.renpuhai_fu = value;
+        Ok(())
+    }
+
+    /// 赤ドラの枚数 (`1/1/1` のような萬子/筒子/索子の枚数)
+    #[wasm_bindgen(getter = akadora)]
+    pub fn akadora_js(&self) -> String {
+        self.akadora_str()
+    }
+
+    /// 赤ドラの枚数を `1/2/1` (萬子/筒子/索子) または合計枚数で設定する
+    #[wasm_bindgen(setter = akadora)]
+    pub fn set_akadora_js(&mut self, value: &str) -> Result<(), JsValue> {
+        self.akadora = Self::akadora_from_str(value)
+            .ok_or_else(|| format!("不正な赤ドラの枚数です: `{}`", value))?;
+        Ok(())
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn renho(&self) -> String {
+        self.renho.as_str().into()
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_renho(&mut self, value: &str) -> Result<(), JsValue> {
+        self.renho = match value {
+            "off" => Renho::Off,
+            "mangan" => Renho::Mangan,
+            "yakuman" => Renho::Yakuman,
+            _ => return Err(format!("不明な人和の扱いです: `{}`", value).into()),
+        };
+        Ok(())
+    }
+
+    #[wasm_bindgen(getter = localYaku)]
+    pub fn local_yaku_js(&self) -> bool {
+        self.local_yaku
+    }
+
+    #[wasm_bindgen(setter = localYaku)]
+    pub fn set_local_yaku_js(&mut self, value: bool) {
+        self.local_yaku = value;
+    }
+
+    #[wasm_bindgen(getter = multiRonAllocation)]
+    pub fn multi_ron_allocation_js(&self) -> String {
+        self.multi_ron_allocation.as_str().into()
+    }
+
+    #[wasm_bindgen(setter = multiRonAllocation)]
+    pub fn set_multi_ron_allocation_js(&mut self, value: &str) -> Result<(), JsValue> {
+        self.multi_ron_allocation = match value {
+            "atamahane" => MultiRonAllocation::Atamahane,
+            "split" => MultiRonAllocation::Split,
+            _ => return Err(format!("不明な本場・供託の配分です: `{}`", value).into()),
+        };
+        Ok(())
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn sanma(&self) -> Option<String> {
+        self.sanma.map(|sanma| sanma.as_str().into())
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_sanma(&mut self, value: Option<String>) -> Result<(), JsValue> {
+        self.sanma = match value.as_deref() {
+            None | Some("") => None,
+            Some(value) => Some(
+                SanmaTsumo::from_name(value)
+                    .ok_or(format!("不明な三人麻雀の精算方法です: `{}`", value))?,
+            ),
+        };
+        Ok(())
+    }
+}
+
+#[cfg(all(feature = "wasm", feature = "serde"))]
+#[wasm_bindgen]
+impl Rules {
+    #[wasm_bindgen(js_name = "toJSON")]
+    pub fn to_json_js(&self) -> Result<JsValue, JsValue> {
+        crate::serde_str::to_js(self)
+    }
+
+    #[wasm_bindgen(js_name = "fromJSON")]
+    pub fn from_json_js(value: &JsValue) -> Result<Rules, JsValue> {
+        crate::serde_str::from_js(value)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn rules_preset() {
+        // (喰いタン, 切り上げ満貫, 数え役満, ダブル役満, 赤ドラ, 連風牌の符, 人和)
+        let flags = |name: &str| {
+            let rules = Rules::from_preset(name).unwrap();
+            let double = [
+                rules.kokushi_13men_double,
+                rules.suanko_tanki == SuankoTanki::Double,
+                rules.junsei_churen_double,
+                rules.daisushi_double,
+            ];
+            assert!(double.iter().all(|d| *d == double[0]), "{}", name);
+            (
+                rules.kuitan,
+                rules.kiriage_mangan,
+                rules.kazoe_yakuman,
+                double[0],
+                rules.akadora,
+                rules.renpuhai_fu,
+                rules.renho,
+            )
+        };
+        assert_eq!(
+            flags("default"),
+            (true, false, true, true, [1, 1, 1], 4, Renho::Mangan)
+        );
+        assert_eq!(
+            flags("tenhou"),
+            (true, false, true, false, [1, 1, 1], 4, Renho::Off)
+        );
+        assert_eq!(
+            flags("m-league"),
+            (true, true, true, false, [1, 1, 1], 2, Renho::Off)
+        );
+        assert_eq!(
+            flags("wrc"),
+            (true, true, false, false, [0, 0, 0], 4, Renho::Off)
+        );
+        assert_eq!(Rules::from_preset("unknown"), None);
+    }
+
+    #[test]
+    fn akadora() {
+        use HaiCategory::*;
+        let mut rules = Rules::default();
+        let limit = |rules: &Rules| {
+            [Manzu, Pinzu, Souzu, Jihai].map(|category| rules.akadora_limit(category))
+        };
+        assert_eq!(limit(&rules), [1, 1, 1, 0]);
+        assert_eq!(limit(&Rules::wrc()), [0, 0, 0, 0]);
+
+        rules.akadora = Rules::akadora_from_str("0/2/1").unwrap();
+        assert_eq!(limit(&rules), [0, 2, 1, 0]);
+        assert_eq!(rules.akadora_str(), "0/2/1");
+        assert_eq!(Rules::akadora_from_str("4"), Some([1, 2, 1]));
+        assert_eq!(Rules::akadora_from_str("2"), Some([0, 1, 1]));
+        assert_eq!(Rules::akadora_from_str("0"), Some([0, 0, 0]));
+        assert_eq!(Rules::akadora_from_str("1/1"), None);
+        assert_eq!(Rules::akadora_from_str("1/5/1"), None);
+        assert_eq!(Rules::akadora_from_str("x"), None);
+    }
+}
@@ -0,0 +1,340 @@
+use crate::{
+    env::{self, Env},
+    hai::Hai,
+    hai_category::HaiCategory,
+    jun_tehai::JunTehai,
+    payment::Payment,
+};
+use num_bigint::BigUint;
+#[cfg(feature = "wasm")]
+use std::str::FromStr;
+use std::{convert::TryFrom, fmt};
+use thiserror::Error;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+/// 不聴罰符の総額
+const NOTEN_BAPPU: i32 = 3000;
+
+/// 流局の種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RyukyokuKind {
+    /// 荒牌平局 (牌山がなくなった場合の流局)
+    Kouhai,
+    /// 九種九牌
+    KyushuKyuhai,
+    /// 四風連打
+    Sufonrenda,
+    /// 四家立直
+    Suchariichi,
+    /// 四槓散了
+    Sukansanra,
+}
+
+impl RyukyokuKind {
+    fn to_name(self) -> &'static str {
+        use RyukyokuKind::*;
+        match self {
+            Kouhai => "荒牌平局",
+            KyushuKyuhai => "九種九牌",
+            Sufonrenda => "四風連打",
+            Suchariichi => "四家立直",
+            Sukansanra => "四槓散了",
+        }
+    }
+
+    /// 九種九牌以外の途中流局の種類を文字列から取得する
+    pub fn from_tochu_str(s: &str) -> Option<Self> {
+        use RyukyokuKind::*;
+        match s {
+            "sufonrenda" => Some(Sufonrenda),
+            "suchariichi" => Some(Suchariichi),
+            "sukansanra" => Some(Sukansanra),
+            _ => None,
+        }
+    }
+}
+
+/// 流局の結果
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ryukyoku {
+    kind: RyukyokuKind,
+    /// 流し満貫を達成したプレイヤーの自風
+    nagashi: Vec<u8>,
+    /// 各プレイヤーの点数の増減 (東家から順)
+    score: Vec<i32>,
+}
+
+impl fmt::Display for Ryukyoku {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.kind.to_name())?;
+        if !self.nagashi.is_empty() {
+            let names = self.nagashi.iter().map(|kaze| env::kaze_name(*kaze));
+            write!(f, " (流し満貫: {})", names.collect::<Vec<_>>().join(", "))?;
+        }
+        for (i, score) in self.score.iter().enumerate() {
+            let sep = if i == 0 { ":" } else { "," };
+            write!(f, "{} {}: {:+}点", sep, env::kaze_name(i as u8 + 1), score)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Error)]
+#[error(transparent)]
+pub struct Error(#[from] ErrorKind);
+
+#[derive(Debug, Error)]
+enum ErrorKind {
+    #[error("卓にいない席が指定されています: `{0}`")]
+    InvalidSeat(Hai),
+    #[error("同じ席が複数回指定されています: `{0}`")]
+    DuplicatedSeat(Hai),
+    #[error("三人麻雀では{}になりません", .0.to_name())]
+    NotInSanma(RyukyokuKind),
+    #[error("九種九牌は14枚の手牌で宣言します (牌の数: `{0}`)")]
+    InvalidHaiCount(usize),
+    #[error("么九牌が9種類未満です ({0}種類)")]
+    NotKyushuKyuhai(usize),
+}
+
+impl Ryukyoku {
+    /// 荒牌平局
+    ///
+    /// 流し満貫を達成したプレイヤーがいる場合は満貫のツモあがりと同じ支払いとし、不聴罰符は精算しない。
+    /// そうでない場合は聴牌者と不聴者で不聴罰符 (3000点) を精算する。
+    pub fn kouhai(env: &Env, tenpai: &[Hai], nagashi: &[Hai]) -> Result<Self, Error> {
+        let tenpai = seats(env, tenpai)?;
+        let nagashi = seats(env, nagashi)?;
+        let n = usize::from(env.num_players());
+        let mut score = vec![0; n];
+
+        if !nagashi.is_empty() {
+            let base_point = BigUint::from(2000u32);
+            for &kaze in &nagashi {
+                let mut env = env.clone();
+                env.jikaze = Hai::try_new(HaiCategory::Jihai, kaze, false).unwrap();
+                for payment in Payment::tsumo(&env, &base_point) {
+                    let amount = i32::try_from(payment.amount()).unwrap();
+                    let payer = env.tacha_kaze(payment.payer().unwrap());
+                    score[usize::from(payer - 1)] -= amount;
+                    score[usize::from(kaze - 1)] += amount;
+                }
+            }
+        } else if !tenpai.is_empty() && tenpai.len() < n {
+            let income = NOTEN_BAPPU / tenpai.len() as i32;
+            let outgo = NOTEN_BAPPU / (n - tenpai.len()) as i32;
+            for (i, score) in score.iter_mut().enumerate() {
+                *score = match tenpai.contains(&(i as u8 + 1)) {
+                    true => income,
+                    false => -outgo,
+                };
+            }
+        }
+
+        Ok(Self {
+            kind: RyukyokuKind::Kouhai,
+            nagashi,
+            score,
+        })
+    }
+
+    /// 九種九牌
+    pub fn kyushu_kyuhai(env: &Env, tehai: &JunTehai) -> Result<Self, Error> {
+        use ErrorKind as E;
+        let hai_count = tehai.as_slice().len();
+        if hai_count != 14 {
+            return Err(E::InvalidHaiCount(hai_count).into());
+        }
+        let mut yaochuhai = tehai
+            .iter()
+            .filter(Hai::is_yaochuhai)
+            .map(|hai| (hai.category(), hai.number()))
+            .collect::<Vec<_>>();
+        yaochuhai.sort();
+        yaochuhai.dedup();
+        if yaochuhai.len() < 9 {
+            return Err(E::NotKyushuKyuhai(yaochuhai.len()).into());
+        }
+        Self::tochu(env, RyukyokuKind::KyushuKyuhai)
+    }
+
+    /// 途中流局 (点数の移動はない)
+    pub fn tochu(env: &Env, kind: RyukyokuKind) -> Result<Self, Error> {
+        use RyukyokuKind::*;
+        if env.rules.sanma.is_some() && matches!(kind, Sufonrenda | Suchariichi) {
+            return Err(ErrorKind::NotInSanma(kind).into());
+        }
+        Ok(Self {
+            kind,
+            nagashi: vec![],
+            score: vec![0; usize::from(env.num_players())],
+        })
+    }
+}
+
+/// 自風の牌を席 (東:1, 南:2, 西:3, 北:4) に変換する
+fn seats(env: &Env, hai: &[Hai]) -> Result<Vec<u8>, Error> {
+    use ErrorKind as E;
+    let mut res = vec![];
+    for hai in hai {
+        if !hai.is_kazehai() || hai.number() > env.num_players() {
+            return Err(E::InvalidSeat(*hai).into());
+        }
+        if res.contains(&hai.number()) {
+            return Err(E::DuplicatedSeat(*hai).into());
+        }
+        res.push(hai.number());
+    }
+    Ok(res)
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+impl Ryukyoku {
+    /// 荒牌平局 (`tenpai`, `nagashi` は `13j` のように自風の牌で指定する)
+    #[wasm_bindgen(js_name = "kouhai")]
+    pub fn kouhai_js(env: &Env, tenpai: &str, nagashi: &str) -> Result<Ryukyoku, JsValue> {
+        let tenpai = JunTehai::from_str(tenpai).map_err(|e| e.to_string())?;
+        let nagashi = JunTehai::from_str(nagashi).map_err(|e| e.to_string())?;
+        let res =
+            Self::kouhai(env, tenpai.as_slice(), nagashi.as_slice()).map_err(|e| e.to_string())?;
+        Ok(res)
+    }
+
+    #[wasm_bindgen(js_name = "kyushuKyuhai")]
+    pub fn kyushu_kyuhai_js(env: &Env, tehai: &str) -> Result<Ryukyoku, JsValue> {
+        let tehai = JunTehai::from_str(tehai).map_err(|e| e.to_string())?;
+        let res = Self::kyushu_kyuhai(env, &tehai).map_err(|e| e.to_string())?;
+        Ok(res)
+    }
+
+    /// 途中流局 (`sufonrenda`, `suchariichi`, `sukansanra`)
+    #[wasm_bindgen(js_name = "tochu")]
+    pub fn tochu_js(env: &Env, kind: &str) -> Result<Ryukyoku, JsValue> {
+        let kind =
+            RyukyokuKind::from_tochu_str(kind).ok_or(format!("不明な途中流局です: `{}`", kind))?;
+        let res = Self::tochu(env, kind).map_err(|e| e.to_string())?;
+        Ok(res)
+    }
+
+    #[wasm_bindgen(getter = name)]
+    pub fn name_js(&self) -> String {
+        self.kind.to_name().into()
+    }
+
+    /// 各プレイヤーの点数の増減 (東家から順)
+    #[wasm_bindgen(getter = score)]
+    pub fn score_js(&self) -> Box<[i32]> {
+        self.score.clone().into_boxed_slice()
+    }
+
+    #[wasm_bindgen(js_name = "toString")]
+    pub fn to_string_js(&self) -> String {
+        self.to_string()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rules::SanmaTsumo;
+    use assert_matches::assert_matches;
+    use std::str::FromStr;
+
+    fn hai(s: &str) -> Vec<Hai> {
+        JunTehai::from_str(s).unwrap().as_slice().into()
+    }
+
+    #[test]
+    fn noten_bappu() {
+        let env = Env::new(Hai::from_str("1j").unwrap(), Hai::from_str("1j").unwrap());
+        let kouhai = |tenpai: &str| {
+            Ryukyoku::kouhai(&env, &hai(tenpai), &[])
+                .unwrap()
+                .to_string()
+        };
+        assert_eq!(
+            kouhai("2j"),
+            "荒牌平局: 東家: -1000点, 南家: +3000点, 西家: -1000点, 北家: -1000点"
+        );
+        assert_eq!(
+            kouhai("13j"),
+            "荒牌平局: 東家: +1500点, 南家: -1500点, 西家: +1500点, 北家: -1500点"
+        );
+        assert_eq!(
+            kouhai("134j"),
+            "荒牌平局: 東家: +1000点, 南家: -3000点, 西家: +1000点, 北家: +1000点"
+        );
+        assert_eq!(
+            kouhai("1234j"),
+            "荒牌平局: 東家: +0点, 南家: +0点, 西家: +0点, 北家: +0点"
+        );
+
+        let mut env = env.clone();
+        env.rules.sanma = Some(SanmaTsumo::Tsumozon);
+        assert_eq!(
+            Ryukyoku::kouhai(&env, &hai("1j"), &[]).unwrap().to_string(),
+            "荒牌平局: 東家: +3000点, 南家: -1500点, 西家: -1500点"
+        );
+    }
+
+    #[test]
+    fn nagashi_mangan() {
+        let env = Env::new(Hai::from_str("1j").unwrap(), Hai::from_str("1j").unwrap());
+        let kouhai = |tenpai: &str, nagashi: &str| {
+            Ryukyoku::kouhai(&env, &hai(tenpai), &hai(nagashi))
+                .unwrap()
+                .to_string()
+        };
+        // 流し満貫がある場合は不聴罰符を精算しない
+        assert_eq!(
+            kouhai("2j", "3j"),
+            "荒牌平局 (流し満貫: 西家): 東家: -4000点, 南家: -2000点, 西家: +8000点, 北家: -2000点"
+        );
+        assert_eq!(
+            kouhai("", "1j"),
+            "荒牌平局 (流し満貫: 東家): 東家: +12000点, 南家: -4000点, 西家: -4000点, 北家: -4000点"
+        );
+        assert_eq!(
+            kouhai("", "24j"),
+            "荒牌平局 (流し満貫: 南家, 北家): 東家: -8000点, 南家: +6000点, 西家: -4000点, 北家: +6000点"
+        );
+
+        let mut env = env.clone();
+        env.rules.sanma = Some(SanmaTsumo::Setsuhan);
+        assert_eq!(
+            Ryukyoku::kouhai(&env, &[], &hai("2j")).unwrap().to_string(),
+            "荒牌平局 (流し満貫: 南家): 東家: -5000点, 南家: +8000点, 西家: -3000点"
+        );
+    }
+
+    #[test]
+    fn tochu() {
+        use ErrorKind::*;
+        let mut env = Env::new(Hai::from_str("1j").unwrap(), Hai::from_str("1j").unwrap());
+        let tehai = JunTehai::from_str("119m19p19s1234567j").unwrap();
+        assert_eq!(
+            Ryukyoku::kyushu_kyuhai(&env, &tehai).unwrap().to_string(),
+            "九種九牌: 東家: +0点, 南家: +0点, 西家: +0点, 北家: +0点"
+        );
+        let tehai = JunTehai::from_str("123456m19p18s1234j").unwrap();
+        assert_matches!(
+            Ryukyoku::kyushu_kyuhai(&env, &tehai).unwrap_err().0,
+            NotKyushuKyuhai(8)
+        );
+
+        env.rules.sanma = Some(SanmaTsumo::Tsumozon);
+        assert_matches!(
+            Ryukyoku::tochu(&env, RyukyokuKind::Suchariichi)
+                .unwrap_err()
+                .0,
+            NotInSanma(RyukyokuKind::Suchariichi)
+        );
+        assert_matches!(
+            Ryukyoku::kouhai(&env, &hai("4j"), &[]).unwrap_err().0,
+            InvalidSeat(..)
+        );
+    }
+}
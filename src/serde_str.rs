@@ -0,0 +1,147 @@
+//! `Display`/`FromStr` による文字列表現でのシリアライズ (`serde` feature)
+
+use serde::{de, Deserialize, Deserializer, Serializer};
+#[cfg(feature = "wasm")]
+use serde::{de::DeserializeOwned, Serialize};
+use std::{fmt::Display, str::FromStr};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+pub(crate) fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Display,
+    S: Serializer,
+{
+    serializer.collect_str(value)
+}
+
+pub(crate) fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: FromStr,
+    T::Err: Display,
+    D: Deserializer<'de>,
+{
+    let s = String::deserialize(deserializer)?;
+    T::from_str(&s).map_err(de::Error::custom)
+}
+
+/// JSON に変換した JavaScript の値 (`toJSON` 用)
+#[cfg(feature = "wasm")]
+pub(crate) fn to_js<T: Serialize>(value: &T) -> Result<JsValue, JsValue> {
+    let json = serde_json::to_string(value).map_err(|e| e.to_string())?;
+    js_sys::JSON::parse(&json)
+}
+
+/// JavaScript の値を JSON として読み込む (`fromJSON` 用)
+#[cfg(feature = "wasm")]
+pub(crate) fn from_js<T: DeserializeOwned>(value: &JsValue) -> Result<T, JsValue> {
+    let json = String::from(js_sys::JSON::stringify(value)?);
+    let res = serde_json::from_str(&json).map_err(|e| e.to_string())?;
+    Ok(res)
+}
+
+/// 型を `Display`/`FromStr` の文字列表現でシリアライズ/デシリアライズする
+macro_rules! impl_serde_str {
+    ($ty:ty) => {
+        impl serde::Serialize for $ty {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                crate::serde_str::serialize(self, serializer)
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for $ty {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                crate::serde_str::deserialize(deserializer)
+            }
+        }
+    };
+}
+
+pub(crate) use impl_serde_str;
+
+#[cfg(test)]
+mod test {
+    use crate::{env::Env, hai::Hai, rules::Rules, tehai::Tehai, yaku::Yaku};
+    use std::str::FromStr;
+
+    #[test]
+    fn tehai() {
+        let tehai = Tehai::from_str("123m456p789s1j <222j ?1j").unwrap();
+        let json = serde_json::to_string(&tehai).unwrap();
+        assert_eq!(json, r#""123m456p789s1j <222j ?1j""#);
+        assert_eq!(serde_json::from_str::<Tehai>(&json).unwrap(), tehai);
+        assert!(serde_json::from_str::<Tehai>(r#""123m""#).is_err());
+    }
+
+    #[test]
+    fn env() {
+        let mut env = Env::new(Hai::from_str("1j").unwrap(), Hai::from_str("2j").unwrap());
+        env.set_richi("richi").unwrap();
+        env.set_hoju("<").unwrap();
+        env.set_dora("1m").unwrap();
+        let json = serde_json::to_value(&env).unwrap();
+        assert_eq!(json["richi"], "richi");
+        assert_eq!(json["hoju"], "<");
+        assert_eq!(json["dora"], serde_json::json!(["1m"]));
+        assert_eq!(json["rules"]["suanko_tanki"], "double");
+
+        let env2: Env = serde_json::from_value(json).unwrap();
+        assert_eq!(serde_json::to_value(&env2).unwrap()["bakaze"], "1j");
+
+        // ルールは省略した項目を既定値とする
+        let json = serde_json::json!({
+            "richi": null, "ippatsu": false, "rinshan": false, "haitei": false,
+            "tenho": false, "renho": false, "bakaze": "1j", "jikaze": "1j",
+            "dora": [], "uradora": [], "aotenjo": false, "honba": 0, "kyotaku": 0,
+            "hoju": null, "rules": { "kuitan": false },
+        });
+        let env: Env = serde_json::from_value(json).unwrap();
+        assert!(!env.rules.kuitan);
+        assert!(env.rules.kazoe_yakuman);
+        assert_eq!(env.rules.akadora, [1, 1, 1]);
+
+        // 赤ドラは種類ごとの枚数または合計枚数
+        let rules = serde_json::to_value(&env.rules).unwrap();
+        assert_eq!(rules["akadora"], serde_json::json!([1, 1, 1]));
+        let rules: Rules = serde_json::from_value(serde_json::json!({ "akadora": 4 })).unwrap();
+        assert_eq!(rules.akadora, [1, 2, 1]);
+        let rules: Rules =
+            serde_json::from_value(serde_json::json!({ "akadora": [0, 1, 1] })).unwrap();
+        assert_eq!(rules.akadora, [0, 1, 1]);
+        // 合計・種類ごとの枚数はいずれも4枚まで
+        assert!(serde_json::from_value::<Rules>(serde_json::json!({ "akadora": 12 })).is_err());
+        assert!(
+            serde_json::from_value::<Rules>(serde_json::json!({ "akadora": [9, 9, 9] })).is_err()
+        );
+    }
+
+    #[test]
+    fn yaku() {
+        let env = Env::new(Hai::from_str("1j").unwrap(), Hai::from_str("2j").unwrap());
+        let tehai = Tehai::from_str("123456789m1234p ?1p").unwrap();
+        let agari = &tehai.to_agari_combinations()[0];
+        let yaku = Yaku::new(agari, &env);
+        assert_eq!(
+            serde_json::to_value(agari).unwrap(),
+            serde_json::json!({
+                "tehai": "123456789m1234p ?1p",
+                "mentsu": ["123m", "456m", "789m", "234p", "11p"],
+                "machi_mentsu": 4,
+                "machi": "tanki",
+            })
+        );
+        assert_eq!(
+            serde_json::to_value(&yaku).unwrap(),
+            serde_json::json!({
+                "name": "",
+                "point": "2600",
+                "total": "2600",
+                "payments": [{ "payer": null, "oya": false, "pao": false, "amount": "2600" }],
+                "fu": 40,
+                "fu_detail": [["副底", 20], ["門前加符", 10], ["単騎待ち", 2], ["切り上げ", 8]],
+                "rank": { "fan": 2 },
+                "detail": [["一気通貫", { "fan": 2 }]],
+            })
+        );
+    }
+}
@@ -0,0 +1,277 @@
+use crate::{
+    furo::Furo, hai::Hai, hai_category::HaiCategory, jun_tehai::JunTehai,
+    kotsu_candidates::KotsuCandidates, shuntsu_candidates::ShuntsuCandidates,
+    toitsu_candidates::ToitsuCandidates,
+};
+use std::fmt;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+/// 向聴数
+///
+/// 聴牌は0、あがりの形 (14枚) は-1となる。七対子と国士無双は副露がある場合は `None`。
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Shanten {
+    /// 一般形 (4面子1雀頭)
+    normal: i32,
+    /// 七対子
+    chitoi: Option<i32>,
+    /// 国士無双
+    kokushi: Option<i32>,
+}
+
+impl fmt::Display for Shanten {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn to_str(shanten: i32) -> String {
+            match shanten {
+                -1 => "和了".into(),
+                0 => "聴牌".into(),
+                n => format!("{}向聴", n),
+            }
+        }
+        write!(f, "一般形: {}", to_str(self.normal))?;
+        if let Some(chitoi) = self.chitoi {
+            write!(f, ", 七対子: {}", to_str(chitoi))?;
+        }
+        if let Some(kokushi) = self.kokushi {
+            write!(f, ", 国士無双: {}", to_str(kokushi))?;
+        }
+        Ok(())
+    }
+}
+
+impl Shanten {
+    pub fn new(jun_tehai: &JunTehai, furo: &[Furo]) -> Self {
+        let mut hai = Vec::from(jun_tehai.as_slice());
+        hai.sort();
+        assert!(hai.len() + furo.len() * 3 <= 14);
+
+        let is_menzen = furo.is_empty();
+        Self {
+            normal: normal(&hai, 4 - furo.len()),
+            chitoi: is_menzen.then(|| chitoi(&hai)),
+            kokushi: is_menzen.then(|| kokushi(&hai)),
+        }
+    }
+
+    /// 各形の向聴数のうち最小のもの
+    pub fn min(&self) -> i32 {
+        self.normal
+            .min(self.chitoi.unwrap_or(i32::MAX))
+            .min(self.kokushi.unwrap_or(i32::MAX))
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+impl Shanten {
+    #[wasm_bindgen(getter = normal)]
+    pub fn normal_js(&self) -> i32 {
+        self.normal
+    }
+
+    #[wasm_bindgen(getter = chitoi)]
+    pub fn chitoi_js(&self) -> Option<i32> {
+        self.chitoi
+    }
+
+    #[wasm_bindgen(getter = kokushi)]
+    pub fn kokushi_js(&self) -> Option<i32> {
+        self.kokushi
+    }
+
+    #[wasm_bindgen(getter = min)]
+    pub fn min_js(&self) -> i32 {
+        self.min()
+    }
+
+    #[wasm_bindgen(js_name = "toString")]
+    pub fn to_string_js(&self) -> String {
+        self.to_string()
+    }
+}
+
+/// 一般形の向聴数
+fn normal(hai: &[Hai], num_mentsu: usize) -> i32 {
+    let mut search = Search {
+        hai,
+        num_mentsu,
+        has_janto: false,
+        best: i32::MAX,
+    };
+    search.visit(0, 0, 0);
+    for (_, janto_bits) in ToitsuCandidates::new(hai, 0, true) {
+        search.has_janto = true;
+        search.visit(janto_bits, 0, 0);
+    }
+    search.best
+}
+
+/// 面子・塔子の取り方を全探索する
+///
+/// 未使用の牌のうち先頭のものを、面子・塔子・孤立牌のいずれかとして使う。同じ種類の牌は常に先頭から使うため、
+/// 各種類の使用済みの牌は先頭に詰まっている。
+#[derive(Debug)]
+struct Search<'a> {
+    hai: &'a [Hai],
+    num_mentsu: usize,
+    has_janto: bool,
+    best: i32,
+}
+
+impl<'a> Search<'a> {
+    fn visit(&mut self, used_bits: u16, mentsu: usize, tatsu: usize) {
+        let all_bits = (0b1 << self.hai.len()) - 1;
+        if used_bits == all_bits || mentsu + tatsu >= self.num_mentsu {
+            let tatsu = tatsu.min(self.num_mentsu - mentsu);
+            let shanten = 2 * (self.num_mentsu - mentsu) as i32
+                - tatsu as i32
+                - if self.has_janto { 1 } else { 0 };
+            self.best = self.best.min(shanten);
+            if used_bits == all_bits || mentsu == self.num_mentsu {
+                return;
+            }
+        }
+
+        let start = used_bits.trailing_ones() as usize;
+        let start_bit = 0b1 << start;
+
+        // 面子
+        if let Some((_, bits)) = KotsuCandidates::new(self.hai, used_bits).next() {
+            if bits & start_bit != 0 {
+                self.visit(used_bits | bits, mentsu + 1, tatsu);
+            }
+        }
+        if let Some((_, bits)) = ShuntsuCandidates::new(self.hai, used_bits).next() {
+            if bits & start_bit != 0 {
+                self.visit(used_bits | bits, mentsu + 1, tatsu);
+            }
+        }
+
+        // 塔子
+        if let Some((_, bits)) = ToitsuCandidates::new(self.hai, used_bits, true).next() {
+            if bits & start_bit != 0 {
+                self.visit(used_bits | bits, mentsu, tatsu + 1);
+            }
+        }
+        for distance in 1..=2 {
+            if let Some(idx) = self.find_unused(used_bits, start, distance) {
+                self.visit(used_bits | start_bit | (0b1 << idx), mentsu, tatsu + 1);
+            }
+        }
+
+        // 孤立牌
+        self.visit(used_bits | start_bit, mentsu, tatsu);
+    }
+
+    /// `start` の牌と同じ種類で数字が `distance` だけ大きい未使用の牌を探す
+    fn find_unused(&self, used_bits: u16, start: usize, distance: u8) -> Option<usize> {
+        let start_hai = self.hai[start];
+        if start_hai.category() == HaiCategory::Jihai {
+            return None;
+        }
+        (start + 1..self.hai.len())
+            .filter(|idx| used_bits & (0b1 << idx) == 0)
+            .take_while(|idx| self.hai[*idx].category() == start_hai.category())
+            .find(|idx| self.hai[*idx].number() == start_hai.number() + distance)
+    }
+}
+
+/// 七対子の向聴数
+fn chitoi(hai: &[Hai]) -> i32 {
+    let mut kinds = 0;
+    let mut toitsu = 0;
+    for (idx, h) in hai.iter().enumerate() {
+        if idx > 0 && hai[idx - 1].is_same(h) {
+            if idx < 2 || !hai[idx - 2].is_same(h) {
+                toitsu += 1;
+            }
+        } else {
+            kinds += 1;
+        }
+    }
+    // 同じ牌4枚は2組の対子として扱わない
+    6 - toitsu + (7 - kinds).max(0)
+}
+
+/// 国士無双の向聴数
+fn kokushi(hai: &[Hai]) -> i32 {
+    let yaochuhai = hai.iter().filter(|h| h.is_yaochuhai()).collect::<Vec<_>>();
+    let mut kinds = 0;
+    let mut has_toitsu = false;
+    for (idx, h) in yaochuhai.iter().enumerate() {
+        if idx > 0 && yaochuhai[idx - 1].is_same(h) {
+            has_toitsu = true;
+        } else {
+            kinds += 1;
+        }
+    }
+    13 - kinds - if has_toitsu { 1 } else { 0 }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::mochihai::Mochihai;
+    use std::str::FromStr;
+
+    #[test]
+    fn shanten() {
+        fn shanten(s: &str) -> String {
+            Mochihai::from_str(s).unwrap().shanten().to_string()
+        }
+
+        // 聴牌
+        assert_eq!(
+            shanten("123m456p789s1122j"),
+            "一般形: 聴牌, 七対子: 4向聴, 国士無双: 8向聴",
+        );
+        assert_eq!(
+            shanten("23m456p567s789s11j"),
+            "一般形: 聴牌, 七対子: 4向聴, 国士無双: 10向聴",
+        );
+        // あがりの形
+        assert_eq!(
+            shanten("123m456p789s11122j"),
+            "一般形: 和了, 七対子: 4向聴, 国士無双: 8向聴",
+        );
+        // 一向聴
+        assert_eq!(
+            shanten("1245m456p789s11j5s"),
+            "一般形: 1向聴, 七対子: 5向聴, 国士無双: 9向聴",
+        );
+        // 七対子
+        assert_eq!(
+            shanten("1122m3344p5566s7j"),
+            "一般形: 3向聴, 七対子: 聴牌, 国士無双: 10向聴",
+        );
+        // 同じ牌4枚は七対子の対子2組にならない
+        assert_eq!(
+            shanten("1111m3344p5566s7j"),
+            "一般形: 2向聴, 七対子: 2向聴, 国士無双: 10向聴",
+        );
+        // 国士無双
+        assert_eq!(
+            shanten("19m19p19s1234567j"),
+            "一般形: 8向聴, 七対子: 6向聴, 国士無双: 聴牌",
+        );
+        assert_eq!(
+            shanten("19m19p19s12345677j"),
+            "一般形: 7向聴, 七対子: 5向聴, 国士無双: 和了",
+        );
+        // バラバラの手牌
+        assert_eq!(
+            shanten("1469m258p147s135j"),
+            "一般形: 7向聴, 七対子: 6向聴, 国士無双: 7向聴",
+        );
+        // 副露がある場合は一般形のみ
+        assert_eq!(shanten("3456m <213p <546p <879p"), "一般形: 聴牌");
+        assert_eq!(shanten("3479m <213p <546p <879p"), "一般形: 1向聴");
+        assert_eq!(shanten("33456m <213p <546p <879p"), "一般形: 和了");
+        // 面子過多
+        assert_eq!(
+            shanten("1234567m1234567p"),
+            "一般形: 聴牌, 七対子: 6向聴, 国士無双: 11向聴",
+        );
+    }
+}
@@ -1,14 +1,20 @@
 use enum_iterator::IntoEnumIterator;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 use std::fmt;
 
 /// 他家 (自家以外のプレイヤー)
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, IntoEnumIterator)]
-pub(crate) enum Tacha {
+pub enum Tacha {
     /// 上家
+    #[cfg_attr(feature = "serde", serde(rename = "<"))]
     Kamicha,
     /// 対面
+    #[cfg_attr(feature = "serde", serde(rename = "^"))]
     Toimen,
     /// 下家
+    #[cfg_attr(feature = "serde", serde(rename = ">"))]
     Shimocha,
 }
 
@@ -19,7 +25,7 @@ impl fmt::Display for Tacha {
 }
 
 impl Tacha {
-    pub(crate) fn to_str(self) -> &'static str {
+    pub fn to_str(self) -> &'static str {
         use Tacha::*;
         match self {
             Kamicha => "<",
@@ -27,4 +33,13 @@ impl Tacha {
             Shimocha => ">",
         }
     }
+
+    pub fn to_name(self) -> &'static str {
+        use Tacha::*;
+        match self {
+            Kamicha => "上家",
+            Toimen => "対面",
+            Shimocha => "下家",
+        }
+    }
 }
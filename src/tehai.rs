@@ -1,18 +1,20 @@
 use crate::{
-    agari::Agari, agari_hai::AgariHai, agari_type::AgariType, furo::Furo, hai::Hai,
+    agari::Agari, agari_hai::AgariHai, agari_type::AgariType, env::Env, furo::Furo, hai::Hai,
     jun_tehai::JunTehai, machi_combinations::MachiCombinations, mentsu::Mentsu,
-    mentsu_combinations,
+    mentsu_combinations, nukidora::Nukidora, rules::Rules,
 };
 use std::{cmp::Ordering, fmt, iter, str::FromStr};
 use thiserror::Error;
+#[cfg(feature = "wasm")]
 use wasm_bindgen::prelude::*;
 
 /// 手牌
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Tehai {
     jun_tehai: JunTehai,
     furo: Vec<Furo>,
+    nukidora: Vec<Nukidora>,
     agari_hai: AgariHai,
 }
 
@@ -22,13 +24,46 @@ impl fmt::Display for Tehai {
         for furo in &self.furo {
             write!(f, " {}", furo)?;
         }
+        for nukidora in &self.nukidora {
+            write!(f, " {}", nukidora)?;
+        }
         write!(f, " {}", self.agari_hai)?;
         Ok(())
     }
 }
 
 impl Tehai {
-    pub(crate) fn all_hai(&self) -> impl Iterator<Item = Hai> + '_ {
+    pub fn new(jun_tehai: JunTehai, furo: Vec<Furo>, agari_hai: AgariHai) -> Self {
+        Self {
+            jun_tehai,
+            furo,
+            nukidora: vec![],
+            agari_hai,
+        }
+    }
+
+    /// 抜きドラを設定する
+    pub fn with_nukidora(self, nukidora: Vec<Nukidora>) -> Self {
+        Self { nukidora, ..self }
+    }
+
+    /// ルールに合わせて手牌をパースする
+    ///
+    /// 三人麻雀では 2m〜8m を含む手牌を、四人麻雀では抜きドラを含む手牌をエラーとする。
+    pub fn from_str_with_rules(s: &str, rules: &Rules) -> Result<Self, ParseError> {
+        use ParseErrorKind as E;
+        let tehai = Self::from_str(s)?;
+        if rules.sanma.is_some() {
+            if let Some(hai) = tehai.all_hai().find(Hai::is_unused_in_sanma) {
+                return Err(E::UnusedInSanma(hai).into());
+            }
+        } else if let Some(nukidora) = tehai.nukidora.first() {
+            return Err(E::NukidoraInYonma(*nukidora).into());
+        }
+        Ok(tehai)
+    }
+
+    pub fn all_hai(&self) -> impl Iterator<Item = Hai> + '_ {
         self.jun_tehai
             .iter()
             .chain(self.furo.iter().flat_map(|furo| furo.iter()))
@@ -42,7 +77,7 @@ impl Tehai {
         mentsu_combinations::combinations(&tehai)
     }
 
-    pub(crate) fn to_agari_combinations(&self) -> Vec<Agari> {
+    pub fn to_agari_combinations(&self) -> Vec<Agari> {
         let mut res = vec![];
         for mentsu in self.to_mentsu_combinations() {
             let machi = MachiCombinations::new(mentsu.iter().copied(), self.agari_hai.hai())
@@ -52,19 +87,45 @@ impl Tehai {
         res
     }
 
-    pub(crate) fn is_menzen(&self) -> bool {
+    /// 局の状況に応じたあがり形を列挙する
+    ///
+    /// ローカル役が有効で天和/地和の状況の場合は、和了形ではない十三不塔の形も含める。
+    pub fn to_agari_combinations_with_env(&self, env: &Env) -> Vec<Agari> {
+        let mut res = self.to_agari_combinations();
+        if env.rules.local_yaku && env.tenho {
+            let mut tehai = Vec::from(self.jun_tehai.as_slice());
+            tehai.push(self.agari_hai.hai());
+            if let Some(mentsu) = mentsu_combinations::to_shisanputa(&tehai) {
+                let machi = MachiCombinations::new(mentsu.iter().copied(), self.agari_hai.hai())
+                    .map(|machi| Agari::new(self.clone(), mentsu.clone(), machi.1, machi.0));
+                res.extend(machi);
+            }
+        }
+        res
+    }
+
+    pub fn is_menzen(&self) -> bool {
         self.furo.iter().all(|furo| furo.is_menzen())
     }
 
-    pub(crate) fn furo(&self) -> &[Furo] {
+    pub fn jun_tehai(&self) -> &JunTehai {
+        &self.jun_tehai
+    }
+
+    pub fn furo(&self) -> &[Furo] {
         &self.furo
     }
 
-    pub(crate) fn agari_hai(&self) -> AgariHai {
+    pub fn nukidora(&self) -> &[Nukidora] {
+        &self.nukidora
+    }
+
+    pub fn agari_hai(&self) -> AgariHai {
         self.agari_hai
     }
 }
 
+#[cfg(feature = "wasm")]
 #[wasm_bindgen]
 impl Tehai {
     #[wasm_bindgen(js_name = "toString")]
@@ -82,6 +143,11 @@ impl Tehai {
         self.furo.iter().copied().map(JsValue::from).collect()
     }
 
+    #[wasm_bindgen(getter = nukidora)]
+    pub fn nukidora_js(&self) -> Box<[JsValue]> {
+        self.nukidora.iter().copied().map(JsValue::from).collect()
+    }
+
     #[wasm_bindgen(getter = agariHai)]
     pub fn agari_hai_js(&self) -> AgariHai {
         self.agari_hai
@@ -95,11 +161,39 @@ impl Tehai {
             .collect()
     }
 
+    #[wasm_bindgen(js_name = "toAgariCombinationsWithEnv")]
+    pub fn to_agari_combinations_with_env_js(&self, env: &Env) -> Box<[JsValue]> {
+        self.to_agari_combinations_with_env(env)
+            .into_iter()
+            .map(JsValue::from)
+            .collect()
+    }
+
     #[wasm_bindgen(js_name = "fromStr")]
     pub fn from_str_js(s: &str) -> Result<Tehai, JsValue> {
         let res = Self::from_str(s).map_err(|e| e.to_string())?;
         Ok(res)
     }
+
+    #[wasm_bindgen(js_name = "fromStrWithRules")]
+    pub fn from_str_with_rules_js(s: &str, rules: &Rules) -> Result<Tehai, JsValue> {
+        let res = Self::from_str_with_rules(s, rules).map_err(|e| e.to_string())?;
+        Ok(res)
+    }
+}
+
+#[cfg(all(feature = "wasm", feature = "serde"))]
+#[wasm_bindgen]
+impl Tehai {
+    #[wasm_bindgen(js_name = "toJSON")]
+    pub fn to_json_js(&self) -> Result<JsValue, JsValue> {
+        crate::serde_str::to_js(self)
+    }
+
+    #[wasm_bindgen(js_name = "fromJSON")]
+    pub fn from_json_js(value: &JsValue) -> Result<Tehai, JsValue> {
+        crate::serde_str::from_js(value)
+    }
 }
 
 #[derive(Debug, Error)]
@@ -120,10 +214,16 @@ enum ParseErrorKind {
     JunTehai(#[from] <JunTehai as FromStr>::Err),
     #[error("副露のパースエラー: {0}")]
     Furo(#[from] <Furo as FromStr>::Err),
+    #[error("抜きドラのパースエラー: {0}")]
+    Nukidora(#[from] <Nukidora as FromStr>::Err),
     #[error("あがり牌のパースエラー: {0}")]
     AgariHai(#[from] <AgariHai as FromStr>::Err),
     #[error("あがり牌が複数あります: `{0}`, `{1}")]
     MultipleAgariHai(AgariHai, AgariHai),
+    #[error("三人麻雀では `{0}` は使用しません")]
+    UnusedInSanma(Hai),
+    #[error("抜きドラ `{0}` は三人麻雀でのみ使用できます")]
+    NukidoraInYonma(Nukidora),
 }
 
 impl FromStr for Tehai {
@@ -137,6 +237,7 @@ impl FromStr for Tehai {
         let jun_tehai = JunTehai::from_str(tehai_chunk).map_err(E::from)?;
 
         let mut furo = vec![];
+        let mut nukidora = vec![];
         let mut agari_hai = None;
         for chunk in chunks {
             if AgariType::is_agari_str(chunk) {
@@ -145,6 +246,8 @@ impl FromStr for Tehai {
                     return Err(E::MultipleAgariHai(old_agari_hai, new_agari_hai).into());
                 }
                 agari_hai = Some(new_agari_hai);
+            } else if Nukidora::is_nukidora_str(chunk) {
+                nukidora.push(Nukidora::from_str(chunk).map_err(E::from)?);
             } else {
                 furo.push(Furo::from_str(chunk).map_err(E::from)?);
             }
@@ -159,14 +262,19 @@ impl FromStr for Tehai {
             Ordering::Greater => return Err(E::Tahai(hai_count).into()),
         }
 
+        // 抜きドラは手牌の枚数に含めない
         Ok(Tehai {
             jun_tehai,
             furo,
+            nukidora,
             agari_hai,
         })
     }
 }
 
+#[cfg(feature = "serde")]
+crate::serde_str::impl_serde_str!(Tehai);
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -211,5 +319,35 @@ mod test {
         assert_matches!(err("123m <x"), Furo(..));
         assert_matches!(err("123m ?x"), AgariHai(..));
         assert_matches!(err("123m ?4m !4m"), MultipleAgariHai(a, b) if h!("?4m!4m", a, b));
+
+        // 抜きドラ
+        assert_eq!(
+            ok("19m123p789p11s123s *4j !9m *4j"),
+            "19m123789p11123s *4j *4j !9m",
+        );
+        assert_matches!(err("19m123p789p11s123s *1j !9m"), Nukidora(..));
+    }
+
+    #[test]
+    fn parse_with_rules() {
+        use crate::rules::SanmaTsumo;
+        use ParseErrorKind::*;
+
+        let yonma = Rules::default();
+        let sanma = Rules {
+            sanma: Some(SanmaTsumo::Tsumozon),
+            ..Rules::default()
+        };
+        fn err(s: &str, rules: &Rules) -> ParseErrorKind {
+            Tehai::from_str_with_rules(s, rules).unwrap_err().0
+        }
+
+        let s = "19m123p789p11s123s *4j !9m";
+        assert!(Tehai::from_str_with_rules(s, &sanma).is_ok());
+        assert_matches!(err(s, &yonma), NukidoraInYonma(..));
+
+        let s = "123m123p789p11s12s !3s";
+        assert!(Tehai::from_str_with_rules(s, &yonma).is_ok());
+        assert_matches!(err(s, &sanma), UnusedInSanma(hai) if hai.to_string() == "2m");
     }
 }
@@ -0,0 +1,511 @@
+//! JSON 形式の牌譜
+//!
+//! 牌は 11〜19 (萬子), 21〜29 (筒子), 31〜39 (索子), 41〜47 (字牌), 51〜53 (赤ドラの五萬・五筒・五索) で表す。
+
+use super::{Error, ErrorKind as E, TenhouAgari};
+use crate::{
+    agari_hai::AgariHai,
+    agari_type::AgariType,
+    furo::{Furo, FuroKind},
+    hai::Hai,
+    hai_category::HaiCategory,
+    nukidora::Nukidora,
+    tacha::Tacha,
+};
+use serde_json::Value;
+use std::convert::TryFrom;
+
+/// ツモ切りを表す打牌
+const TSUMOGIRI: u64 = 60;
+
+/// JSON 形式の牌譜からすべてのあがりを読み込む
+///
+/// `log` 配列を含むオブジェクト、または `log` 配列そのものを受け付ける。
+pub fn parse_json(s: &str) -> Result<Vec<TenhouAgari>, Error> {
+    let value = serde_json::from_str::<Value>(s).map_err(E::from)?;
+    let log = match &value {
+        Value::Object(obj) => obj.get("log").unwrap_or(&Value::Null),
+        value => value,
+    };
+    let mut res = vec![];
+    for kyoku in array(log, "log")? {
+        res.extend(parse_kyoku(kyoku)?);
+    }
+    Ok(res)
+}
+
+/// 局の記録
+///
+/// `[[局, 本場, 供託], 持ち点, ドラ表示牌, 裏ドラ表示牌, (配牌, 取得牌, 打牌) × 4, 結果]`
+fn parse_kyoku(kyoku: &Value) -> Result<Vec<TenhouAgari>, Error> {
+    let kyoku = array(kyoku, "局")?;
+    if kyoku.len() != 17 {
+        return Err(E::invalid_format(format!("局の要素数が不正です: {}", kyoku.len())).into());
+    }
+    let result = array(&kyoku[16], "結果")?;
+    if result.first().and_then(Value::as_str) != Some("和了") {
+        return Ok(vec![]);
+    }
+
+    let (kyoku_num, honba, kyotaku) = match array(&kyoku[0], "局")?[..] {
+        [ref kyoku_num, ref honba, ref kyotaku] => (
+            num(kyoku_num, "局")? as u32,
+            num(honba, "本場")? as u32,
+            num(kyotaku, "供託")? as u32,
+        ),
+        _ => return Err(E::invalid_format("局・本場・供託の形式が不正です").into()),
+    };
+    let dora = hai_array(&kyoku[2], "ドラ表示牌")?;
+    let uradora = hai_array(&kyoku[3], "裏ドラ表示牌")?;
+    let players = (0..4)
+        .map(|i| -> Result<_, Error> {
+            Ok(Player {
+                haipai: array(&kyoku[4 + 3 * i], "配牌")?,
+                takes: array(&kyoku[5 + 3 * i], "取得牌")?,
+                discards: array(&kyoku[6 + 3 * i], "打牌")?,
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let sanma = players[3].haipai.is_empty();
+    let n = if sanma { 3 } else { 4 };
+
+    let mut res = vec![];
+    // 結果は `["和了", 点数の増減, あがりの情報, ...]` で、複数人のあがりの場合は点数の増減とあがりの情報が続く
+    for chunk in result[1..].chunks(2) {
+        let info = array(chunk.get(1).unwrap_or(&Value::Null), "あがりの情報")?;
+        let (who, from_who, point_str) = match info {
+            [who, from_who, _pao, point_str, ..] => (
+                num(who, "あがった席")?,
+                num(from_who, "放銃した席")?,
+                point_str.as_str().unwrap_or_default(),
+            ),
+            _ => return Err(E::invalid_format("あがりの情報の形式が不正です").into()),
+        };
+        if who >= n || from_who >= n {
+            return Err(E::invalid_format(format!("不正な席です: {}, {}", who, from_who)).into());
+        }
+        let (who, from_who) = (who as u8, from_who as u8);
+
+        let mut env = super::new_env(sanma, kyoku_num, (kyoku_num % 4) as u8, who);
+        env.honba = honba;
+        env.kyotaku = kyotaku;
+        env.dora = dora.clone();
+        env.uradora = uradora.clone();
+        env.hoju = super::hoju_tacha(who, from_who, sanma);
+        for yaku in &info[4..] {
+            let name = yaku.as_str().unwrap_or_default();
+            super::set_flag(&mut env, name.split('(').next().unwrap_or_default());
+        }
+        let (fu, point) = parse_point(point_str, sanma)
+            .ok_or_else(|| E::invalid_format(format!("点数の形式が不正です: {}", point_str)))?;
+
+        let winner = &players[who as usize];
+        let mut hand = winner.replay()?;
+        let agari_hai = if who == from_who {
+            if winner.takes.len() != winner.discards.len() + 1 {
+                return Err(E::invalid_format("ツモあがりの取得牌の数が不正です").into());
+            }
+            let hai = hand
+                .drawn
+                .ok_or_else(|| E::invalid_format("あがり牌がありません"))?;
+            AgariHai::new(AgariType::Tsumo, hai)
+        } else {
+            let hai = players[from_who as usize].last_discard()?;
+            hand.hai.push(hai);
+            AgariHai::new(AgariType::Ron, hai)
+        };
+        let tehai = super::build_tehai(hand.hai, hand.furo, hand.nukidora, agari_hai)?;
+
+        res.push(TenhouAgari {
+            who,
+            from_who,
+            tehai,
+            env,
+            fu,
+            point,
+        });
+    }
+    Ok(res)
+}
+
+/// 1人のプレイヤーの配牌・取得牌・打牌
+struct Player<'a> {
+    haipai: &'a [Value],
+    takes: &'a [Value],
+    discards: &'a [Value],
+}
+
+/// 取得牌・打牌を反映した手牌
+struct Hand {
+    hai: Vec<Hai>,
+    furo: Vec<Furo>,
+    nukidora: Vec<Nukidora>,
+    /// 最後にツモった牌
+    drawn: Option<Hai>,
+}
+
+impl Player<'_> {
+    /// 配牌から順に取得牌・打牌を反映した手牌を返す
+    fn replay(&self) -> Result<Hand, Error> {
+        let mut hand = self
+            .haipai
+            .iter()
+            .map(|code| hai(num(code, "配牌")?))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut furo = vec![];
+        let mut nukidora = vec![];
+        let mut drawn = None;
+
+        for (i, take) in self.takes.iter().enumerate() {
+            match take {
+                Value::String(s) => {
+                    let meld = Meld::parse(s)?;
+                    for (j, hai) in meld.hai.iter().enumerate() {
+                        if j != meld.called {
+                            super::remove_hai(&mut hand, *hai)?;
+                        }
+                    }
+                    furo.push(meld.to_furo()?);
+                    drawn = None;
+                }
+                code => {
+                    let hai = hai(num(code, "取得牌")?)?;
+                    hand.push(hai);
+                    drawn = Some(hai);
+                }
+            }
+
+            let discard = match self.discards.get(i) {
+                Some(discard) => discard,
+                None => break,
+            };
+            match discard {
+                Value::String(s) if s.starts_with('r') => {
+                    let code = s[1..].parse().map_err(|_| E::InvalidMeld(s.clone()))?;
+                    discard_hai(&mut hand, code, drawn)?;
+                }
+                Value::String(s) => {
+                    let meld = Meld::parse(s)?;
+                    match meld.kind {
+                        'a' => {
+                            for hai in &meld.hai {
+                                super::remove_hai(&mut hand, *hai)?;
+                            }
+                            furo.push(meld.to_furo()?);
+                        }
+                        'k' => {
+                            let (pon, added) = meld.find_pon(&furo)?;
+                            super::remove_hai(&mut hand, added)?;
+                            furo[pon] = match *furo[pon].kind() {
+                                FuroKind::Pon {
+                                    from_tehai,
+                                    from_tacha,
+                                    tacha,
+                                } => FuroKind::Kakan {
+                                    from_tehai,
+                                    from_tacha,
+                                    tacha,
+                                    added,
+                                }
+                                .into(),
+                                _ => unreachable!(),
+                            };
+                        }
+                        'f' => {
+                            let hai = meld.hai[meld.called];
+                            super::remove_hai(&mut hand, hai)?;
+                            let n =
+                                Nukidora::try_from(hai).map_err(|_| E::InvalidMeld(s.clone()))?;
+                            nukidora.push(n);
+                        }
+                        _ => return Err(E::InvalidMeld(s.clone()).into()),
+                    }
+                }
+                code => discard_hai(&mut hand, num(code, "打牌")?, drawn)?,
+            }
+        }
+
+        Ok(Hand {
+            hai: hand,
+            furo,
+            nukidora,
+            drawn,
+        })
+    }
+
+    /// 最後に捨てた牌 (ロンあがりの場合の放銃牌。槍槓の場合は加槓した牌)
+    fn last_discard(&self) -> Result<Hai, Error> {
+        let no_discard = || E::invalid_format("放銃した牌がありません");
+        let last_take = || {
+            self.takes
+                .last()
+                .and_then(Value::as_u64)
+                .ok_or_else(no_discard)
+        };
+        let code = match self.discards.last().ok_or_else(no_discard)? {
+            Value::String(s) if s.starts_with('r') => {
+                s[1..].parse().map_err(|_| E::InvalidMeld(s.clone()))?
+            }
+            Value::String(s) => {
+                let meld = Meld::parse(s)?;
+                return Ok(meld.hai[meld.called]);
+            }
+            code => num(code, "打牌")?,
+        };
+        match code {
+            TSUMOGIRI => hai(last_take()?),
+            code => hai(code),
+        }
+    }
+}
+
+/// 打牌を手牌から取り除く (0 は大明槓の後の打牌なし)
+fn discard_hai(hand: &mut Vec<Hai>, code: u64, drawn: Option<Hai>) -> Result<(), Error> {
+    match code {
+        0 => Ok(()),
+        TSUMOGIRI => {
+            let hai = drawn.ok_or_else(|| E::invalid_format("ツモ切りする牌がありません"))?;
+            super::remove_hai(hand, hai)
+        }
+        code => super::remove_hai(hand, hai(code)?),
+    }
+}
+
+/// 副露の文字列 (`c275226`, `15p1515`, `393939a39` など)
+///
+/// 英字の位置が牌の取得元を、英字の直後の牌が鳴いた牌 (加槓の場合は追加した牌) を表す。
+struct Meld {
+    kind: char,
+    hai: Vec<Hai>,
+    /// 英字の直後の牌の位置
+    called: usize,
+    source: String,
+}
+
+impl Meld {
+    fn parse(s: &str) -> Result<Self, Error> {
+        let invalid = || Error::from(E::InvalidMeld(s.into()));
+        let pos = s
+            .find(|c: char| c.is_ascii_alphabetic())
+            .ok_or_else(invalid)?;
+        let kind = s[pos..].chars().next().unwrap();
+        let digits = format!("{}{}", &s[..pos], &s[pos + 1..]);
+        if pos % 2 != 0 || digits.len() % 2 != 0 || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(invalid());
+        }
+        let hai = (0..digits.len())
+            .step_by(2)
+            .map(|i| hai(digits[i..i + 2].parse().unwrap()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let called = pos / 2;
+        if called >= hai.len() {
+            return Err(invalid());
+        }
+        Ok(Self {
+            kind,
+            hai,
+            called,
+            source: s.into(),
+        })
+    }
+
+    fn invalid(&self) -> Error {
+        E::InvalidMeld(self.source.clone()).into()
+    }
+
+    /// 英字の位置から牌の取得元を求める (先頭: 上家、2番目: 対面、末尾: 下家)
+    fn tacha(&self) -> Result<Tacha, Error> {
+        match self.called {
+            0 => Ok(Tacha::Kamicha),
+            i if i + 1 == self.hai.len() => Ok(Tacha::Shimocha),
+            1 => Ok(Tacha::Toimen),
+            _ => Err(self.invalid()),
+        }
+    }
+
+    fn to_furo(&self) -> Result<Furo, Error> {
+        let called = self.hai[self.called];
+        let others = self
+            .hai
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| *i != self.called)
+            .map(|(_, hai)| *hai)
+            .collect::<Vec<_>>();
+        let kind = match (self.kind, &others[..]) {
+            ('c', &[t0, t1]) if self.called == 0 => FuroKind::Chi {
+                from_tehai: [t0, t1],
+                from_kamicha: called,
+            },
+            ('p', &[t0, t1]) => FuroKind::Pon {
+                from_tehai: [t0, t1],
+                from_tacha: called,
+                tacha: self.tacha()?,
+            },
+            ('m', &[t0, t1, t2]) => FuroKind::Daiminkan {
+                from_tehai: [t0, t1, t2],
+                from_tacha: called,
+                tacha: self.tacha()?,
+            },
+            ('a', _) if self.hai.len() == 4 => FuroKind::Ankan {
+                from_tehai: [self.hai[0], self.hai[1], self.hai[2], self.hai[3]],
+            },
+            _ => return Err(self.invalid()),
+        };
+        Ok(kind.into())
+    }
+
+    /// 加槓するポンの位置と追加した牌を求める
+    fn find_pon(&self, furo: &[Furo]) -> Result<(usize, Hai), Error> {
+        for (i, f) in furo.iter().enumerate() {
+            if let FuroKind::Pon { from_tacha, .. } = f.kind() {
+                if !from_tacha.is_same(&self.hai[0]) {
+                    continue;
+                }
+                // ポンした3枚を除いた残りの1枚が追加した牌
+                let mut rest = self.hai.clone();
+                for hai in f.iter() {
+                    super::remove_hai(&mut rest, hai)?;
+                }
+                return match rest[..] {
+                    [added] => Ok((i, added)),
+                    _ => Err(self.invalid()),
+                };
+            }
+        }
+        Err(self.invalid())
+    }
+}
+
+fn array<'a>(value: &'a Value, name: &str) -> Result<&'a [Value], Error> {
+    value
+        .as_array()
+        .map(Vec::as_slice)
+        .ok_or_else(|| E::invalid_format(format!("{} が配列ではありません", name)).into())
+}
+
+fn num(value: &Value, name: &str) -> Result<u64, Error> {
+    value.as_u64().ok_or_else(|| {
+        E::invalid_format(format!("{} が数値ではありません: {}", name, value)).into()
+    })
+}
+
+fn hai_array(value: &Value, name: &str) -> Result<Vec<Hai>, Error> {
+    array(value, name)?
+        .iter()
+        .map(|code| hai(num(code, name)?))
+        .collect()
+}
+
+fn hai(code: u64) -> Result<Hai, Error> {
+    let invalid = || Error::from(E::InvalidHai(code as u32));
+    let (category, number, akadora) = match code {
+        51..=53 => (code - 50, 5, true),
+        _ => (code / 10, code % 10, false),
+    };
+    let category = match category {
+        1 => HaiCategory::Manzu,
+        2 => HaiCategory::Pinzu,
+        3 => HaiCategory::Souzu,
+        4 => HaiCategory::Jihai,
+        _ => return Err(invalid()),
+    };
+    Hai::try_new(category, number as u8, akadora).map_err(|_| invalid())
+}
+
+/// 点数の文字列 (`30符1飜1000点`, `満貫2000-4000点`, `跳満6000点∀` など) から符と点数を求める
+///
+/// 子のツモあがりは `子の支払い-親の支払い`、親のツモあがりは末尾の `∀` で1人あたりの支払いを表す。
+fn parse_point(s: &str, sanma: bool) -> Option<(Option<u32>, u32)> {
+    let fu = s.find('符').and_then(|i| s[..i].parse().ok());
+    let (s, oya_tsumo) = match s.strip_suffix('∀') {
+        Some(s) => (s, true),
+        None => (s, false),
+    };
+    let s = s.strip_suffix('点')?;
+    let start = s
+        .char_indices()
+        .rev()
+        .find(|(_, c)| !c.is_ascii_digit() && *c != '-')
+        .map_or(0, |(i, c)| i + c.len_utf8());
+    let point = &s[start..];
+    let tacha = if sanma { 2 } else { 3 };
+    let point = match point.split_once('-') {
+        Some((ko, oya)) => {
+            let (ko, oya) = (ko.parse::<u32>().ok()?, oya.parse::<u32>().ok()?);
+            ko * (tacha - 1) + oya
+        }
+        None if oya_tsumo => point.parse::<u32>().ok()? * tacha,
+        None => point.parse().ok()?,
+    };
+    Some((fu, point))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{env::RichiType, tehai::Tehai};
+    use std::str::FromStr;
+
+    #[test]
+    fn point() {
+        assert_eq!(parse_point("30符1飜1000点", false), Some((Some(30), 1000)));
+        assert_eq!(
+            parse_point("40符2飜700-1300点", false),
+            Some((Some(40), 2700))
+        );
+        assert_eq!(parse_point("満貫2000-4000点", true), Some((None, 6000)));
+        assert_eq!(parse_point("跳満6000点∀", false), Some((None, 18000)));
+        assert_eq!(parse_point("跳満6000点∀", true), Some((None, 12000)));
+        assert_eq!(parse_point("役満", false), None);
+    }
+
+    #[test]
+    fn kyoku() {
+        let log = r#"{"title": ["", ""], "name": ["A", "B", "C", "D"], "rule": {"disp": "般南喰赤", "aka": 1},
+"log": [
+  [[0, 0, 0], [25000, 25000, 25000, 25000], [23], [29],
+   [11, 12, 13, 24, 52, 26, 37, 38, 41, 41, 41, 47, 47], [35, 39], ["r60"],
+   [19], [], [], [19], [], [], [19], [], [],
+   ["和了", [19000, -6000, -6000, -6000],
+    [0, 0, 0, "跳満6000点∀", "立直(1飜)", "門前清自摸和(1飜)", "場風 東(1飜)", "自風 東(1飜)", "ドラ(1飜)", "赤ドラ(1飜)"]]],
+  [[5, 1, 0], [25000, 25000, 25000, 25000], [29], [],
+   [19], [], [], [19], [], [],
+   [12, 13, 14, 22, 23, 24, 31, 31, 37, 38, 46, 46, 19], ["p464646", 42], [19, 60],
+   [19], [36], [36],
+   ["和了", [0, 0, 1300, -1300], [2, 3, 2, "30符1飜1000点", "役牌 發(1飜)"]]],
+  [[1, 0, 0], [25000, 25000, 25000, 25000], [29], [],
+   [19], [], [], [19], [], [], [19], [], [], [19], [], [],
+   ["流局", [0, 0, 0, 0]]]
+]}"#;
+        let res = parse_json(log).unwrap();
+        assert_eq!(res.len(), 2);
+
+        let tsumo = &res[0];
+        assert_eq!(
+            tsumo.tehai,
+            Tehai::from_str("123m45$6p78s111j77j !9s").unwrap()
+        );
+        assert_eq!(tsumo.env.jikaze, Hai::from_str("1j").unwrap());
+        assert_eq!(tsumo.env.richi, Some(RichiType::Richi));
+        assert_eq!(tsumo.env.hoju, None);
+        assert_eq!(tsumo.env.uradora, [Hai::from_str("9p").unwrap()]);
+        assert_eq!((tsumo.fu, tsumo.point), (None, 18000));
+        assert!(tsumo.is_consistent());
+
+        let ron = &res[1];
+        assert_eq!(
+            ron.tehai,
+            Tehai::from_str("234m234p1178s <666j ?6s").unwrap()
+        );
+        assert_eq!(ron.env.bakaze, Hai::from_str("2j").unwrap());
+        assert_eq!(ron.env.jikaze, Hai::from_str("2j").unwrap());
+        assert_eq!(ron.env.hoju, Some(Tacha::Shimocha));
+        assert_eq!(ron.env.honba, 1);
+        assert_eq!((ron.fu, ron.point), (Some(30), 1000));
+        assert!(ron.is_consistent());
+
+        assert!(parse_json("{}").is_err());
+        assert!(parse_json("[[[0, 0, 0]]]").is_err());
+    }
+}
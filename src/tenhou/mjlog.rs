@@ -0,0 +1,444 @@
+//! mjlog 形式 (XML) の牌譜
+
+use super::{Error, ErrorKind as E, TenhouAgari};
+use crate::{
+    agari_hai::AgariHai,
+    agari_type::AgariType,
+    furo::{Furo, FuroKind},
+    hai::Hai,
+    hai_category::HaiCategory,
+    nukidora::Nukidora,
+    tacha::Tacha,
+};
+use std::{convert::TryFrom, str::FromStr};
+
+/// 卓の種別 (`<GO type>`) の赤ドラなしのビット
+const GO_NO_AKA: u32 = 0x02;
+/// 卓の種別 (`<GO type>`) の三人麻雀のビット
+const GO_SANMA: u32 = 0x10;
+
+/// mjlog 形式の牌譜からすべてのあがりを読み込む
+///
+/// あがりの局の情報は直前の `<INIT>` 要素から、赤ドラの有無と三人麻雀かどうかは `<GO>` 要素から求める。
+pub fn parse_mjlog(s: &str) -> Result<Vec<TenhouAgari>, Error> {
+    let mut go_type = 0;
+    let mut init = None;
+    let mut res = vec![];
+    for tag in parse_tags(s)? {
+        match tag.name {
+            "GO" => go_type = tag.num("type")?,
+            "INIT" => {
+                let seed = tag.nums("seed")?;
+                let kyoku = *seed.first().ok_or_else(|| tag.invalid("seed"))?;
+                init = Some((kyoku, tag.num::<u8>("oya")?));
+            }
+            "AGARI" => {
+                let (kyoku, oya) = init
+                    .ok_or_else(|| E::invalid_format("`<INIT>` より前に `<AGARI>` があります"))?;
+                res.push(parse_agari(&tag, go_type, kyoku, oya)?);
+            }
+            _ => {}
+        }
+    }
+    Ok(res)
+}
+
+fn parse_agari(tag: &Tag<'_>, go_type: u32, kyoku: u32, oya: u8) -> Result<TenhouAgari, Error> {
+    let aka = go_type & GO_NO_AKA == 0;
+    let sanma = go_type & GO_SANMA != 0;
+    let hai = |id| decode_hai(id, aka);
+
+    let who = tag.num("who")?;
+    let from_who = tag.num("fromWho")?;
+    let n = if sanma { 3 } else { 4 };
+    if who >= n || from_who >= n {
+        return Err(E::invalid_format(format!("不正な席です: {}, {}", who, from_who)).into());
+    }
+    if oya >= n {
+        return Err(E::invalid_format(format!("不正な親の席です: {}", oya)).into());
+    }
+    let mut env = super::new_env(sanma, kyoku, oya, who);
+    if let [honba, kyotaku] = tag.nums("ba")?[..] {
+        env.honba = honba;
+        env.kyotaku = kyotaku;
+    } else {
+        return Err(tag.invalid("ba"));
+    }
+    env.dora = tag
+        .nums("doraHai")?
+        .into_iter()
+        .map(hai)
+        .collect::<Result<_, _>>()?;
+    env.uradora = tag
+        .opt_nums("doraHaiUra")?
+        .into_iter()
+        .map(hai)
+        .collect::<Result<_, _>>()?;
+    env.hoju = super::hoju_tacha(who, from_who, sanma);
+    let yaku = tag.opt_nums("yaku")?;
+    let yakuman = tag.opt_nums("yakuman")?;
+    for id in yaku.iter().step_by(2).chain(&yakuman) {
+        if let Some(name) = yaku_name(*id) {
+            super::set_flag(&mut env, name);
+        }
+    }
+
+    let (fu, point) = match tag.nums("ten")?[..] {
+        [fu, point, ..] => (Some(fu).filter(|fu| *fu > 0), point),
+        _ => return Err(tag.invalid("ten")),
+    };
+
+    let mut furo = vec![];
+    let mut nukidora = vec![];
+    for m in tag.opt_nums("m")? {
+        match decode_meld(m, aka)? {
+            Meld::Furo(f) => furo.push(f),
+            Meld::Nukidora(n) => nukidora.push(n),
+        }
+    }
+    let hand = tag
+        .nums("hai")?
+        .into_iter()
+        .map(hai)
+        .collect::<Result<_, _>>()?;
+    let type_ = if who == from_who {
+        AgariType::Tsumo
+    } else {
+        AgariType::Ron
+    };
+    let agari_hai = AgariHai::new(type_, hai(tag.num("machi")?)?);
+    let tehai = super::build_tehai(hand, furo, nukidora, agari_hai)?;
+
+    Ok(TenhouAgari {
+        who,
+        from_who,
+        tehai,
+        env,
+        fu,
+        point,
+    })
+}
+
+/// 状況に関係する役の番号の名前
+fn yaku_name(id: u32) -> Option<&'static str> {
+    let name = match id {
+        1 => "立直",
+        2 => "一発",
+        3 => "槍槓",
+        4 => "嶺上開花",
+        5 => "海底摸月",
+        6 => "河底撈魚",
+        21 => "両立直",
+        37 => "天和",
+        38 => "地和",
+        _ => return None,
+    };
+    Some(name)
+}
+
+/// 牌の番号 (0〜135) を牌に変換する
+///
+/// 牌の種類ごとに4つずつ番号が振られており、赤ドラありの場合は各五の最初の番号 (16, 52, 88) が赤ドラ。
+fn decode_hai(id: u32, aka: bool) -> Result<Hai, Error> {
+    let kind = id / 4;
+    let (category, number) = match kind {
+        0..=8 => (HaiCategory::Manzu, kind + 1),
+        9..=17 => (HaiCategory::Pinzu, kind - 8),
+        18..=26 => (HaiCategory::Souzu, kind - 17),
+        27..=33 => (HaiCategory::Jihai, kind - 26),
+        _ => return Err(E::InvalidHai(id).into()),
+    };
+    let akadora = aka && matches!(id, 16 | 52 | 88);
+    Ok(Hai::try_new(category, number as u8, akadora).unwrap())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Meld {
+    Furo(Furo),
+    Nukidora(Nukidora),
+}
+
+/// 副露のビット列 (`m` 属性) を副露または抜きドラに変換する
+///
+/// 下位2ビットは牌の取得元 (0: 自家, 1: 下家, 2: 対面, 3: 上家)。
+fn decode_meld(m: u32, aka: bool) -> Result<Meld, Error> {
+    let hai = |id| decode_hai(id, aka);
+    let invalid = || Error::from(E::InvalidMeld(m.to_string()));
+    let tacha = match m & 3 {
+        0 => None,
+        1 => Some(Tacha::Shimocha),
+        2 => Some(Tacha::Toimen),
+        _ => Some(Tacha::Kamicha),
+    };
+
+    // 鳴いた牌を除いた牌
+    let others = |ids: &[u32], called: u32| -> Result<Vec<Hai>, Error> {
+        ids.iter()
+            .enumerate()
+            .filter(|(i, _)| *i as u32 != called)
+            .map(|(_, id)| hai(*id))
+            .collect()
+    };
+
+    if m & 0x04 != 0 {
+        // チー
+        let pattern = m >> 10;
+        let called = pattern % 3;
+        let base = pattern / 3;
+        if base >= 21 {
+            return Err(invalid());
+        }
+        let base = base / 7 * 9 + base % 7;
+        let ids = (0..3)
+            .map(|i| 4 * (base + i) + ((m >> (3 + 2 * i)) & 3))
+            .collect::<Vec<_>>();
+        let from_tehai = others(&ids, called)?;
+        let kind = FuroKind::Chi {
+            from_tehai: [from_tehai[0], from_tehai[1]],
+            from_kamicha: hai(ids[called as usize])?,
+        };
+        return Ok(Meld::Furo(kind.into()));
+    }
+
+    if m & 0x18 != 0 {
+        // ポン・加槓 (加槓で追加した牌の番号を除いた3枚)
+        let pattern = m >> 9;
+        let called = pattern % 3;
+        let base = 4 * (pattern / 3);
+        let added = (m >> 5) & 3;
+        let ids = (0..4)
+            .filter(|i| *i != added)
+            .map(|i| base + i)
+            .collect::<Vec<_>>();
+        let from_tehai = others(&ids, called)?;
+        let from_tehai = [from_tehai[0], from_tehai[1]];
+        let from_tacha = hai(ids[called as usize])?;
+        let tacha = tacha.ok_or_else(invalid)?;
+        let kind = if m & 0x08 != 0 {
+            FuroKind::Pon {
+                from_tehai,
+                from_tacha,
+                tacha,
+            }
+        } else {
+            FuroKind::Kakan {
+                from_tehai,
+                from_tacha,
+                tacha,
+                added: hai(base + added)?,
+            }
+        };
+        return Ok(Meld::Furo(kind.into()));
+    }
+
+    if m & 0x20 != 0 {
+        // 抜きドラ
+        let nukidora = Nukidora::try_from(hai(m >> 8)?).map_err(|_| invalid())?;
+        return Ok(Meld::Nukidora(nukidora));
+    }
+
+    // 大明槓・暗槓
+    let pattern = m >> 8;
+    let called = pattern % 4;
+    let base = 4 * (pattern / 4);
+    let ids = (0..4).map(|i| base + i).collect::<Vec<_>>();
+    let kind = match tacha {
+        None => FuroKind::Ankan {
+            from_tehai: [hai(ids[0])?, hai(ids[1])?, hai(ids[2])?, hai(ids[3])?],
+        },
+        Some(tacha) => {
+            let from_tehai = others(&ids, called)?;
+            FuroKind::Daiminkan {
+                from_tehai: [from_tehai[0], from_tehai[1], from_tehai[2]],
+                from_tacha: hai(ids[called as usize])?,
+                tacha,
+            }
+        }
+    };
+    Ok(Meld::Furo(kind.into()))
+}
+
+/// XML の要素 (開始タグまたは空要素タグ)
+#[derive(Debug)]
+struct Tag<'a> {
+    name: &'a str,
+    attrs: Vec<(&'a str, &'a str)>,
+}
+
+impl<'a> Tag<'a> {
+    fn attr(&self, name: &'static str) -> Result<&'a str, Error> {
+        self.attrs
+            .iter()
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| *value)
+            .ok_or_else(|| E::MissingAttr(self.name.into(), name).into())
+    }
+
+    fn invalid(&self, name: &'static str) -> Error {
+        let value = self.attr(name).unwrap_or_default();
+        E::InvalidAttr(name, value.into()).into()
+    }
+
+    fn num<T: FromStr>(&self, name: &'static str) -> Result<T, Error> {
+        self.attr(name)?.parse().map_err(|_| self.invalid(name))
+    }
+
+    /// カンマ区切りの数値の属性
+    fn nums(&self, name: &'static str) -> Result<Vec<u32>, Error> {
+        let value = self.attr(name)?;
+        if value.is_empty() {
+            return Ok(vec![]);
+        }
+        value
+            .split(',')
+            .map(|s| s.trim().parse().map_err(|_| self.invalid(name)))
+            .collect()
+    }
+
+    /// 省略可能なカンマ区切りの数値の属性
+    fn opt_nums(&self, name: &'static str) -> Result<Vec<u32>, Error> {
+        if self.attrs.iter().any(|(key, _)| *key == name) {
+            self.nums(name)
+        } else {
+            Ok(vec![])
+        }
+    }
+}
+
+/// 牌譜に含まれる要素を順に取り出す
+///
+/// 牌譜の読み込みに必要な範囲だけを扱う簡易的なパーサで、終了タグ・宣言・コメントは読み飛ばす。
+fn parse_tags(s: &str) -> Result<Vec<Tag<'_>>, Error> {
+    let unclosed = || E::invalid_format("タグが閉じられていません");
+    let mut tags = vec![];
+    let mut rest = s;
+    while let Some(start) = rest.find('<') {
+        let len = rest[start..].find('>').ok_or_else(unclosed)?;
+        let body = &rest[start + 1..start + len];
+        rest = &rest[start + len + 1..];
+        if body.starts_with(['/', '?', '!']) {
+            continue;
+        }
+
+        let body = body.strip_suffix('/').unwrap_or(body).trim();
+        let name_len = body.find(char::is_whitespace).unwrap_or(body.len());
+        let (name, mut attrs_str) = body.split_at(name_len);
+        let mut attrs = vec![];
+        loop {
+            attrs_str = attrs_str.trim_start();
+            if attrs_str.is_empty() {
+                break;
+            }
+            let eq = attrs_str.find('=').ok_or_else(unclosed)?;
+            let key = attrs_str[..eq].trim();
+            let value_str = attrs_str[eq + 1..]
+                .trim_start()
+                .strip_prefix('"')
+                .ok_or_else(unclosed)?;
+            let value_len = value_str.find('"').ok_or_else(unclosed)?;
+            attrs.push((key, &value_str[..value_len]));
+            attrs_str = &value_str[value_len + 1..];
+        }
+        tags.push(Tag { name, attrs });
+    }
+    Ok(tags)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tehai::Tehai;
+
+    fn hai(s: &str) -> Hai {
+        Hai::from_str(s).unwrap()
+    }
+
+    #[test]
+    fn meld() {
+        let furo = |m| match decode_meld(m, true).unwrap() {
+            Meld::Furo(furo) => *furo.kind(),
+            meld => panic!("{:?}", meld),
+        };
+        assert_eq!(
+            furo(7239),
+            FuroKind::Chi {
+                from_tehai: [hai("3m"), hai("5$m")],
+                from_kamicha: hai("4m"),
+            }
+        );
+        assert_eq!(
+            furo(51722),
+            FuroKind::Pon {
+                from_tehai: [hai("7j"), hai("7j")],
+                from_tacha: hai("7j"),
+                tacha: Tacha::Toimen,
+            }
+        );
+        assert_eq!(
+            furo(19985),
+            FuroKind::Kakan {
+                from_tehai: [hai("5p"), hai("5p")],
+                from_tacha: hai("5p"),
+                tacha: Tacha::Shimocha,
+                added: hai("5$p"),
+            }
+        );
+        assert_eq!(
+            furo(18691),
+            FuroKind::Daiminkan {
+                from_tehai: [hai("1s"), hai("1s"), hai("1s")],
+                from_tacha: hai("1s"),
+                tacha: Tacha::Kamicha,
+            }
+        );
+        assert_eq!(
+            furo(27648),
+            FuroKind::Ankan {
+                from_tehai: [hai("1j"), hai("1j"), hai("1j"), hai("1j")],
+            }
+        );
+        assert_eq!(
+            decode_meld(30752, true).unwrap(),
+            Meld::Nukidora(Nukidora::from_str("*4j").unwrap())
+        );
+        assert!(decode_meld(30752 - 256, true).is_err());
+    }
+
+    #[test]
+    fn agari() {
+        let log = r#"<mjloggm ver="2.3"><SHUFFLE seed="" ref=""/>
+<GO type="169" lobby="0"/><UN n0="" n1="" n2="" n3=""/>
+<INIT seed="5,1,1,2,4,80" ten="250,250,240,250" oya="1" hai0="" hai1="" hai2="" hai3=""/>
+<AGARI ba="1,1" hai="5,9,13,49,53,57,72,73,97,101,105" m="51722" machi="105" ten="30,1000,0"
+ yaku="20,1" doraHai="80" who="2" fromWho="1" sc="250,-13,240,23,250,0,250,0"/>
+</mjloggm>"#;
+        let res = parse_mjlog(log).unwrap();
+        assert_eq!(res.len(), 1);
+        let agari = &res[0];
+        assert_eq!(
+            agari.tehai,
+            Tehai::from_str("234m456p1178s ^777j ?9s").unwrap()
+        );
+        assert_eq!(agari.env.bakaze, hai("2j"));
+        assert_eq!(agari.env.jikaze, hai("2j"));
+        assert_eq!(agari.env.hoju, Some(Tacha::Kamicha));
+        assert_eq!(agari.env.dora, [hai("3s")]);
+        assert_eq!((agari.env.honba, agari.env.kyotaku), (1, 1));
+        assert_eq!((agari.fu, agari.point), (Some(30), 1000));
+        assert!(agari.is_consistent());
+
+        // 不正な席
+        for (seats, oya) in [
+            (r#"who="5" fromWho="0""#, "1"),
+            (r#"who="255" fromWho="255""#, "1"),
+            (r#"who="2" fromWho="1""#, "4"),
+        ] {
+            let log = log
+                .replace(r#"who="2" fromWho="1""#, seats)
+                .replace(r#"oya="1""#, &format!(r#"oya="{}""#, oya));
+            assert!(parse_mjlog(&log).is_err());
+        }
+        assert!(parse_mjlog(r#"<AGARI who="0"/>"#).is_err());
+        assert!(parse_mjlog("<INIT seed=\"0\" oya=\"0\"").is_err());
+    }
+}
@@ -0,0 +1,154 @@
+//! 天鳳の牌譜の読み込み (`tenhou` feature)
+//!
+//! mjlog 形式 (XML) の `<AGARI>` 要素、または JSON 形式の牌譜の `log` 配列から、
+//! あがった手牌と状況を復元する。復元した手牌の点数を再計算して、牌譜に記録された点数と照合できる。
+
+use crate::{
+    agari_hai::AgariHai,
+    env::{Env, RichiType},
+    furo::Furo,
+    hai::Hai,
+    hai_category::HaiCategory,
+    jun_tehai::JunTehai,
+    nukidora::Nukidora,
+    rules::{Rules, SanmaTsumo},
+    tacha::Tacha,
+    tehai::Tehai,
+    yaku::Yaku,
+};
+use num_bigint::BigUint;
+use thiserror::Error;
+
+mod json;
+mod mjlog;
+
+pub use self::{json::parse_json, mjlog::parse_mjlog};
+
+/// 牌譜に記録されたあがり
+#[derive(Debug, Clone)]
+pub struct TenhouAgari {
+    /// あがった席 (起家から順に 0〜3)
+    pub who: u8,
+    /// 放銃した席 (ツモあがりの場合は `who` と同じ)
+    pub from_who: u8,
+    pub tehai: Tehai,
+    pub env: Env,
+    /// 牌譜に記録された符 (記録されていない場合は `None`)
+    pub fu: Option<u32>,
+    /// 牌譜に記録された点数 (本場・供託を含まない)
+    pub point: u32,
+}
+
+impl TenhouAgari {
+    /// 点数を再計算する (点数の最も高いあがり形の役)
+    pub fn rescore(&self) -> Option<Yaku> {
+        self.tehai
+            .to_agari_combinations_with_env(&self.env)
+            .iter()
+            .map(|agari| Yaku::new(agari, &self.env))
+            .max()
+    }
+
+    /// 再計算した点数が牌譜に記録された点数と一致するか
+    pub fn is_consistent(&self) -> bool {
+        self.rescore().map(|yaku| yaku.point().clone()) == Some(BigUint::from(self.point))
+    }
+}
+
+#[derive(Debug, Error)]
+#[error(transparent)]
+pub struct Error(#[from] ErrorKind);
+
+#[derive(Debug, Error)]
+enum ErrorKind {
+    #[error("JSON のパースエラー: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("牌譜の形式が不正です: {0}")]
+    InvalidFormat(String),
+    #[error("`<{0}>` に属性 `{1}` がありません")]
+    MissingAttr(String, &'static str),
+    #[error("属性 `{0}` の値が不正です: `{1}`")]
+    InvalidAttr(&'static str, String),
+    #[error("不正な牌の番号です: `{0}`")]
+    InvalidHai(u32),
+    #[error("不正な副露です: `{0}`")]
+    InvalidMeld(String),
+    #[error("手牌に `{0}` がありません")]
+    HaiNotFound(Hai),
+    #[error("手牌の枚数が不正です: {0}枚")]
+    InvalidHaiCount(usize),
+}
+
+impl ErrorKind {
+    fn invalid_format(msg: impl Into<String>) -> Self {
+        Self::InvalidFormat(msg.into())
+    }
+}
+
+/// 局の情報からあがった席の状況を作る
+///
+/// `kyoku` は東1局を 0 とする局の番号、`oya` は親の席。
+fn new_env(sanma: bool, kyoku: u32, oya: u8, who: u8) -> Env {
+    let n = if sanma { 3 } else { 4 };
+    let kaze = |number| Hai::try_new(HaiCategory::Jihai, number, false).unwrap();
+    let mut env = Env::new(
+        kaze((kyoku / 4 % 4) as u8 + 1),
+        kaze((who + n - oya % n) % n + 1),
+    );
+    env.rules = Rules::tenhou();
+    if sanma {
+        // 三人麻雀の赤ドラは筒子と索子の1枚ずつ
+        env.rules.sanma = Some(SanmaTsumo::Tsumozon);
+        env.rules.akadora = [0, 1, 1];
+    }
+    env
+}
+
+/// 放銃した席から見た他家を求める (ツモあがりの場合は `None`)
+fn hoju_tacha(who: u8, from_who: u8, sanma: bool) -> Option<Tacha> {
+    let n = if sanma { 3 } else { 4 };
+    match (from_who + n - who) % n {
+        0 => None,
+        1 => Some(Tacha::Shimocha),
+        offset if offset == n - 1 => Some(Tacha::Kamicha),
+        _ => Some(Tacha::Toimen),
+    }
+}
+
+/// 役の名前から立直・一発などの状況を設定する
+fn set_flag(env: &mut Env, name: &str) {
+    match name {
+        "立直" => env.richi = Some(RichiType::Richi),
+        "両立直" | "ダブル立直" => env.richi = Some(RichiType::Daburi),
+        "一発" => env.ippatsu = true,
+        "嶺上開花" | "槍槓" => env.rinshan = true,
+        "海底摸月" | "河底撈魚" => env.haitei = true,
+        "天和" | "地和" => env.tenho = true,
+        _ => {}
+    }
+}
+
+/// 手牌から牌を1枚取り除く
+fn remove_hai(hand: &mut Vec<Hai>, hai: Hai) -> Result<(), Error> {
+    let idx = hand
+        .iter()
+        .position(|h| *h == hai)
+        .ok_or(ErrorKind::HaiNotFound(hai))?;
+    hand.remove(idx);
+    Ok(())
+}
+
+/// あがり牌を含む純手牌と副露・抜きドラから手牌を組み立てる
+fn build_tehai(
+    mut hand: Vec<Hai>,
+    furo: Vec<Furo>,
+    nukidora: Vec<Nukidora>,
+    agari_hai: AgariHai,
+) -> Result<Tehai, Error> {
+    remove_hai(&mut hand, agari_hai.hai())?;
+    if hand.len() + furo.len() * 3 != 13 {
+        return Err(ErrorKind::InvalidHaiCount(hand.len() + furo.len() * 3 + 1).into());
+    }
+    hand.sort();
+    Ok(Tehai::new(JunTehai::new(hand), furo, agari_hai).with_nukidora(nukidora))
+}
@@ -0,0 +1,184 @@
+use crate::{
+    agari::Agari, agari_hai::AgariHai, agari_type::AgariType, furo::Furo, hai::Hai,
+    jun_tehai::JunTehai, machi::Machi, rules::Rules, tehai::Tehai,
+};
+use std::fmt;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+/// 待ち牌
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct MachiHai {
+    hai: Hai,
+    /// 待ちの形 (重複なし)
+    machi: Vec<Machi>,
+    /// 見えていない牌の枚数
+    nokori: usize,
+}
+
+impl fmt::Display for MachiHai {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (", self.hai)?;
+        for (idx, machi) in self.machi.iter().enumerate() {
+            if idx > 0 {
+                write!(f, "/")?;
+            }
+            write!(f, "{}", machi)?;
+        }
+        write!(f, ", 残り{}枚)", self.nokori)
+    }
+}
+
+impl MachiHai {
+    pub fn hai(&self) -> Hai {
+        self.hai
+    }
+
+    pub fn nokori(&self) -> usize {
+        self.nokori
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+impl MachiHai {
+    #[wasm_bindgen(getter = hai)]
+    pub fn hai_js(&self) -> Hai {
+        self.hai
+    }
+
+    #[wasm_bindgen(getter = machi)]
+    pub fn machi_js(&self) -> Box<[JsValue]> {
+        self.machi
+            .iter()
+            .map(|machi| JsValue::from(machi.to_str()))
+            .collect()
+    }
+
+    #[wasm_bindgen(getter = nokori)]
+    pub fn nokori_js(&self) -> usize {
+        self.nokori
+    }
+
+    #[wasm_bindgen(js_name = "toString")]
+    pub fn to_string_js(&self) -> String {
+        self.to_string()
+    }
+}
+
+/// 13枚の手牌の待ち牌を列挙する
+///
+/// 手牌と副露で4枚すべてを使っている牌は、あがることができないため待ち牌に含めない。
+/// 三人麻雀では 2m〜8m を待ち牌に含めない。
+pub(crate) fn machi_hai(jun_tehai: &JunTehai, furo: &[Furo], rules: &Rules) -> Vec<MachiHai> {
+    assert_eq!(jun_tehai.as_slice().len() + furo.len() * 3, 13);
+
+    let visible = jun_tehai
+        .iter()
+        .chain(furo.iter().flat_map(|furo| furo.iter()))
+        .collect::<Vec<_>>();
+
+    Hai::all_kinds()
+        .filter(|hai| rules.sanma.is_none() || !hai.is_unused_in_sanma())
+        .filter_map(|hai| {
+            let count = visible.iter().filter(|h| h.is_same(&hai)).count();
+            if count >= 4 {
+                return None;
+            }
+            let agari_hai = AgariHai::new(AgariType::Ron, hai);
+            let tehai = Tehai::new(jun_tehai.clone(), furo.to_vec(), agari_hai);
+            let mut machi = tehai
+                .to_agari_combinations()
+                .iter()
+                .map(Agari::machi)
+                .collect::<Vec<_>>();
+            machi.sort();
+            machi.dedup();
+            (!machi.is_empty()).then(|| MachiHai {
+                hai,
+                machi,
+                nokori: 4 - count,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{mochihai::Mochihai, rules::SanmaTsumo};
+    use std::str::FromStr;
+
+    #[test]
+    fn machi_hai() {
+        fn machi_with_rules(s: &str, rules: &Rules) -> String {
+            Mochihai::from_str(s)
+                .unwrap()
+                .machi_hai(rules)
+                .unwrap()
+                .iter()
+                .map(|machi| machi.to_string())
+                .collect::<Vec<_>>()
+                .join(" ")
+        }
+        let machi = |s| machi_with_rules(s, &Rules::default());
+
+        // 両面待ち
+        assert_eq!(
+            machi("23m456p567s789s11j"),
+            "1m (両面, 残り4枚) 4m (両面, 残り4枚)",
+        );
+        // 双碰待ち
+        assert_eq!(
+            machi("123m456p789s1122j"),
+            "1j (双碰, 残り2枚) 2j (双碰, 残り2枚)",
+        );
+        // 嵌張待ちと単騎待ちの複合
+        assert_eq!(machi("4556m <213p <546p <879p"), "5m (嵌張/単騎, 残り2枚)");
+        // 延べ単
+        assert_eq!(
+            machi("3456m <213p <546p <879p"),
+            "3m (単騎, 残り3枚) 6m (単騎, 残り3枚)",
+        );
+        // 七対子
+        assert_eq!(machi("1122m3344p5566s7j"), "7j (単騎, 残り3枚)");
+        // 九蓮宝燈
+        assert_eq!(
+            machi("1112345678999m"),
+            [
+                "1m (両面/双碰, 残り1枚)",
+                "2m (単騎, 残り3枚)",
+                "3m (両面/辺張, 残り3枚)",
+                "4m (両面, 残り3枚)",
+                "5m (単騎, 残り3枚)",
+                "6m (両面, 残り3枚)",
+                "7m (両面/辺張, 残り3枚)",
+                "8m (単騎, 残り3枚)",
+                "9m (両面/双碰, 残り1枚)",
+            ]
+            .join(" "),
+        );
+        // 国士無双十三面待ち
+        let kokushi = Mochihai::from_str("19m19p19s1234567j")
+            .unwrap()
+            .machi_hai(&Rules::default())
+            .unwrap();
+        assert_eq!(kokushi.len(), 13);
+        assert!(kokushi.iter().all(|machi| machi.machi == [Machi::Tanki]));
+        // 4枚使いの牌は待ち牌にならない
+        assert_eq!(machi("1111m <213p <546p <879p"), "");
+        // 聴牌していない
+        assert_eq!(machi("1469m258p147s135j"), "");
+
+        // 三人麻雀では 2m〜8m を待ち牌にしない
+        let sanma = Rules {
+            sanma: Some(SanmaTsumo::Tsumozon),
+            ..Rules::default()
+        };
+        assert_eq!(
+            machi_with_rules("23m456p567s789s11j", &sanma),
+            "1m (両面, 残り4枚)",
+        );
+    }
+}
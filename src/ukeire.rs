@@ -0,0 +1,169 @@
+use crate::{env::Env, furo::Furo, hai::Hai, jun_tehai::JunTehai, shanten::Shanten};
+use std::fmt;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+/// 打牌候補ごとの受け入れ
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ukeire {
+    /// 打牌
+    dahai: Hai,
+    /// 打牌後の向聴数
+    shanten: i32,
+    /// 向聴数が進む牌と見えていない枚数
+    hai: Vec<(Hai, usize)>,
+}
+
+impl fmt::Display for Ukeire {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "打{}: ", self.dahai)?;
+        match self.shanten {
+            -1 => write!(f, "和了")?,
+            0 => write!(f, "聴牌")?,
+            n => write!(f, "{}向聴", n)?,
+        }
+        write!(f, ", 受け入れ ")?;
+        for (idx, (hai, _)) in self.hai.iter().enumerate() {
+            if idx > 0 {
+                write!(f, "/")?;
+            }
+            write!(f, "{}", hai)?;
+        }
+        write!(f, " ({}枚)", self.total())
+    }
+}
+
+impl Ukeire {
+    /// 受け入れ枚数の合計
+    pub fn total(&self) -> usize {
+        self.hai.iter().map(|(_, nokori)| nokori).sum()
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+impl Ukeire {
+    #[wasm_bindgen(getter = dahai)]
+    pub fn dahai_js(&self) -> Hai {
+        self.dahai
+    }
+
+    #[wasm_bindgen(getter = shanten)]
+    pub fn shanten_js(&self) -> i32 {
+        self.shanten
+    }
+
+    #[wasm_bindgen(getter = hai)]
+    pub fn hai_js(&self) -> Box<[JsValue]> {
+        self.hai
+            .iter()
+            .map(|(hai, _)| JsValue::from(*hai))
+            .collect()
+    }
+
+    #[wasm_bindgen(getter = total)]
+    pub fn total_js(&self) -> usize {
+        self.total()
+    }
+
+    #[wasm_bindgen(js_name = "toString")]
+    pub fn to_string_js(&self) -> String {
+        self.to_string()
+    }
+}
+
+/// 14枚の手牌の打牌候補を、打牌後の向聴数が小さい順、受け入れ枚数が多い順に列挙する
+///
+/// 受け入れ枚数からは、手牌・副露・ドラ表示牌で見えている牌を除く。
+pub(crate) fn ukeire(jun_tehai: &JunTehai, furo: &[Furo], env: &Env) -> Vec<Ukeire> {
+    assert_eq!(jun_tehai.as_slice().len() + furo.len() * 3, 14);
+
+    let visible = jun_tehai
+        .iter()
+        .chain(furo.iter().flat_map(|furo| furo.iter()))
+        .chain(env.dora.iter().copied())
+        .collect::<Vec<_>>();
+
+    let mut dahai_list = Vec::from(jun_tehai.as_slice());
+    dahai_list.sort();
+    dahai_list.dedup();
+
+    let mut res = dahai_list
+        .into_iter()
+        .map(|dahai| {
+            let mut rest = Vec::from(jun_tehai.as_slice());
+            let idx = rest.iter().position(|hai| *hai == dahai).unwrap();
+            rest.remove(idx);
+
+            let shanten = Shanten::new(&JunTehai::new(rest.clone()), furo).min();
+            let hai = Hai::all_kinds()
+                .filter(|hai| env.rules.sanma.is_none() || !hai.is_unused_in_sanma())
+                .filter_map(|hai| {
+                    let count = visible.iter().filter(|h| h.is_same(&hai)).count();
+                    if count >= 4 {
+                        return None;
+                    }
+                    let mut tsumo = rest.clone();
+                    tsumo.push(hai);
+                    let next = Shanten::new(&JunTehai::new(tsumo), furo).min();
+                    (next < shanten).then(|| (hai, 4 - count))
+                })
+                .collect();
+            Ukeire {
+                dahai,
+                shanten,
+                hai,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    res.sort_by_key(|ukeire| (ukeire.shanten, std::cmp::Reverse(ukeire.total())));
+    res
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mochihai::Mochihai;
+    use std::str::FromStr;
+
+    #[test]
+    fn ukeire() {
+        fn ukeire(s: &str, dora: &str) -> Vec<String> {
+            let mut env = Env::new(Hai::from_str("1j").unwrap(), Hai::from_str("2j").unwrap());
+            env.dora = vec![Hai::from_str(dora).unwrap()];
+            Mochihai::from_str(s)
+                .unwrap()
+                .ukeire(&env)
+                .unwrap()
+                .iter()
+                .map(|ukeire| ukeire.to_string())
+                .collect()
+        }
+
+        let res = ukeire("23m456p567s789s11j9m", "1p");
+        assert_eq!(res[0], "打9m: 聴牌, 受け入れ 1m/4m (8枚)");
+        assert_eq!(res.len(), 12);
+
+        // ドラ表示牌と手牌の牌は受け入れ枚数から除く
+        let res = ukeire("23m456p567s789s11j9m", "1m");
+        assert_eq!(res[0], "打9m: 聴牌, 受け入れ 1m/4m (7枚)");
+
+        // 向聴数の小さい順、受け入れ枚数の多い順
+        let res = ukeire("1245m456p789s11j5s9s", "1p");
+        assert_eq!(
+            res[..4],
+            [
+                "打5s: 1向聴, 受け入れ 3m/6m (8枚)",
+                "打9s: 1向聴, 受け入れ 3m/6m (8枚)",
+                "打1m: 2向聴, 受け入れ 1m/2m/3m/4m/5m/6m/7m/3s/4s/5s/6s/7s/8s/9s/1j (49枚)",
+                "打5m: 2向聴, 受け入れ 1m/2m/3m/4m/5m/6m/3s/4s/5s/6s/7s/8s/9s/1j (45枚)",
+            ],
+        );
+
+        // 副露あり
+        let res = ukeire("34568m <213p <546p <879p", "1p");
+        assert_eq!(res[0], "打8m: 聴牌, 受け入れ 3m/6m (6枚)");
+    }
+}
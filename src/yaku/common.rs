@@ -55,6 +55,11 @@ pub(super) fn kazehai_bits(agari: &Agari) -> u8 {
     bits
 }
 
+/// 十三不塔の形 (国士無双以外の、1組の対子と12枚の単独牌) か否か
+pub(super) fn is_shisanputa(agari: &Agari) -> bool {
+    agari.num_toitsu() == 1 && agari.num_single() == 12 && agari.num_yaochuhai() < agari.num_hai()
+}
+
 pub(super) fn num_peko(agari: &Agari) -> usize {
     let shuntsu_heads = agari
         .all_mentsu()
@@ -81,7 +86,7 @@ pub(super) mod test {
 
     pub(in super::super) fn yaku(s: &str, env: &Env) -> String {
         let tehai = Tehai::from_str(s).unwrap();
-        let comb = tehai.to_agari_combinations();
+        let comb = tehai.to_agari_combinations_with_env(env);
         comb.into_iter()
             .map(|agari| {
                 let s = super::super::judge(&agari, env)
@@ -97,4 +102,16 @@ pub(super) mod test {
             })
             .collect()
     }
+
+    /// 点数が最も高いあがりの名前と点数
+    pub(in super::super) fn point(s: &str, env: &Env) -> String {
+        let yaku = Tehai::from_str(s)
+            .unwrap()
+            .to_agari_combinations_with_env(env)
+            .iter()
+            .map(|agari| super::super::Yaku::new(agari, env))
+            .max()
+            .unwrap();
+        format!("{}{}", yaku.name(), yaku.point())
+    }
 }
@@ -1,43 +1,74 @@
-use crate::{agari::Agari, env::Env};
+use crate::{agari::Agari, env::Env, hai::Hai, hai_category::HaiCategory};
 
-pub(super) fn dora(agari: &Agari, env: &Env) -> Option<(&'static str, u32)> {
-    let count = agari
-        .tehai()
-        .all_hai()
-        .map(|hai| env.dora.iter().filter(|dora| hai.is_next_to(dora)).count())
-        .sum::<usize>();
-    (count > 0).then(|| ("ドラ", count as u32))
+/// 表示牌 `indicator` に対して `hai` がドラか否か
+///
+/// 三人麻雀では 2m〜8m を使用しないため、1m の次は 9m となる。
+fn is_dora(hai: &Hai, indicator: &Hai, env: &Env) -> bool {
+    if env.rules.sanma.is_some()
+        && indicator.category() == HaiCategory::Manzu
+        && indicator.number() == 1
+    {
+        return hai.category() == HaiCategory::Manzu && hai.number() == 9;
+    }
+    hai.is_next_to(indicator)
 }
 
-pub(super) fn uradora(agari: &Agari, env: &Env) -> Option<(&'static str, u32)> {
-    let _ = env.richi?;
-    let count = agari
-        .tehai()
+/// 表示牌 `indicators` に対するドラの枚数 (抜きドラの北を含む)
+fn count_dora(agari: &Agari, env: &Env, indicators: &[Hai]) -> u32 {
+    let tehai = agari.tehai();
+    tehai
         .all_hai()
+        .chain(tehai.nukidora().iter().map(|nukidora| nukidora.hai()))
         .map(|hai| {
-            env.uradora
+            indicators
                 .iter()
-                .filter(|dora| hai.is_next_to(dora))
-                .count()
+                .filter(|indicator| is_dora(&hai, indicator, env))
+                .count() as u32
+        })
+        .sum()
+}
+
+pub(super) fn dora(agari: &Agari, env: &Env) -> Option<(&'static str, u32)> {
+    let count = count_dora(agari, env, &env.dora);
+    (count > 0).then(|| ("ドラ", count))
+}
+
+pub(super) fn uradora(agari: &Agari, env: &Env) -> Option<(&'static str, u32)> {
+    let _ = env.richi?;
+    let count = count_dora(agari, env, &env.uradora);
+    (count > 0).then(|| ("裏ドラ", count))
+}
+
+pub(super) fn akadora(agari: &Agari, env: &Env) -> Option<(&'static str, u32)> {
+    // ルールの枚数を超える赤ドラは数えない
+    let count = [HaiCategory::Manzu, HaiCategory::Pinzu, HaiCategory::Souzu]
+        .iter()
+        .map(|category| {
+            let count = agari
+                .tehai()
+                .all_hai()
+                .filter(|hai| hai.akadora() && hai.category() == *category)
+                .count() as u32;
+            count.min(env.rules.akadora_limit(*category).into())
         })
-        .sum::<usize>();
-    (count > 0).then(|| ("裏ドラ", count as u32))
+        .sum::<u32>();
+    (count > 0).then(|| ("赤ドラ", count))
 }
 
-pub(super) fn akadora(agari: &Agari, _env: &Env) -> Option<(&'static str, u32)> {
-    let count = agari.tehai().all_hai().filter(|hai| hai.akadora()).count();
-    (count > 0).then(|| ("赤ドラ", count as u32))
+pub(super) fn nukidora(agari: &Agari, _env: &Env) -> Option<(&'static str, u32)> {
+    let count = agari.tehai().nukidora().len();
+    (count > 0).then(|| ("抜きドラ", count as u32))
 }
 
 #[cfg(test)]
 mod test {
     use super::{super::common::test::yaku, *};
-    use crate::{env::RichiType, hai::Hai};
+    use crate::{env::RichiType, rules::SanmaTsumo};
     use std::str::FromStr;
 
     #[test]
     fn dora() {
-        let mut env = Env::new_empty(Hai::from_str("1j").unwrap(), Hai::from_str("1j").unwrap());
+        let mut env = Env::new(Hai::from_str("1j").unwrap(), Hai::from_str("1j").unwrap());
 
         env.dora = vec![Hai::from_str("1m").unwrap(), Hai::from_str("5m").unwrap()];
         assert_eq!(yaku("1112345m345s123p ?6m", &env), "[平和:1,ドラ:2]");
@@ -68,7 +99,7 @@ mod test {
 
     #[test]
     fn uradora() {
-        let mut env = Env::new_empty(Hai::from_str("1j").unwrap(), Hai::from_str("1j").unwrap());
+        let mut env = Env::new(Hai::from_str("1j").unwrap(), Hai::from_str("1j").unwrap());
 
         env.uradora = vec![Hai::from_str("1m").unwrap(), Hai::from_str("5m").unwrap()];
         env.richi = Some(RichiType::Richi);
@@ -87,7 +118,50 @@ mod test {
 
     #[test]
     fn akadora() {
-        let env = Env::new_empty(Hai::from_str("1j").unwrap(), Hai::from_str("1j").unwrap());
+        let mut env = Env::new(Hai::from_str("1j").unwrap(), Hai::from_str("1j").unwrap());
         assert_eq!(yaku("1112345$m345$s123p ?6m", &env), "[平和:1,赤ドラ:2]");
+
+        // 赤ドラなし
+        env.rules.akadora = [0, 0, 0];
+        assert_eq!(yaku("1112345$m345$s123p ?6m", &env), "[平和:1]");
+
+        // 種類ごとの枚数を超える赤ドラは数えない
+        env.rules.akadora = [1, 1, 1];
+        let tehai = "5$5$5$m345$p345$s2399p !1p";
+        assert_eq!(yaku(tehai, &env), "[門前清自摸和:1,赤ドラ:3]");
+        env.rules.akadora = [0, 2, 1];
+        assert_eq!(yaku(tehai, &env), "[門前清自摸和:1,赤ドラ:2]");
+    }
+
+    #[test]
+    fn sanma_dora() {
+        let mut env = Env::new(Hai::from_str("1j").unwrap(), Hai::from_str("1j").unwrap());
+        env.rules.sanma = Some(SanmaTsumo::Tsumozon);
+
+        // 三人麻雀ではドラ表示牌が 1m の時は 9m がドラになる
+        env.dora = vec![Hai::from_str("1m").unwrap()];
+        assert_eq!(yaku("99m123p456p789s23s ?1s", &env), "[平和:1,ドラ:2]");
+
+        env.rules.sanma = None;
+        assert_eq!(yaku("99m123p456p789s23s ?1s", &env), "[平和:1]");
+    }
+
+    #[test]
+    fn nukidora() {
+        let mut env = Env::new(Hai::from_str("1j").unwrap(), Hai::from_str("1j").unwrap());
+        env.rules.sanma = Some(SanmaTsumo::Tsumozon);
+
+        // 北は1枚につき1飜
+        assert_eq!(
+            yaku("123p456p789p11s23s *4j *4j !4s", &env),
+            "[門前清自摸和:1,平和:1,一気通貫:2,抜きドラ:2]"
+        );
+
+        // ドラ表示牌が 3j の場合は抜いた北もドラになる
+        env.dora = vec![Hai::from_str("3j").unwrap()];
+        assert_eq!(
+            yaku("123p456p789p11s23s *4j *4j !4s", &env),
+            "[門前清自摸和:1,平和:1,一気通貫:2,ドラ:2,抜きドラ:2]"
+        );
     }
 }
@@ -27,12 +27,11 @@ pub(super) fn tsumo(agari: &Agari, env: &Env) -> Option<(&'static str, u32)> {
         .then(|| ("門前清自摸和", 1))
 }
 
-pub(super) fn tanyao(agari: &Agari, _env: &Env) -> Option<(&'static str, u32)> {
-    agari
-        .tehai()
-        .all_hai()
-        .all(|hai| !hai.is_yaochuhai())
-        .then(|| ("断么九", 1))
+pub(super) fn tanyao(agari: &Agari, env: &Env) -> Option<(&'static str, u32)> {
+    // 喰いタンなしの場合は門前のみ
+    ((env.rules.kuitan || agari.tehai().is_menzen())
+        && agari.tehai().all_hai().all(|hai| !hai.is_yaochuhai()))
+    .then(|| ("断么九", 1))
 }
 
 pub(super) fn pinfu(agari: &Agari, env: &Env) -> Option<(&'static str, u32)> {
@@ -43,7 +42,11 @@ pub(super) fn pinfu(agari: &Agari, env: &Env) -> Option<(&'static str, u32)> {
         .then(|| ("平和", 1))
 }
 
-pub(super) fn ipeko(agari: &Agari, _env: &Env) -> Option<(&'static str, u32)> {
+pub(super) fn ipeko(agari: &Agari, env: &Env) -> Option<(&'static str, u32)> {
+    // ローカル役の一色三順とは複合しない
+    if env.rules.local_yaku && super::local::isshoku_sanjun(agari, env).is_some() {
+        return None;
+    }
     (agari.tehai().is_menzen() && common::num_peko(agari) == 1).then(|| ("一盃口", 1))
 }
 
@@ -134,14 +137,14 @@ mod test {
 
     #[test]
     fn rich() {
-        let mut env = Env::new_empty(Hai::from_str("1j").unwrap(), Hai::from_str("1j").unwrap());
+        let mut env = Env::new(Hai::from_str("1j").unwrap(), Hai::from_str("1j").unwrap());
         env.richi = Some(RichiType::Richi);
         assert_eq!(yaku("344556m24678s66j ?3s", &env), "[立直:1]");
     }
 
     #[test]
     fn ippatsu() {
-        let mut env = Env::new_empty(Hai::from_str("1j").unwrap(), Hai::from_str("1j").unwrap());
+        let mut env = Env::new(Hai::from_str("1j").unwrap(), Hai::from_str("1j").unwrap());
         env.richi = Some(RichiType::Richi);
         env.ippatsu = true;
         assert_eq!(yaku("1112345m345s123p ?6m", &env), "[立直:1,一発:1,平和:1]");
@@ -149,7 +152,7 @@ mod test {
 
     #[test]
     fn tsumo() {
-        let env = Env::new_empty(Hai::from_str("1j").unwrap(), Hai::from_str("1j").unwrap());
+        let env = Env::new(Hai::from_str("1j").unwrap(), Hai::from_str("1j").unwrap());
         assert_eq!(yaku("345567m1113368s !7s", &env), "[門前清自摸和:1]");
         assert_eq!(
             yaku("345m345p3344588s !5s", &env),
@@ -157,11 +160,11 @@ mod test {
         );
 
         // 青天常時、天和/地和とは複合しない
-        let mut env = Env::new_empty(Hai::from_str("1j").unwrap(), Hai::from_str("1j").unwrap());
+        let mut env = Env::new(Hai::from_str("1j").unwrap(), Hai::from_str("1j").unwrap());
         env.tenho = true;
         env.aotenjo = true;
         assert_eq!(yaku("345567m1113368s !7s", &env), "[天和:!1]");
-        let mut env = Env::new_empty(Hai::from_str("1j").unwrap(), Hai::from_str("2j").unwrap());
+        let mut env = Env::new(Hai::from_str("1j").unwrap(), Hai::from_str("2j").unwrap());
         env.tenho = true;
         env.aotenjo = true;
         assert_eq!(yaku("345567m1113368s !7s", &env), "[地和:!1]");
@@ -169,7 +172,7 @@ mod test {
 
     #[test]
     fn tanyao() {
-        let env = Env::new_empty(Hai::from_str("1j").unwrap(), Hai::from_str("1j").unwrap());
+        let env = Env::new(Hai::from_str("1j").unwrap(), Hai::from_str("1j").unwrap());
         // 門前のケース
         assert_eq!(yaku("22555m678s23477p ?2m", &env), "[断么九:1]");
         // 片あがりのケース
@@ -189,11 +192,17 @@ mod test {
             yaku("2267888m ^333m <657m ?2m", &env),
             "[断么九:1,清一色:5]"
         );
+
+        // 喰いタンなしの場合、副露していると不成立
+        let mut env = Env::new(Hai::from_str("1j").unwrap(), Hai::from_str("1j").unwrap());
+        env.rules.kuitan = false;
+        assert_eq!(yaku("22555m678s23477p ?2m", &env), "[断么九:1]");
+        assert_eq!(yaku("22567m88s <657p <657s !2m", &env), "[三色同順:1]");
     }
 
     #[test]
     fn pinfu() {
-        let env = Env::new_empty(Hai::from_str("1j").unwrap(), Hai::from_str("2j").unwrap());
+        let env = Env::new(Hai::from_str("1j").unwrap(), Hai::from_str("2j").unwrap());
         // 平和になる配姿
         assert_eq!(yaku("345567m234p3378s ?6s", &env), "[断么九:1,平和:1]");
         assert_eq!(yaku("345567m234p3378s ?9s", &env), "[平和:1]");
@@ -228,7 +237,7 @@ mod test {
 
     #[test]
     fn ipeko() {
-        let env = Env::new_empty(Hai::from_str("1j").unwrap(), Hai::from_str("1j").unwrap());
+        let env = Env::new(Hai::from_str("1j").unwrap(), Hai::from_str("1j").unwrap());
         // 嵌張待ちのケース
         assert_eq!(yaku("33455m111p33789s ?4m", &env), "[一盃口:1]");
         // 高目で一盃口になるケース
@@ -265,7 +274,7 @@ mod test {
 
     #[test]
     fn yakuhai() {
-        let env = Env::new_empty(Hai::from_str("1j").unwrap(), Hai::from_str("1j").unwrap());
+        let env = Env::new(Hai::from_str("1j").unwrap(), Hai::from_str("1j").unwrap());
         // 門前のケース
         assert_eq!(
             yaku("345567m3378s111j ?6s", &env),
@@ -281,7 +290,7 @@ mod test {
         );
 
         // 青天井の場合大三元とは複合しない
-        let mut env = Env::new_empty(Hai::from_str("1j").unwrap(), Hai::from_str("1j").unwrap());
+        let mut env = Env::new(Hai::from_str("1j").unwrap(), Hai::from_str("1j").unwrap());
         env.aotenjo = true;
         assert_eq!(yaku("33s78p777j ^666j ^555j ?6p", &env), "[大三元:!1]");
     }
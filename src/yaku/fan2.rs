@@ -143,7 +143,7 @@ mod test {
 
     #[test]
     fn sanshoku() {
-        let env = Env::new_empty(Hai::from_str("1j").unwrap(), Hai::from_str("1j").unwrap());
+        let env = Env::new(Hai::from_str("1j").unwrap(), Hai::from_str("1j").unwrap());
         // 出来合いだが三色のみの配姿
         assert_eq!(yaku("567m567p22567s99s ?9s", &env), "[三色同順:2]");
         // 出来合いのタンピン三色
@@ -168,7 +168,7 @@ mod test {
 
     #[test]
     fn ittsu() {
-        let env = Env::new_empty(Hai::from_str("1j").unwrap(), Hai::from_str("1j").unwrap());
+        let env = Env::new(Hai::from_str("1j").unwrap(), Hai::from_str("1j").unwrap());
         // 一気通貫が確定しているが待ちが狭いケース
         assert_eq!(yaku("12456789m33789s ?3m", &env), "[一気通貫:2]");
         // 待ちは広いが一気通貫が確定しないケース
@@ -204,7 +204,7 @@ mod test {
 
     #[test]
     fn chanta() {
-        let env = Env::new_empty(Hai::from_str("1j").unwrap(), Hai::from_str("1j").unwrap());
+        let env = Env::new(Hai::from_str("1j").unwrap(), Hai::from_str("1j").unwrap());
         assert_eq!(yaku("123999m789p33j12s ?3s", &env), "[混全帯么九:2]");
         assert_eq!(
             yaku("123m123789p23s22j ?1s", &env),
@@ -216,7 +216,7 @@ mod test {
 
     #[test]
     fn chitoi() {
-        let env = Env::new_empty(Hai::from_str("1j").unwrap(), Hai::from_str("1j").unwrap());
+        let env = Env::new(Hai::from_str("1j").unwrap(), Hai::from_str("1j").unwrap());
         // 七対子のみのケース
         assert_eq!(yaku("115599m2233p8s22j ?8s", &env), "[七対子:2]");
         // 他の役が複合しているケース
@@ -232,7 +232,7 @@ mod test {
 
     #[test]
     fn toitoi() {
-        let env = Env::new_empty(Hai::from_str("1j").unwrap(), Hai::from_str("1j").unwrap());
+        let env = Env::new(Hai::from_str("1j").unwrap(), Hai::from_str("1j").unwrap());
         // 副露したケース
         assert_eq!(yaku("55888m33j ^111p ^222s ?5m", &env), "[対対和:2]");
         assert_eq!(yaku("55888m33j ^111p ^222s ?3j", &env), "[対対和:2]");
@@ -253,7 +253,7 @@ mod test {
 
     #[test]
     fn sananko() {
-        let env = Env::new_empty(Hai::from_str("1j").unwrap(), Hai::from_str("1j").unwrap());
+        let env = Env::new(Hai::from_str("1j").unwrap(), Hai::from_str("1j").unwrap());
         // 確定三暗刻の単騎待ち
         assert_eq!(yaku("111333s555m3459p ?9p", &env), "[三暗刻:2]");
         // 一手変わりで四暗刻になる両面待ち
@@ -278,7 +278,7 @@ mod test {
 
     #[test]
     fn honro() {
-        let env = Env::new_empty(Hai::from_str("1j").unwrap(), Hai::from_str("1j").unwrap());
+        let env = Env::new(Hai::from_str("1j").unwrap(), Hai::from_str("1j").unwrap());
         // 副露したケース
         assert_eq!(
             yaku("11199p66j <777j ^111m ?9p", &env),
@@ -293,14 +293,14 @@ mod test {
         assert_eq!(yaku("66j1199p99s11m442j ?2j", &env), "[七対子:2,混老頭:2]",);
 
         // 青天常時、国士無双とは複合しない
-        let mut env = Env::new_empty(Hai::from_str("1j").unwrap(), Hai::from_str("1j").unwrap());
+        let mut env = Env::new(Hai::from_str("1j").unwrap(), Hai::from_str("1j").unwrap());
         env.aotenjo = true;
         assert_eq!(yaku("19m19s19p1123456j ?7j", &env), "[国士無双:!1]");
     }
 
     #[test]
     fn sandoko() {
-        let env = Env::new_empty(Hai::from_str("1j").unwrap(), Hai::from_str("1j").unwrap());
+        let env = Env::new(Hai::from_str("1j").unwrap(), Hai::from_str("1j").unwrap());
         // 対対和との複合/2副露したケース
         assert_eq!(
             yaku("222m7799s >222s >222p ?7s", &env),
@@ -315,7 +315,7 @@ mod test {
 
     #[test]
     fn sankantsu() {
-        let env = Env::new_empty(Hai::from_str("1j").unwrap(), Hai::from_str("1j").unwrap());
+        let env = Env::new(Hai::from_str("1j").unwrap(), Hai::from_str("1j").unwrap());
         // 対対和になっていないケース
         assert_eq!(
             yaku("11p45m 6666j ^888+8m ^2222p ?3m", &env),
@@ -330,7 +330,7 @@ mod test {
 
     #[test]
     fn shosan() {
-        let env = Env::new_empty(Hai::from_str("1j").unwrap(), Hai::from_str("1j").unwrap());
+        let env = Env::new(Hai::from_str("1j").unwrap(), Hai::from_str("1j").unwrap());
         // 高目大三元のケース
         assert_eq!(
             yaku("55577j567m22p >666j ?2p", &env),
@@ -345,7 +345,7 @@ mod test {
 
     #[test]
     fn daburi() {
-        let mut env = Env::new_empty(Hai::from_str("1j").unwrap(), Hai::from_str("1j").unwrap());
+        let mut env = Env::new(Hai::from_str("1j").unwrap(), Hai::from_str("1j").unwrap());
         env.richi = Some(RichiType::Daburi);
         assert_eq!(yaku("1112345m345s123p ?6m", &env), "[平和:1,ダブル立直:2]");
     }
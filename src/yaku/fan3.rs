@@ -33,7 +33,7 @@ mod test {
 
     #[test]
     fn honitsu() {
-        let env = Env::new_empty(Hai::from_str("1j").unwrap(), Hai::from_str("1j").unwrap());
+        let env = Env::new(Hai::from_str("1j").unwrap(), Hai::from_str("1j").unwrap());
         // 風牌の刻子があるケース
         assert_eq!(
             yaku("11456678m11166j ?6j", &env),
@@ -110,7 +110,7 @@ mod test {
 
     #[test]
     fn junchan() {
-        let env = Env::new_empty(Hai::from_str("1j").unwrap(), Hai::from_str("1j").unwrap());
+        let env = Env::new(Hai::from_str("1j").unwrap(), Hai::from_str("1j").unwrap());
         // 純全帯么九のみのケース
         assert_eq!(yaku("11m123s789p789s99m ?1m", &env), "[純全帯么九:3]");
         assert_eq!(yaku("11m123s789p789s99m ?9m", &env), "[純全帯么九:3]");
@@ -159,7 +159,7 @@ mod test {
 
     #[test]
     fn ryanpeko() {
-        let env = Env::new_empty(Hai::from_str("1j").unwrap(), Hai::from_str("1j").unwrap());
+        let env = Env::new(Hai::from_str("1j").unwrap(), Hai::from_str("1j").unwrap());
         // 平和との複合/高目で二盃口になるケース
         assert_eq!(yaku("334455m22334p99s ?1p", &env), "[平和:1,一盃口:1]");
         assert_eq!(
@@ -19,7 +19,7 @@ mod test {
 
     #[test]
     fn chinitsu() {
-        let env = Env::new_empty(Hai::from_str("1j").unwrap(), Hai::from_str("1j").unwrap());
+        let env = Env::new(Hai::from_str("1j").unwrap(), Hai::from_str("1j").unwrap());
         // 多面張のケース
         assert_eq!(yaku("1113456677778m ?2m", &env), "[清一色:6][清一色:6]");
         assert_eq!(yaku("1113456677778m ?5m", &env), "[清一色:6][清一色:6]");
@@ -0,0 +1,224 @@
+//! ローカル役
+//!
+//! `Rules::local_yaku` が有効な場合のみ判定する。
+//! 十三不塔は和了形ではないため、`Tehai::to_agari_combinations_with_env` が天和/地和の状況の場合のみあがり形に含める。
+
+use crate::{
+    agari::Agari,
+    env::{Env, RichiType},
+    hai::Hai,
+    hai_category::HaiCategory,
+    mentsu::MentsuKind,
+};
+
+/// 同じ種類の数牌の刻子/槓子のうち、最も長く連続する数
+fn max_renko(agari: &Agari) -> usize {
+    let mut heads = agari
+        .all_mentsu()
+        .filter(|mentsu| {
+            matches!(
+                mentsu.kind(),
+                MentsuKind::Kotsu(..) | MentsuKind::Kantsu(..)
+            )
+        })
+        .map(|mentsu| mentsu.head())
+        .filter(|head| head.category() != HaiCategory::Jihai)
+        .map(|head| (head.category(), head.number()))
+        .collect::<Vec<_>>();
+    heads.sort();
+
+    let mut max = 0;
+    let mut len = 0;
+    let mut prev = None;
+    for (category, number) in heads {
+        len = match prev {
+            Some((c, n)) if c == category && n + 1 == number => len + 1,
+            _ => 1,
+        };
+        max = usize::max(max, len);
+        prev = Some((category, number));
+    }
+    max
+}
+
+pub(super) fn sanrenko(agari: &Agari, _env: &Env) -> Option<(&'static str, u32)> {
+    (max_renko(agari) == 3).then(|| ("三連刻", 2))
+}
+
+pub(super) fn isshoku_sanjun(agari: &Agari, _env: &Env) -> Option<(&'static str, u32)> {
+    let heads = agari
+        .all_mentsu()
+        .filter(|mentsu| matches!(mentsu.kind(), MentsuKind::Shuntsu(..)))
+        .map(|mentsu| mentsu.head())
+        .collect::<Vec<_>>();
+    heads
+        .iter()
+        .any(|head| heads.iter().filter(|h| h.is_same(head)).count() == 3)
+        .then(|| ("一色三順", super::common::kuisagari(agari, 3)))
+}
+
+pub(super) fn uumenchii(agari: &Agari, _env: &Env) -> Option<(&'static str, u32)> {
+    let mut bits = 0;
+    for hai in agari.tehai().all_hai() {
+        bits |= match hai.category() {
+            HaiCategory::Manzu => 0b00001,
+            HaiCategory::Pinzu => 0b00010,
+            HaiCategory::Souzu => 0b00100,
+            HaiCategory::Jihai if hai.is_kazehai() => 0b01000,
+            HaiCategory::Jihai => 0b10000,
+        };
+    }
+    (bits == 0b11111).then(|| ("五門斉", 2))
+}
+
+pub(super) fn daisharin(agari: &Agari, _env: &Env) -> Option<(&'static str, u32)> {
+    let mut hai = agari.tehai().all_hai().collect::<Vec<_>>();
+    hai.sort();
+    (agari.tehai().is_menzen()
+        && hai.iter().enumerate().all(|(i, hai)| {
+            hai.category() == HaiCategory::Pinzu && usize::from(hai.number()) == i / 2 + 2
+        }))
+    .then(|| ("大車輪", 1))
+}
+
+pub(super) fn surenko(agari: &Agari, _env: &Env) -> Option<(&'static str, u32)> {
+    (max_renko(agari) == 4).then(|| ("四連刻", 1))
+}
+
+pub(super) fn shisanputa(agari: &Agari, env: &Env) -> Option<(&'static str, u32)> {
+    (env.tenho && super::common::is_shisanputa(agari)).then(|| ("十三不塔", 1))
+}
+
+pub(super) fn daichisei(agari: &Agari, _env: &Env) -> Option<(&'static str, u32)> {
+    // 字一色と複合してダブル役満となる
+    (agari.num_toitsu() == 7 && agari.num_jihai() == agari.num_hai()).then(|| ("大七星", 1))
+}
+
+pub(super) fn benikujaku(agari: &Agari, _env: &Env) -> Option<(&'static str, u32)> {
+    let is_beni = |hai: Hai| match (hai.category(), hai.number()) {
+        (HaiCategory::Souzu, n) => n == 1 || n == 5 || n == 7 || n == 9,
+        (HaiCategory::Jihai, n) => n == 7,
+        _ => false,
+    };
+    agari.tehai().all_hai().all(is_beni).then(|| ("紅孔雀", 1))
+}
+
+pub(super) fn ishi_no_ue_ni_mo_sannen(_agari: &Agari, env: &Env) -> Option<(&'static str, u32)> {
+    (env.richi == Some(RichiType::Daburi) && env.haitei).then(|| ("石の上にも三年", 1))
+}
+
+pub(super) fn hyakumangoku(agari: &Agari, _env: &Env) -> Option<(&'static str, u32)> {
+    let sum = agari
+        .tehai()
+        .all_hai()
+        .map(|hai| u32::from(hai.number()))
+        .sum::<u32>();
+    (agari.num_manzu() == agari.num_hai() && sum >= 100).then(|| ("百万石", 1))
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::common::test::yaku;
+    use crate::{
+        env::{Env, RichiType},
+        hai::Hai,
+    };
+    use std::str::FromStr;
+
+    fn local_env() -> Env {
+        let mut env = Env::new(Hai::from_str("1j").unwrap(), Hai::from_str("2j").unwrap());
+        env.rules.local_yaku = true;
+        env
+    }
+
+    #[test]
+    fn sanrenko() {
+        let mut env = local_env();
+        assert_eq!(yaku("444m5p789s <222m <333m ?5p", &env), "[三連刻:2]");
+
+        // ローカル役なし
+        env.rules.local_yaku = false;
+        assert_eq!(yaku("444m5p789s <222m <333m ?5p", &env), "[]");
+    }
+
+    #[test]
+    fn surenko() {
+        let env = local_env();
+        assert_eq!(yaku("5p <222m <333m <444m ^555m ?5p", &env), "[四連刻:!1]");
+    }
+
+    #[test]
+    fn isshoku_sanjun() {
+        let env = local_env();
+        // 一盃口とは複合しない
+        assert_eq!(
+            yaku("123123123m456p7s ?7s", &env),
+            "[一色三順:3][三暗刻:2,三連刻:2]"
+        );
+        assert_eq!(yaku("123123m456p7s <123m ?7s", &env), "[一色三順:2]");
+    }
+
+    #[test]
+    fn uumenchii() {
+        let env = local_env();
+        assert_eq!(
+            yaku("123m456p789s1j555j ?1j", &env),
+            "[役牌: 白:1,五門斉:2]"
+        );
+    }
+
+    #[test]
+    fn daisharin() {
+        let env = local_env();
+        assert_eq!(
+            yaku("2233445566778p ?8p", &env),
+            "[大車輪:!1][大車輪:!1][大車輪:!1][大車輪:!1]"
+        );
+    }
+
+    #[test]
+    fn shisanputa() {
+        let mut env = local_env();
+        env.tenho = true;
+        assert_eq!(yaku("147m258p369s1235j !5j", &env), "[十三不塔:!1]");
+        // 国士無双は十三不塔にしない
+        assert_eq!(yaku("19m19p19s1234566j !7j", &env), "[国士無双:!1,地和:!1]");
+
+        // 天和/地和の状況でない場合は和了形ではない
+        env.tenho = false;
+        assert_eq!(yaku("147m258p369s1235j !5j", &env), "");
+        // ローカル役なし
+        env.tenho = true;
+        env.rules.local_yaku = false;
+        assert_eq!(yaku("147m258p369s1235j !5j", &env), "");
+    }
+
+    #[test]
+    fn daichisei() {
+        let env = local_env();
+        assert_eq!(yaku("1122334455667j ?7j", &env), "[字一色:!1,大七星:!1]");
+    }
+
+    #[test]
+    fn benikujaku() {
+        let env = local_env();
+        assert_eq!(yaku("11155s777s99s777j ?5s", &env), "[紅孔雀:!1]");
+    }
+
+    #[test]
+    fn ishi_no_ue_ni_mo_sannen() {
+        let mut env = local_env();
+        env.richi = Some(RichiType::Daburi);
+        env.haitei = true;
+        assert_eq!(yaku("1112345m345s123p ?6m", &env), "[石の上にも三年:!1]");
+    }
+
+    #[test]
+    fn hyakumangoku() {
+        let env = local_env();
+        assert_eq!(
+            yaku("6667778889999m ?6m", &env),
+            "[百万石:!1][百万石:!1][百万石:!1][百万石:!1]"
+        );
+    }
+}
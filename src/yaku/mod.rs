@@ -2,11 +2,19 @@ use crate::{
     agari::Agari,
     agari_type::AgariType,
     env::Env,
+    payment::Payment,
     rank::{Rank, RankKind},
+    rules::{Renho, SanmaTsumo},
 };
+#[cfg(feature = "wasm")]
 use js_sys::Array;
 use num_bigint::BigUint;
-use std::{borrow::Cow, cmp::Ordering, iter};
+#[cfg(feature = "serde")]
+use serde::Serialize;
+#[cfg(feature = "wasm")]
+use std::iter;
+use std::{borrow::Cow, cmp::Ordering};
+#[cfg(feature = "wasm")]
 use wasm_bindgen::prelude::*;
 
 mod common;
@@ -15,14 +23,31 @@ mod fan1;
 mod fan2;
 mod fan3;
 mod fan6;
+mod local;
+mod pao;
 mod yakuman;
 
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 #[derive(Debug, Clone)]
 pub struct Yaku {
     name: Cow<'static, str>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(serialize_with = "crate::serde_str::serialize")
+    )]
     point: BigUint,
+    /// 本場・供託を含めた収入
+    #[cfg_attr(
+        feature = "serde",
+        serde(serialize_with = "crate::serde_str::serialize")
+    )]
+    total: BigUint,
+    /// 他家ごとの支払い
+    payments: Vec<Payment>,
     fu: u32,
+    /// 符の内訳
+    fu_detail: Vec<(String, u32)>,
     rank: Rank,
     detail: Vec<(&'static str, Rank)>,
 }
@@ -51,45 +76,110 @@ impl Ord for Yaku {
 }
 
 impl Yaku {
-    pub(crate) fn new(agari: &Agari, env: &Env) -> Self {
-        let fu = agari.compute_fu(env);
+    pub fn new(agari: &Agari, env: &Env) -> Self {
+        let (fu, fu_detail) = agari.compute_fu_detail(env);
         let (rank, detail) = judge(agari, env);
-        let (name, point) = compute_point(agari, env, fu, rank);
+        let (name, point, payments) = compute_point(agari, env, fu, rank);
+        // 供託は1本につき1000点
+        let kyotaku = if payments.is_empty() {
+            0
+        } else {
+            env.kyotaku * 1000
+        };
+        let total = payments.iter().map(Payment::amount).sum::<BigUint>() + kyotaku;
         Self {
             name,
             point,
+            total,
+            payments,
             fu,
+            fu_detail,
             rank,
             detail,
         }
     }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn point(&self) -> &BigUint {
+        &self.point
+    }
+
+    pub fn total(&self) -> &BigUint {
+        &self.total
+    }
+
+    pub fn payments(&self) -> &[Payment] {
+        &self.payments
+    }
+
+    pub fn fu(&self) -> u32 {
+        self.fu
+    }
+
+    pub fn fu_detail(&self) -> &[(String, u32)] {
+        &self.fu_detail
+    }
+
+    pub fn rank(&self) -> Rank {
+        self.rank
+    }
+
+    pub fn detail(&self) -> &[(&'static str, Rank)] {
+        &self.detail
+    }
 }
 
+#[cfg(feature = "wasm")]
 #[wasm_bindgen]
 impl Yaku {
     #[wasm_bindgen(getter = name)]
     pub fn name_js(&self) -> String {
-        self.name.to_string()
+        self.name().to_string()
     }
 
     #[wasm_bindgen(getter = point)]
     pub fn point_js(&self) -> String {
-        self.point.to_string()
+        self.point().to_string()
+    }
+
+    #[wasm_bindgen(getter = total)]
+    pub fn total_js(&self) -> String {
+        self.total().to_string()
+    }
+
+    #[wasm_bindgen(getter = payments)]
+    pub fn payments_js(&self) -> Box<[JsValue]> {
+        self.payments().iter().cloned().map(JsValue::from).collect()
     }
 
     #[wasm_bindgen(getter = fu)]
     pub fn fu_js(&self) -> u32 {
-        self.fu
+        self.fu()
+    }
+
+    #[wasm_bindgen(getter = fuDetail)]
+    pub fn fu_detail_js(&self) -> Array {
+        self.fu_detail()
+            .iter()
+            .map(|(name, fu)| {
+                iter::once(JsValue::from(name.as_str()))
+                    .chain(iter::once(JsValue::from(*fu)))
+                    .collect::<Array>()
+            })
+            .collect()
     }
 
     #[wasm_bindgen(getter = rank)]
     pub fn rank_js(&self) -> Rank {
-        self.rank
+        self.rank()
     }
 
     #[wasm_bindgen(getter = detail)]
     pub fn detail_js(&self) -> Array {
-        self.detail
+        self.detail()
             .iter()
             .map(|(name, rank)| {
                 iter::once(JsValue::from(*name))
@@ -109,14 +199,26 @@ impl Yaku {
     }
 }
 
+#[cfg(all(feature = "wasm", feature = "serde"))]
+#[wasm_bindgen]
+impl Yaku {
+    #[wasm_bindgen(js_name = "toJSON")]
+    pub fn to_json_js(&self) -> Result<JsValue, JsValue> {
+        crate::serde_str::to_js(self)
+    }
+}
+
 fn judge(agari: &Agari, env: &Env) -> (Rank, Vec<(&'static str, Rank)>) {
     let mut total_fan = 0;
     let mut list = vec![];
 
     // 役満
-    if let Some((yakuman_count, mut yakuman_list)) =
-        judge_list(agari, env, YAKUMAN_LIST, Rank::new_yakuman)
-    {
+    if let Some((yakuman_count, mut yakuman_list)) = judge_list(
+        agari,
+        env,
+        with_local(env, YAKUMAN_LIST, LOCAL_YAKUMAN_LIST),
+        Rank::new_yakuman,
+    ) {
         if !env.aotenjo {
             return (Rank::new_yakuman(yakuman_count), yakuman_list);
         }
@@ -125,7 +227,12 @@ fn judge(agari: &Agari, env: &Env) -> (Rank, Vec<(&'static str, Rank)>) {
     }
 
     // 通常の役
-    if let Some((yaku_fan, mut yaku_list)) = judge_list(agari, env, YAKU_LIST, Rank::new_fan) {
+    if let Some((yaku_fan, mut yaku_list)) = judge_list(
+        agari,
+        env,
+        with_local(env, YAKU_LIST, LOCAL_YAKU_LIST),
+        Rank::new_fan,
+    ) {
         total_fan += yaku_fan;
         list.append(&mut yaku_list);
     }
@@ -142,10 +249,20 @@ fn judge(agari: &Agari, env: &Env) -> (Rank, Vec<(&'static str, Rank)>) {
     (Rank::new_fan(total_fan), list)
 }
 
-fn judge_list(
+/// ローカル役が有効な場合は `local` を `list` の後に加える
+fn with_local<'a>(
+    env: &Env,
+    list: &'a [JudgeFn],
+    local: &'a [JudgeFn],
+) -> impl Iterator<Item = &'a JudgeFn> {
+    let local = if env.rules.local_yaku { local } else { &[] };
+    list.iter().chain(local)
+}
+
+fn judge_list<'a>(
     agari: &Agari,
     env: &Env,
-    list: &[JudgeFn],
+    list: impl IntoIterator<Item = &'a JudgeFn>,
     gen: impl Fn(u32) -> Rank,
 ) -> Option<(u32, Vec<(&'static str, Rank)>)> {
     let mut total_rank = 0;
@@ -159,24 +276,72 @@ fn judge_list(
     (!res.is_empty()).then(|| (total_rank, res))
 }
 
-fn compute_point(agari: &Agari, env: &Env, fu: u32, rank: Rank) -> (Cow<'static, str>, BigUint) {
+/// 点数と他家ごとの支払いを計算する
+///
+/// 支払いには本場を含む (ロンの場合は放銃者が1本場につき300点、ツモの場合は各自100点)。
+/// 包 (責任払い) の役満の場合は包の他家の支払いを含む。
+///
+/// 三人麻雀のツモあがりでは、北家の支払い分をツモ損の場合は誰も支払わず、
+/// 北家折半の場合は残りの2人で折半する (本場も同様に1人150点となる)。
+fn compute_point(
+    agari: &Agari,
+    env: &Env,
+    fu: u32,
+    rank: Rank,
+) -> (Cow<'static, str>, BigUint, Vec<Payment>) {
     let (name, base_point) = compute_base_point(env, fu, rank);
-    let is_oya = env.jikaze.number() == 1;
+    if base_point == BigUint::from(0u32) {
+        return (name, base_point, vec![]);
+    }
+
     let is_ron = agari.tehai().agari_hai().type_() == AgariType::Ron;
-    fn round(point: BigUint) -> BigUint {
-        (point + 99u32) / 100u32 * 100u32
-    }
-    let point = match (is_oya, is_ron) {
-        (true, true) => round(base_point * 6u32),
-        (false, true) => round(base_point * 4u32),
-        (true, false) => round(base_point * 2u32) * 3u32,
-        (false, false) => round(&base_point * 2u32) + round(base_point) * 2u32,
+    let setsuhan = env.rules.sanma == Some(SanmaTsumo::Setsuhan);
+    let pao = match rank.kind() {
+        RankKind::Yakuman(count) => pao::pao(agari, env).map(|pao| (*count, pao)),
+        RankKind::Fan(_) => None,
+    };
+    let mut payments = match (pao, is_ron) {
+        (Some((count, (tacha, pao_count))), _) => {
+            Payment::with_pao(env, is_ron, count, tacha, pao_count)
+        }
+        (None, true) => Payment::ron(env, &base_point),
+        (None, false) => Payment::tsumo(env, &base_point),
     };
+    let point = payments.iter().map(Payment::amount).sum();
 
-    (name, point)
+    let honba_point = match (is_ron, setsuhan) {
+        (true, _) => 300,
+        (false, true) => 150,
+        (false, false) => 100,
+    };
+    if is_ron {
+        // 包の場合も本場は放銃者が支払う
+        payments[0].add_amount(env.honba * honba_point);
+    } else if payments.len() == 1 {
+        // 包の他家のみが支払う場合は本場もすべて支払う
+        payments[0].add_amount(env.honba * honba_point * env.tacha().count() as u32);
+    } else {
+        for payment in &mut payments {
+            payment.add_amount(env.honba * honba_point);
+        }
+    }
+
+    (name, point, payments)
 }
 
+/// 基本点を計算する
+///
+/// 人和を満貫とするルールでは、人和は他の役と複合せず、人和を除いた点数が満貫に満たない場合は満貫とする。
 fn compute_base_point(env: &Env, fu: u32, rank: Rank) -> (Cow<'static, str>, BigUint) {
+    let (name, point) = compute_base_point_by_rank(env, fu, rank);
+    let renho = env.renho && env.rules.renho == Renho::Mangan;
+    if renho && matches!(rank.kind(), RankKind::Fan(_)) && point < BigUint::from(2000u32) {
+        return ("満貫".into(), 2000u32.into());
+    }
+    (name, point)
+}
+
+fn compute_base_point_by_rank(env: &Env, fu: u32, rank: Rank) -> (Cow<'static, str>, BigUint) {
     if env.aotenjo {
         match rank.kind() {
             RankKind::Fan(0) => return ("無役".into(), 0u32.into()),
@@ -189,7 +354,10 @@ fn compute_base_point(env: &Env, fu: u32, rank: Rank) -> (Cow<'static, str>, Big
         RankKind::Fan(0) => ("無役".into(), 0),
         RankKind::Fan(fan) if *fan <= 5 => {
             let base_point = fu * 2u32.pow(fan + 2);
-            if base_point <= 2000 {
+            // 切り上げ満貫: 4飜30符、3飜60符 (基本点1920点)
+            if env.rules.kiriage_mangan && base_point == 1920 {
+                ("満貫".into(), 2000)
+            } else if base_point <= 2000 {
                 ("".into(), base_point)
             } else {
                 ("満貫".into(), 2000)
@@ -198,7 +366,8 @@ fn compute_base_point(env: &Env, fu: u32, rank: Rank) -> (Cow<'static, str>, Big
         RankKind::Fan(fan) if *fan <= 7 => ("跳満".into(), 3000),
         RankKind::Fan(fan) if *fan <= 10 => ("倍満".into(), 4000),
         RankKind::Fan(fan) if *fan <= 12 => ("三倍満".into(), 6000),
-        RankKind::Fan(_) => ("数え役満".into(), 8000),
+        RankKind::Fan(_) if env.rules.kazoe_yakuman => ("数え役満".into(), 8000),
+        RankKind::Fan(_) => ("三倍満".into(), 6000),
         RankKind::Yakuman(1) => ("役満".into(), 8000),
         RankKind::Yakuman(n) => (format!("{}倍役満", n).into(), (n * 8000)),
     };
@@ -236,6 +405,7 @@ const YAKU_LIST: &[JudgeFn] = &[
     fan3::junchan,
     fan3::ryanpeko,
     fan6::chinitsu,
+    yakuman::renho_mangan,
 ];
 
 const YAKUMAN_LIST: &[JudgeFn] = &[
@@ -250,6 +420,288 @@ const YAKUMAN_LIST: &[JudgeFn] = &[
     yakuman::sukantsu,
     yakuman::churen,
     yakuman::tenho,
+    yakuman::renho,
+];
+
+const LOCAL_YAKU_LIST: &[JudgeFn] = &[local::sanrenko, local::isshoku_sanjun, local::uumenchii];
+
+const LOCAL_YAKUMAN_LIST: &[JudgeFn] = &[
+    local::daisharin,
+    local::surenko,
+    local::shisanputa,
+    local::daichisei,
+    local::benikujaku,
+    local::ishi_no_ue_ni_mo_sannen,
+    local::hyakumangoku,
 ];
 
-const DORA_LIST: &[JudgeFn] = &[dora::dora, dora::uradora, dora::akadora];
+const DORA_LIST: &[JudgeFn] = &[dora::dora, dora::uradora, dora::akadora, dora::nukidora];
+
+#[cfg(test)]
+mod test {
+    use super::{
+        common::test::{point, yaku},
+        *,
+    };
+    use crate::{hai::Hai, rules::Rules, tehai::Tehai};
+    use std::str::FromStr;
+
+    #[test]
+    fn compute_base_point() {
+        fn base_point(rules: Rules, fu: u32, rank: Rank) -> String {
+            let mut env = Env::new(Hai::from_str("1j").unwrap(), Hai::from_str("2j").unwrap());
+            env.rules = rules;
+            let (name, point) = super::compute_base_point(&env, fu, rank);
+            format!("{}{}", name, point)
+        }
+
+        // 切り上げ満貫
+        assert_eq!(base_point(Rules::default(), 30, Rank::new_fan(4)), "1920");
+        assert_eq!(base_point(Rules::default(), 60, Rank::new_fan(3)), "1920");
+        assert_eq!(
+            base_point(Rules::m_league(), 30, Rank::new_fan(4)),
+            "満貫2000"
+        );
+        assert_eq!(
+            base_point(Rules::m_league(), 60, Rank::new_fan(3)),
+            "満貫2000"
+        );
+        assert_eq!(base_point(Rules::m_league(), 50, Rank::new_fan(3)), "1600");
+
+        // 数え役満
+        assert_eq!(
+            base_point(Rules::default(), 30, Rank::new_fan(13)),
+            "数え役満8000"
+        );
+        assert_eq!(
+            base_point(Rules::wrc(), 30, Rank::new_fan(13)),
+            "三倍満6000"
+        );
+        assert_eq!(
+            base_point(Rules::wrc(), 30, Rank::new_yakuman(1)),
+            "役満8000"
+        );
+    }
+
+    #[test]
+    fn compute_total() {
+        fn total(s: &str, env: &Env) -> String {
+            let tehai = Tehai::from_str(s).unwrap();
+            let yaku = tehai
+                .to_agari_combinations()
+                .iter()
+                .map(|agari| Yaku::new(agari, env))
+                .max()
+                .unwrap();
+            format!("{}/{}", yaku.point, yaku.total)
+        }
+
+        let mut env = Env::new(Hai::from_str("1j").unwrap(), Hai::from_str("2j").unwrap());
+        assert_eq!(total("1112345m345s123p ?6m", &env), "1000/1000");
+
+        // ロンの場合は1本場につき300点
+        env.honba = 2;
+        assert_eq!(total("1112345m345s123p ?6m", &env), "1000/1600");
+        // ツモの場合は3人から100点ずつ
+        assert_eq!(total("1112345m345s123p !6m", &env), "1500/2100");
+        // 供託は1本につき1000点
+        env.kyotaku = 3;
+        assert_eq!(total("1112345m345s123p ?6m", &env), "1000/4600");
+
+        // 無役の場合は収入なし
+        assert_eq!(total("1145m345s123p <123m ?6m", &env), "0/0");
+    }
+
+    #[test]
+    fn akadora_limit() {
+        // 赤5萬が3枚あっても、ルールの枚数 (萬子1枚) までしか数えない
+        let mut env = Env::new(Hai::from_str("1j").unwrap(), Hai::from_str("2j").unwrap());
+        assert_eq!(point("5$5$5$m345p345s2399p !1p", &env), "2000");
+        env.rules.akadora = [3, 0, 0];
+        assert_eq!(point("5$5$5$m345p345s2399p !1p", &env), "7900");
+        env.rules.akadora = [0, 1, 1];
+        assert_eq!(point("5$5$5$m345p345s2399p !1p", &env), "1100");
+    }
+
+    #[test]
+    fn renho_mangan() {
+        let mut env = Env::new(Hai::from_str("1j").unwrap(), Hai::from_str("2j").unwrap());
+        env.renho = true;
+
+        // 人和のみでも満貫
+        assert_eq!(yaku("11145m345s123p99p ?6m", &env), "[人和:0]");
+        assert_eq!(point("11145m345s123p99p ?6m", &env), "満貫8000");
+        // 他の役の飜数には加算しない
+        assert_eq!(yaku("1112345m345s123p ?6m", &env), "[平和:1,人和:0]");
+        assert_eq!(point("1112345m345s123p ?6m", &env), "満貫8000");
+        // 人和を除いた点数が満貫を超える場合はその点数 (清一色・一気通貫で倍満)
+        assert_eq!(point("12345678911m55m ?5m", &env), "倍満16000");
+        env.dora = vec![Hai::from_str("4m").unwrap()];
+        assert_eq!(point("1112345m345s123p ?6m", &env), "満貫8000");
+
+        // 人和なし
+        env.rules.renho = Renho::Off;
+        assert_eq!(yaku("1112345m345s123p ?6m", &env), "[平和:1,ドラ:1]");
+        assert_eq!(point("1112345m345s123p ?6m", &env), "2000");
+    }
+
+    #[test]
+    fn payments() {
+        fn payments(s: &str, env: &Env) -> String {
+            let tehai = Tehai::from_str(s).unwrap();
+            let yaku = tehai
+                .to_agari_combinations()
+                .iter()
+                .map(|agari| Yaku::new(agari, env))
+                .max()
+                .unwrap();
+            yaku.payments
+                .iter()
+                .map(|payment| payment.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        }
+
+        // 子のツモあがりは親が2倍支払う
+        let mut env = Env::new(Hai::from_str("1j").unwrap(), Hai::from_str("2j").unwrap());
+        assert_eq!(
+            payments("1112345m345s123p !6m", &env),
+            "上家(親): 700点, 対面: 400点, 下家: 400点",
+        );
+        env.jikaze = Hai::from_str("4j").unwrap();
+        assert_eq!(
+            payments("1112345m345s123p !6m", &env),
+            "上家: 400点, 対面: 400点, 下家(親): 700点",
+        );
+        // 親のツモあがり
+        env.jikaze = Hai::from_str("1j").unwrap();
+        assert_eq!(
+            payments("1112345m345s123p !6m", &env),
+            "上家: 700点, 対面: 700点, 下家: 700点",
+        );
+        // ロンあがり
+        assert_eq!(payments("1112345m345s123p ?6m", &env), "放銃者: 1500点");
+
+        // 本場を含む
+        env.honba = 1;
+        assert_eq!(payments("1112345m345s123p ?6m", &env), "放銃者: 1800点");
+        assert_eq!(
+            payments("1112345m345s123p !6m", &env),
+            "上家: 800点, 対面: 800点, 下家: 800点",
+        );
+
+        // 無役の場合は支払いなし
+        assert_eq!(payments("1145m345s123p <123m ?6m", &env), "");
+    }
+
+    #[test]
+    fn sanma_payments() {
+        fn payments(s: &str, env: &Env) -> String {
+            let tehai = Tehai::from_str_with_rules(s, &env.rules).unwrap();
+            let yaku = tehai
+                .to_agari_combinations()
+                .iter()
+                .map(|agari| Yaku::new(agari, env))
+                .max()
+                .unwrap();
+            yaku.payments
+                .iter()
+                .map(|payment| payment.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        }
+
+        // ツモ損: 北家の支払い分はなくなる
+        let mut env = Env::new(Hai::from_str("1j").unwrap(), Hai::from_str("2j").unwrap());
+        env.rules.sanma = Some(SanmaTsumo::Tsumozon);
+        assert_eq!(
+            payments("99m123p456p789s23s !1s", &env),
+            "上家(親): 700点, 下家: 400点",
+        );
+        env.jikaze = Hai::from_str("3j").unwrap();
+        assert_eq!(
+            payments("99m123p456p789s23s !1s", &env),
+            "上家: 400点, 下家(親): 700点",
+        );
+        env.jikaze = Hai::from_str("1j").unwrap();
+        assert_eq!(
+            payments("99m123p456p789s23s !1s", &env),
+            "上家: 700点, 下家: 700点",
+        );
+        // ロンあがりは四人麻雀と同じ
+        assert_eq!(payments("99m123p456p789s23s ?1s", &env), "放銃者: 1500点");
+
+        // 北家折半: 北家の支払い分を折半する
+        env.rules.sanma = Some(SanmaTsumo::Setsuhan);
+        assert_eq!(
+            payments("99m123p456p789s23s !1s", &env),
+            "上家: 1000点, 下家: 1000点",
+        );
+        env.jikaze = Hai::from_str("2j").unwrap();
+        assert_eq!(
+            payments("99m123p456p789s23s !1s", &env),
+            "上家(親): 800点, 下家: 500点",
+        );
+
+        // 本場は北家の分も折半する
+        env.honba = 1;
+        assert_eq!(
+            payments("99m123p456p789s23s !1s", &env),
+            "上家(親): 950点, 下家: 650点",
+        );
+        env.rules.sanma = Some(SanmaTsumo::Tsumozon);
+        assert_eq!(
+            payments("99m123p456p789s23s !1s", &env),
+            "上家(親): 800点, 下家: 500点",
+        );
+    }
+
+    #[test]
+    fn kiriage_mangan() {
+        fn point(s: &str, env: &Env) -> String {
+            let tehai = Tehai::from_str(s).unwrap();
+            let yaku = tehai
+                .to_agari_combinations()
+                .iter()
+                .map(|agari| Yaku::new(agari, env))
+                .max()
+                .unwrap();
+            let payments = yaku
+                .payments
+                .iter()
+                .map(|payment| payment.to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("{}{} {}符 [{}]", yaku.name, yaku.point, yaku.fu, payments)
+        }
+
+        // 平和・断么九・一盃口・ドラ1 (4飜30符)
+        let mut env = Env::new(Hai::from_str("1j").unwrap(), Hai::from_str("2j").unwrap());
+        env.dora = vec![Hai::from_str("5s").unwrap()];
+        assert_eq!(
+            point("234m223344p67s88s ?5s", &env),
+            "7700 30符 [放銃者: 7700点]"
+        );
+        env.rules.kiriage_mangan = true;
+        assert_eq!(
+            point("234m223344p67s88s ?5s", &env),
+            "満貫8000 30符 [放銃者: 8000点]"
+        );
+        assert_eq!(
+            point("234m223344p67s88s !5s", &env),
+            "満貫8000 20符 [上家(親): 4000点, 対面: 2000点, 下家: 2000点]"
+        );
+
+        // 親の場合
+        env.jikaze = Hai::from_str("1j").unwrap();
+        assert_eq!(
+            point("234m223344p67s88s ?5s", &env),
+            "満貫12000 30符 [放銃者: 12000点]"
+        );
+        env.rules.kiriage_mangan = false;
+        assert_eq!(
+            point("234m223344p67s88s ?5s", &env),
+            "11600 30符 [放銃者: 11600点]"
+        );
+    }
+}
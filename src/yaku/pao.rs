@@ -0,0 +1,166 @@
+use super::yakuman;
+use crate::{
+    agari::Agari,
+    env::Env,
+    furo::{Furo, FuroKind},
+    hai::Hai,
+    mentsu::MentsuKind,
+    tacha::Tacha,
+};
+
+/// 包 (責任払い) の対象となる他家と、包の対象となる役満の数
+///
+/// 大三元/大四喜は3つ目の三元牌/4つ目の風牌を鳴かせた他家が、
+/// 四槓子は4つ目の槓子を大明槓させた他家が責任を負う。
+/// 副露は鳴いた順に並んでいるものとする。
+pub(super) fn pao(agari: &Agari, env: &Env) -> Option<(Tacha, u32)> {
+    if let Some((_, count)) = yakuman::daisangen(agari, env) {
+        if let Some(tacha) = last_kotsu_tacha(agari, Hai::is_sangenpai) {
+            return Some((tacha, count));
+        }
+    }
+    if let Some((_, count)) = yakuman::daisushi(agari, env) {
+        if let Some(tacha) = last_kotsu_tacha(agari, Hai::is_kazehai) {
+            return Some((tacha, count));
+        }
+    }
+    if let Some((_, count)) = yakuman::sukantsu(agari, env) {
+        let last_kan = agari.tehai().furo().iter().rev().find(|furo| {
+            matches!(
+                furo.kind(),
+                FuroKind::Kakan { .. } | FuroKind::Daiminkan { .. } | FuroKind::Ankan { .. }
+            )
+        })?;
+        if let FuroKind::Daiminkan { tacha, .. } = last_kan.kind() {
+            return Some((*tacha, count));
+        }
+    }
+    None
+}
+
+/// `pred` を満たす刻子/槓子が揃った最後の副露の取得元
+///
+/// 最後の副露より後にあがり牌で刻子が揃った場合や、最後の副露が暗槓の場合は `None`。
+fn last_kotsu_tacha(agari: &Agari, pred: impl Fn(&Hai) -> bool) -> Option<Tacha> {
+    let machi_mentsu = agari.machi_mentsu();
+    if matches!(machi_mentsu.kind(), MentsuKind::Kotsu(..)) && pred(&machi_mentsu.head()) {
+        return None;
+    }
+    let head = |furo: &Furo| furo.iter().next().unwrap();
+    let last = agari
+        .tehai()
+        .furo()
+        .iter()
+        .rev()
+        .find(|furo| pred(&head(furo)))?;
+    match *last.kind() {
+        FuroKind::Pon { tacha, .. }
+        | FuroKind::Kakan { tacha, .. }
+        | FuroKind::Daiminkan { tacha, .. } => Some(tacha),
+        FuroKind::Chi { .. } | FuroKind::Ankan { .. } => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::Yaku;
+    use crate::{env::Env, hai::Hai, tacha::Tacha, tehai::Tehai};
+    use std::str::FromStr;
+
+    fn payments(s: &str, env: &Env) -> String {
+        let tehai = Tehai::from_str(s).unwrap();
+        let yaku = tehai
+            .to_agari_combinations()
+            .iter()
+            .map(|agari| Yaku::new(agari, env))
+            .max()
+            .unwrap();
+        yaku.payments
+            .iter()
+            .map(|payment| payment.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    #[test]
+    fn daisangen() {
+        let mut env = Env::new(Hai::from_str("1j").unwrap(), Hai::from_str("2j").unwrap());
+        // ツモあがりは包の他家が全額支払う
+        assert_eq!(
+            payments("1m234p ^555j <666j >777j !1m", &env),
+            "下家(包): 32000点"
+        );
+        // ロンあがりは放銃者と折半する
+        assert_eq!(
+            payments("1m234p ^555j <666j >777j ?1m", &env),
+            "放銃者: 16000点, 下家(包): 16000点"
+        );
+        env.hoju = Some(Tacha::Toimen);
+        assert_eq!(
+            payments("1m234p ^555j <666j >777j ?1m", &env),
+            "対面: 16000点, 下家(包): 16000点"
+        );
+        // 包の他家からのロンあがりは全額支払う
+        env.hoju = Some(Tacha::Shimocha);
+        assert_eq!(
+            payments("1m234p ^555j <666j >777j ?1m", &env),
+            "下家(包): 32000点"
+        );
+
+        // あがり牌で3つ目の刻子が揃った場合は包にならない
+        env.hoju = None;
+        assert_eq!(
+            payments("11m234p77j ^555j <666j ?7j", &env),
+            "放銃者: 32000点"
+        );
+
+        // 暗槓で3つ目の刻子が揃った場合は包にならない
+        assert_eq!(
+            payments("1m234p ^555j <666j 7777j !1m", &env),
+            "上家(親): 16000点, 対面: 8000点, 下家: 8000点"
+        );
+    }
+
+    #[test]
+    fn multiple_yakuman() {
+        let mut env = Env::new(Hai::from_str("1j").unwrap(), Hai::from_str("2j").unwrap());
+        // 包の対象外の役満 (字一色) は通常通り支払う
+        assert_eq!(
+            payments("2224j ^555j <666j >777j ?4j", &env),
+            "放銃者: 48000点, 下家(包): 16000点"
+        );
+        assert_eq!(
+            payments("2224j ^555j <666j >777j !4j", &env),
+            "上家(親): 16000点, 対面: 8000点, 下家(包): 40000点"
+        );
+
+        // 本場はロンの場合は放銃者が、包の他家のみが支払うツモの場合は包の他家が支払う
+        env.honba = 1;
+        assert_eq!(
+            payments("2224j ^555j <666j >777j ?4j", &env),
+            "放銃者: 48300点, 下家(包): 16000点"
+        );
+        assert_eq!(
+            payments("1m234p ^555j <666j >777j !1m", &env),
+            "下家(包): 32300点"
+        );
+    }
+
+    #[test]
+    fn daisushi_and_sukantsu() {
+        let env = Env::new(Hai::from_str("1j").unwrap(), Hai::from_str("2j").unwrap());
+        assert_eq!(
+            payments("1m333j <111j ^222j >444j !1m", &env),
+            "下家(包): 64000点"
+        );
+        assert_eq!(
+            payments("1m ^1111p <2222p 3333s ^4444s ?1m", &env),
+            "放銃者: 16000点, 対面(包): 16000点"
+        );
+        // 最後の槓子が暗槓の場合は包にならない
+        assert_eq!(
+            payments("1m ^1111p <2222p ^3333s 4444s ?1m", &env),
+            "放銃者: 32000点"
+        );
+    }
+}
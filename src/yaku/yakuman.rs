@@ -1,19 +1,38 @@
 use super::common;
-use crate::{agari::Agari, env::Env, hai_category::HaiCategory, mentsu::MentsuKind};
-
-pub(super) fn kokushi(agari: &Agari, _env: &Env) -> Option<(&'static str, u32)> {
-    (agari.num_toitsu() == 1 && agari.num_single() == 12).then(|| {
-        if let MentsuKind::Toitsu(..) = agari.machi_mentsu().kind() {
-            ("国士無双十三面待ち", 2)
-        } else {
-            ("国士無双", 1)
-        }
-    })
+use crate::{
+    agari::Agari,
+    agari_type::AgariType,
+    env::Env,
+    hai::Hai,
+    hai_category::HaiCategory,
+    mentsu::MentsuKind,
+    rules::{Renho, SuankoTanki},
+};
+
+pub(super) fn kokushi(agari: &Agari, env: &Env) -> Option<(&'static str, u32)> {
+    (agari.num_toitsu() == 1 && agari.num_single() == 12 && !common::is_shisanputa(agari)).then(
+        || {
+            if let (MentsuKind::Toitsu(..), true) =
+                (agari.machi_mentsu().kind(), env.rules.kokushi_13men_double)
+            {
+                ("国士無双十三面待ち", 2)
+            } else {
+                ("国士無双", 1)
+            }
+        },
+    )
 }
 
-pub(super) fn suanko(agari: &Agari, _env: &Env) -> Option<(&'static str, u32)> {
+pub(super) fn suanko(agari: &Agari, env: &Env) -> Option<(&'static str, u32)> {
     (agari.num_anko() + agari.num_ankan() == 4).then(|| {
-        if let MentsuKind::Toitsu(..) = agari.machi_mentsu().kind() {
+        let is_tanki = matches!(agari.machi_mentsu().kind(), MentsuKind::Toitsu(..));
+        let is_tsumo = agari.tehai().agari_hai().type_() == AgariType::Tsumo;
+        let is_double = match env.rules.suanko_tanki {
+            SuankoTanki::Single => false,
+            SuankoTanki::TsumoDouble => is_tsumo,
+            SuankoTanki::Double => true,
+        };
+        if is_tanki && is_double {
             ("四暗刻単騎", 2)
         } else {
             ("四暗刻", 1)
@@ -35,18 +54,22 @@ pub(super) fn shosushi(agari: &Agari, _env: &Env) -> Option<(&'static str, u32)>
         .then(|| ("小四喜", 1))
 }
 
-pub(super) fn daisushi(agari: &Agari, _env: &Env) -> Option<(&'static str, u32)> {
-    (common::kazehai_bits(agari) == 0b1111).then(|| ("大四喜", 2))
+pub(super) fn daisushi(agari: &Agari, env: &Env) -> Option<(&'static str, u32)> {
+    (common::kazehai_bits(agari) == 0b1111)
+        .then(|| ("大四喜", if env.rules.daisushi_double { 2 } else { 1 }))
 }
 
-pub(super) fn ryuiso(agari: &Agari, _env: &Env) -> Option<(&'static str, u32)> {
+pub(super) fn ryuiso(agari: &Agari, env: &Env) -> Option<(&'static str, u32)> {
+    let is_hatsu = |hai: &Hai| hai.category() == HaiCategory::Jihai && hai.number() == 6;
+    if env.rules.ryuiso_hatsu_required && !agari.tehai().all_hai().any(|hai| is_hatsu(&hai)) {
+        return None;
+    }
     agari
         .tehai()
         .all_hai()
         .all(|hai| match (hai.category(), hai.number()) {
             (HaiCategory::Souzu, n) if n == 2 || n == 3 || n == 4 || n == 6 || n == 8 => true,
-            (HaiCategory::Jihai, 6) => true,
-            _ => false,
+            _ => is_hatsu(&hai),
         })
         .then(|| ("緑一色", 1))
 }
@@ -59,11 +82,11 @@ pub(super) fn sukantsu(agari: &Agari, _env: &Env) -> Option<(&'static str, u32)>
     (agari.num_ankan() + agari.num_minkan() == 4).then(|| ("四槓子", 1))
 }
 
-pub(super) fn churen(agari: &Agari, _env: &Env) -> Option<(&'static str, u32)> {
+pub(super) fn churen(agari: &Agari, env: &Env) -> Option<(&'static str, u32)> {
     if !agari.tehai().is_menzen()
         || (agari.num_manzu() != agari.num_hai()
-            && agari.num_souzu() != agari.num_hai()
-            && agari.num_pinzu() != agari.num_hai())
+            && (env.rules.churen_manzu_only
+                || (agari.num_souzu() != agari.num_hai() && agari.num_pinzu() != agari.num_hai())))
     {
         return None;
     }
@@ -85,15 +108,16 @@ pub(super) fn churen(agari: &Agari, _env: &Env) -> Option<(&'static str, u32)> {
         }
     }
     assert!(extra.is_some());
-    if extra.unwrap().is_same(&agari.tehai().agari_hai().hai()) {
+    if env.rules.junsei_churen_double && extra.unwrap().is_same(&agari.tehai().agari_hai().hai()) {
         Some(("純正九蓮宝燈", 2))
     } else {
         Some(("九蓮宝燈", 1))
     }
 }
 
-pub(super) fn tenho(_agari: &Agari, env: &Env) -> Option<(&'static str, u32)> {
-    env.tenho.then(|| {
+pub(super) fn tenho(agari: &Agari, env: &Env) -> Option<(&'static str, u32)> {
+    // 十三不塔は和了形ではないため天和/地和にならない
+    (env.tenho && !common::is_shisanputa(agari)).then(|| {
         if env.jikaze == env.bakaze {
             ("天和", 1)
         } else {
@@ -102,15 +126,29 @@ pub(super) fn tenho(_agari: &Agari, env: &Env) -> Option<(&'static str, u32)> {
     })
 }
 
+pub(super) fn renho(_agari: &Agari, env: &Env) -> Option<(&'static str, u32)> {
+    (env.renho && env.rules.renho == Renho::Yakuman).then(|| ("人和", 1))
+}
+
+/// 人和 (満貫扱い)
+///
+/// 飜数には加算しない。満貫に満たない場合の切り上げは点数の計算で行う。
+pub(super) fn renho_mangan(_agari: &Agari, env: &Env) -> Option<(&'static str, u32)> {
+    (env.renho && env.rules.renho == Renho::Mangan).then(|| ("人和", 0))
+}
+
 #[cfg(test)]
 mod test {
-    use super::{super::common::test::yaku, *};
+    use super::{
+        super::common::test::{point, yaku},
+        *,
+    };
     use crate::hai::Hai;
     use std::str::FromStr;
 
     #[test]
     fn kokushi() {
-        let env = Env::new_empty(Hai::from_str("1j").unwrap(), Hai::from_str("1j").unwrap());
+        let env = Env::new(Hai::from_str("1j").unwrap(), Hai::from_str("1j").unwrap());
         // 単騎待ち
         assert_eq!(yaku("19m19p19s1234577j ?6j", &env), "[国士無双:!1]");
         // 十三面待ち
@@ -123,12 +161,17 @@ mod test {
         assert_eq!(
             yaku("19m19p19s1234567j !1j", &env),
             "[国士無双十三面待ち:!2]",
-        )
+        );
+
+        // 十三面待ちをダブル役満としないルール
+        let mut env = Env::new(Hai::from_str("1j").unwrap(), Hai::from_str("1j").unwrap());
+        env.rules.kokushi_13men_double = false;
+        assert_eq!(yaku("19m19p19s1234567j ?1m", &env), "[国士無双:!1]");
     }
 
     #[test]
     fn suanko() {
-        let env = Env::new_empty(Hai::from_str("1j").unwrap(), Hai::from_str("1j").unwrap());
+        let env = Env::new(Hai::from_str("1j").unwrap(), Hai::from_str("1j").unwrap());
         // 双碰待ちの場合
         assert_eq!(yaku("333s111s555m7799p !9p", &env), "[四暗刻:!1]");
         assert_eq!(yaku("333s111s555m7799p ?9p", &env), "[対対和:2,三暗刻:2]");
@@ -155,9 +198,18 @@ mod test {
         // 注意を要する単騎待ち
         assert_eq!(yaku("666888s222m1112p ?2p", &env), "[四暗刻単騎:!2]");
         assert_eq!(yaku("666888s222m1112p ?3p", &env), "[三暗刻:2]");
+        // 四暗刻単騎をダブル役満としないルール
+        let mut env = Env::new(Hai::from_str("1j").unwrap(), Hai::from_str("1j").unwrap());
+        env.rules.suanko_tanki = SuankoTanki::Single;
+        assert_eq!(yaku("111j333555m333p5s ?5s", &env), "[四暗刻:!1]");
+        assert_eq!(yaku("111j333555m333p5s !5s", &env), "[四暗刻:!1]");
+        // ツモあがりの場合のみダブル役満とするルール
+        env.rules.suanko_tanki = SuankoTanki::TsumoDouble;
+        assert_eq!(yaku("111j333555m333p5s ?5s", &env), "[四暗刻:!1]");
+        assert_eq!(yaku("111j333555m333p5s !5s", &env), "[四暗刻単騎:!2]");
 
         // 青天井時、三暗刻、対対和とは複合しない
-        let mut env = Env::new_empty(Hai::from_str("1j").unwrap(), Hai::from_str("1j").unwrap());
+        let mut env = Env::new(Hai::from_str("1j").unwrap(), Hai::from_str("1j").unwrap());
         env.aotenjo = true;
         assert_eq!(yaku("333s111s555m7999p ?7p", &env), "[四暗刻単騎:!2]");
         // 四暗刻単騎と門前清自摸和は複合する
@@ -171,7 +223,7 @@ mod test {
 
     #[test]
     fn daisangen() {
-        let env = Env::new_empty(Hai::from_str("2j").unwrap(), Hai::from_str("2j").unwrap());
+        let env = Env::new(Hai::from_str("2j").unwrap(), Hai::from_str("2j").unwrap());
         // 高目大三元のケース
         assert_eq!(yaku("11p456m55566777j ?6j", &env), "[大三元:!1]");
         assert_eq!(
@@ -198,7 +250,7 @@ mod test {
 
     #[test]
     fn tsuiso() {
-        let env = Env::new_empty(Hai::from_str("2j").unwrap(), Hai::from_str("2j").unwrap());
+        let env = Env::new(Hai::from_str("2j").unwrap(), Hai::from_str("2j").unwrap());
         // 副露した字一色
         assert_eq!(yaku("1112277j ^444j ^555j ?2j", &env), "[字一色:!1]");
         // 四喜和との複合
@@ -213,7 +265,7 @@ mod test {
         // 字一色七対子
         assert_eq!(yaku("1122334455667j ?7j", &env), "[字一色:!1]");
 
-        let mut env = Env::new_empty(Hai::from_str("2j").unwrap(), Hai::from_str("2j").unwrap());
+        let mut env = Env::new(Hai::from_str("2j").unwrap(), Hai::from_str("2j").unwrap());
         env.aotenjo = true;
         // 青天井時、混全帯么九とは複合しない
         assert_eq!(
@@ -226,7 +278,7 @@ mod test {
 
     #[test]
     fn shosushi() {
-        let env = Env::new_empty(Hai::from_str("2j").unwrap(), Hai::from_str("2j").unwrap());
+        let env = Env::new(Hai::from_str("2j").unwrap(), Hai::from_str("2j").unwrap());
         // 単騎待ちのケース
         assert_eq!(yaku("1112224j234m <333j !4j", &env), "[小四喜:!1]");
         // 双碰待ちのケース
@@ -236,7 +288,7 @@ mod test {
         assert_eq!(yaku("44j23m <333j >222j ^111j ?1m", &env), "[小四喜:!1]");
         assert_eq!(yaku("44j23m <333j >222j ^111j ?4m", &env), "[小四喜:!1]");
 
-        let mut env = Env::new_empty(Hai::from_str("2j").unwrap(), Hai::from_str("2j").unwrap());
+        let mut env = Env::new(Hai::from_str("2j").unwrap(), Hai::from_str("2j").unwrap());
         env.aotenjo = true;
         // 青天井時、役牌、混一色と複合する
         assert_eq!(
@@ -247,9 +299,16 @@ mod test {
 
     #[test]
     fn daisushi() {
-        let env = Env::new_empty(Hai::from_str("2j").unwrap(), Hai::from_str("2j").unwrap());
+        let env = Env::new(Hai::from_str("2j").unwrap(), Hai::from_str("2j").unwrap());
         // 双碰待ちのケース
         assert_eq!(yaku("33344j22s >222j ^111j ?4j", &env), "[大四喜:!2]");
+        // ダブル役満としないルール
+        let mut single_env = env.clone();
+        single_env.rules.daisushi_double = false;
+        assert_eq!(
+            yaku("33344j22s >222j ^111j ?4j", &single_env),
+            "[大四喜:!1]"
+        );
         assert_eq!(yaku("33344j22s >222j ^111j ?2s", &env), "[小四喜:!1]");
         // 単騎待ちのケース
         assert_eq!(
@@ -257,7 +316,7 @@ mod test {
             "[字一色:!1,大四喜:!2]",
         );
 
-        let mut env = Env::new_empty(Hai::from_str("2j").unwrap(), Hai::from_str("2j").unwrap());
+        let mut env = Env::new(Hai::from_str("2j").unwrap(), Hai::from_str("2j").unwrap());
         env.aotenjo = true;
         // 青天井時、役牌、対対和とは複合しない
         assert_eq!(
@@ -268,7 +327,7 @@ mod test {
 
     #[test]
     fn ryuiso() {
-        let env = Env::new_empty(Hai::from_str("2j").unwrap(), Hai::from_str("2j").unwrap());
+        let env = Env::new(Hai::from_str("2j").unwrap(), Hai::from_str("2j").unwrap());
         // 緑一色が確定しているケース
         assert_eq!(yaku("22334466s66j >888s ?6s", &env), "[緑一色:!1]");
         assert_eq!(yaku("22334466s66j >888s ?6j", &env), "[緑一色:!1]");
@@ -281,8 +340,16 @@ mod test {
         // 發のないケース
         assert_eq!(yaku("4466s <888s <333s ^222s ?4s", &env), "[緑一色:!1]");
         assert_eq!(yaku("4466s <888s <333s ^222s ?6s", &env), "[緑一色:!1]");
+        // 發を必須とするルール
+        let mut hatsu_env = env.clone();
+        hatsu_env.rules.ryuiso_hatsu_required = true;
+        assert_eq!(yaku("22334466s66j >888s ?6s", &hatsu_env), "[緑一色:!1]");
+        assert_eq!(
+            yaku("4466s <888s <333s ^222s ?4s", &hatsu_env),
+            "[断么九:1,対対和:2,清一色:5]",
+        );
 
-        let mut env = Env::new_empty(Hai::from_str("2j").unwrap(), Hai::from_str("2j").unwrap());
+        let mut env = Env::new(Hai::from_str("2j").unwrap(), Hai::from_str("2j").unwrap());
         env.aotenjo = true;
         // 發無し緑一色を認めているため、青天井時は混一色と複合する
         assert_eq!(yaku("22334466s66j >888s ?6s", &env), "[緑一色:!1,混一色:2]");
@@ -300,7 +367,7 @@ mod test {
 
     #[test]
     fn chinro() {
-        let env = Env::new_empty(Hai::from_str("2j").unwrap(), Hai::from_str("2j").unwrap());
+        let env = Env::new(Hai::from_str("2j").unwrap(), Hai::from_str("2j").unwrap());
         // 副露したケース
         assert_eq!(yaku("11s111p11m <999s ^999m ?1s", &env), "[清老頭:!1]");
         assert_eq!(yaku("11s111p11m <999s ^999m ?1m", &env), "[清老頭:!1]");
@@ -310,7 +377,7 @@ mod test {
             "[四暗刻単騎:!2,清老頭:!1]",
         );
 
-        let mut env = Env::new_empty(Hai::from_str("2j").unwrap(), Hai::from_str("2j").unwrap());
+        let mut env = Env::new(Hai::from_str("2j").unwrap(), Hai::from_str("2j").unwrap());
         env.aotenjo = true;
         // 青天井時、純全帯么九とは複合しない。七対子の4枚使いも認めていないため、対対和とも複合しない
         assert_eq!(yaku("11s111p11m <999s ^999m ?1s", &env), "[清老頭:!1]");
@@ -318,7 +385,7 @@ mod test {
 
     #[test]
     fn sukantsu() {
-        let env = Env::new_empty(Hai::from_str("2j").unwrap(), Hai::from_str("2j").unwrap());
+        let env = Env::new(Hai::from_str("2j").unwrap(), Hai::from_str("2j").unwrap());
         assert_eq!(
             yaku("1m 5555j 3333j ^7777s ^8888p ?1m", &env),
             "[四槓子:!1]",
@@ -331,7 +398,7 @@ mod test {
         );
 
         // 青天井時、三槓子、対対和とは複合しない
-        let mut env = Env::new_empty(Hai::from_str("2j").unwrap(), Hai::from_str("2j").unwrap());
+        let mut env = Env::new(Hai::from_str("2j").unwrap(), Hai::from_str("2j").unwrap());
         env.aotenjo = true;
         assert_eq!(
             yaku("1m 5555j 3333j ^7777s ^8888p ?1m", &env),
@@ -341,7 +408,7 @@ mod test {
 
     #[test]
     fn churen() {
-        let env = Env::new_empty(Hai::from_str("2j").unwrap(), Hai::from_str("2j").unwrap());
+        let env = Env::new(Hai::from_str("2j").unwrap(), Hai::from_str("2j").unwrap());
         assert_eq!(yaku("1113455678999p ?2p", &env), "[九蓮宝燈:!1]");
         assert_eq!(
             yaku("1112345678999m ?1m", &env),
@@ -371,7 +438,17 @@ mod test {
             "[純正九蓮宝燈:!2][純正九蓮宝燈:!2]",
         );
 
-        let mut env = Env::new_empty(Hai::from_str("2j").unwrap(), Hai::from_str("2j").unwrap());
+        // 純正九蓮宝燈をダブル役満としないルール
+        let mut single_env = env.clone();
+        single_env.rules.junsei_churen_double = false;
+        assert_eq!(yaku("1112345678999m ?5m", &single_env), "[九蓮宝燈:!1]");
+        // 萬子のみに限るルール
+        let mut manzu_env = env.clone();
+        manzu_env.rules.churen_manzu_only = true;
+        assert_eq!(yaku("1113455678999m ?2m", &manzu_env), "[九蓮宝燈:!1]");
+        assert_eq!(yaku("1113455678999p ?2p", &manzu_env), "[清一色:6]");
+
+        let mut env = Env::new(Hai::from_str("2j").unwrap(), Hai::from_str("2j").unwrap());
         env.aotenjo = true;
         // 青天井時、清一色とは複合しない
         assert_eq!(yaku("1113455678999p ?2p", &env), "[九蓮宝燈:!1]");
@@ -381,15 +458,28 @@ mod test {
 
     #[test]
     fn tenho() {
-        let mut env = Env::new_empty(Hai::from_str("1j").unwrap(), Hai::from_str("1j").unwrap());
+        let mut env = Env::new(Hai::from_str("1j").unwrap(), Hai::from_str("1j").unwrap());
         env.tenho = true;
         assert_eq!(yaku("1112345m345s123p !6m", &env), "[天和:!1]");
     }
 
     #[test]
     fn chiho() {
-        let mut env = Env::new_empty(Hai::from_str("1j").unwrap(), Hai::from_str("2j").unwrap());
+        let mut env = Env::new(Hai::from_str("1j").unwrap(), Hai::from_str("2j").unwrap());
         env.tenho = true;
         assert_eq!(yaku("1112345m345s123p !6m", &env), "[地和:!1]");
     }
+
+    #[test]
+    fn renho() {
+        let mut env = Env::new(Hai::from_str("1j").unwrap(), Hai::from_str("2j").unwrap());
+        env.renho = true;
+        env.rules.renho = Renho::Yakuman;
+        assert_eq!(yaku("1112345m345s123p ?6m", &env), "[人和:!1]");
+
+        // 満貫扱いの場合は役満にならない
+        env.rules.renho = Renho::Mangan;
+        assert_eq!(yaku("1112345m345s123p ?6m", &env), "[平和:1,人和:0]");
+        assert_eq!(point("1112345m345s123p ?6m", &env), "満貫8000");
+    }
 }
@@ -25,6 +25,8 @@ module.exports = {
     new CopyPlugin({ patterns: [path.resolve(__dirname, "static")] }),
     new WasmPackPlugin({
       crateDirectory: __dirname,
+      // `toJSON`/`fromJSON` で入力状態を保存するため serde を有効にする
+      extraArgs: "-- --features serde",
     }),
   ],
 };